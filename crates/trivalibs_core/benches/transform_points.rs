@@ -0,0 +1,45 @@
+//! Compares `Transform::transform_points` with transforming each point separately.
+//! Run with `cargo bench -p trivalibs_core --bench transform_points`.
+
+use std::{hint::black_box, time::Instant};
+use trivalibs_core::{
+	glam::{vec3, Quat, Vec3},
+	rendering::transform::Transform,
+};
+
+const POINTS: usize = 100_000;
+const RUNS: u32 = 50;
+
+fn measure(name: &str, mut f: impl FnMut()) {
+	f();
+	let start = Instant::now();
+	for _ in 0..RUNS {
+		f();
+	}
+	println!("{name}: {:?} per run", start.elapsed() / RUNS);
+}
+
+fn main() {
+	let transform = Transform::from_xyz(1.0, 2.0, 3.0)
+		.with_rotation(Quat::from_rotation_y(0.5))
+		.with_scale(vec3(2.0, 1.0, 0.5));
+	let points = (0..POINTS)
+		.map(|i| {
+			let i = i as f32;
+			vec3(i.sin(), i.cos(), i * 0.001)
+		})
+		.collect::<Vec<_>>();
+	let mut out = vec![Vec3::ZERO; POINTS];
+
+	measure("transform_point loop", || {
+		for (point, out) in points.iter().zip(out.iter_mut()) {
+			*out = black_box(&transform).transform_point(*point);
+		}
+		black_box(&out);
+	});
+
+	measure("transform_points", || {
+		black_box(&transform).transform_points(&points, &mut out);
+		black_box(&out);
+	});
+}
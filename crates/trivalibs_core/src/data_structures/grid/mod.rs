@@ -1,11 +1,14 @@
+use glam::{vec2, Vec2};
 use lerp::Lerp;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::{cmp::Ordering, collections::BinaryHeap, f32::consts::SQRT_2};
 
 pub trait CoordOpsFn: Copy + Clone {
 	fn adjust_coords(&self, x: i32, y: i32, width: usize, height: usize) -> (usize, usize);
 	fn circle(&self) -> (bool, bool);
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct ClampToEdgeCoordOps;
 impl CoordOpsFn for ClampToEdgeCoordOps {
 	fn adjust_coords(&self, x: i32, y: i32, width: usize, height: usize) -> (usize, usize) {
@@ -19,7 +22,7 @@ impl CoordOpsFn for ClampToEdgeCoordOps {
 }
 pub static CLAMP_TO_EDGE_COORD_OPS: ClampToEdgeCoordOps = ClampToEdgeCoordOps {};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct CircleRowsCoordOps;
 impl CoordOpsFn for CircleRowsCoordOps {
 	fn adjust_coords(&self, x: i32, y: i32, width: usize, height: usize) -> (usize, usize) {
@@ -39,7 +42,7 @@ impl CoordOpsFn for CircleRowsCoordOps {
 }
 pub static CIRCLE_ROWS_COORD_OPS: CircleRowsCoordOps = CircleRowsCoordOps {};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct CircleColsCoordOps;
 impl CoordOpsFn for CircleColsCoordOps {
 	fn adjust_coords(&self, x: i32, y: i32, width: usize, height: usize) -> (usize, usize) {
@@ -59,7 +62,7 @@ impl CoordOpsFn for CircleColsCoordOps {
 }
 pub static CIRCLE_COLS_COORD_OPS: CircleColsCoordOps = CircleColsCoordOps {};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct CircleAllCoordOps;
 impl CoordOpsFn for CircleAllCoordOps {
 	fn adjust_coords(&self, x: i32, y: i32, width: usize, height: usize) -> (usize, usize) {
@@ -86,6 +89,81 @@ impl CoordOpsFn for CircleAllCoordOps {
 }
 pub static CIRCLE_ALL_COORD_OPS: CircleAllCoordOps = CircleAllCoordOps {};
 
+/// The four corner values of a grid quad, together with their grid coordinates.
+pub type QuadWithCoords<T> = ([T; 4], [(i32, i32); 4]);
+
+/// How [`Grid::pad`] fills the added border cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadMode<T> {
+	/// Fill the border with the given value.
+	Constant(T),
+	/// Repeat the nearest edge cell.
+	Edge,
+	/// Continue with the cells from the opposite side.
+	Wrap,
+	/// Mirror the cells at the edge, without repeating the edge cell itself.
+	Reflect,
+}
+
+/// Which neighbours of a cell are reachable in a single step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Connectivity {
+	/// The 4 orthogonally adjacent cells.
+	#[default]
+	VonNeumann,
+	/// The 8 surrounding cells. Diagonal steps cost `sqrt(2)` times the cost of the cell.
+	Moore,
+}
+
+/// Cell of the dijkstra queue, ordered by smallest distance first.
+struct FieldEntry {
+	dist: f32,
+	x: usize,
+	y: usize,
+}
+
+impl PartialEq for FieldEntry {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for FieldEntry {}
+
+impl PartialOrd for FieldEntry {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for FieldEntry {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.dist.total_cmp(&self.dist)
+	}
+}
+
+fn pad_index(i: i32, len: usize, mode: &PadMode<impl Copy>) -> usize {
+	let len = len as i32;
+	let i = match mode {
+		PadMode::Constant(_) | PadMode::Edge => i.clamp(0, len - 1),
+		PadMode::Wrap => i.rem_euclid(len),
+		PadMode::Reflect => {
+			if len == 1 {
+				0
+			} else {
+				let period = 2 * (len - 1);
+				let i = i.rem_euclid(period);
+				if i < len {
+					i
+				} else {
+					period - i
+				}
+			}
+		}
+	};
+	i as usize
+}
+
 /// A two dimensional grid structure. Grid quad rotation assumes 0,0 is the lower left corner.
 pub struct Grid<T, A>
 where
@@ -110,6 +188,7 @@ where
 	grid: &'a Grid<T, A>,
 }
 
+/// Creates an empty grid, that clamps out of bounds coordinates to the nearest edge.
 pub fn make_grid<T: Clone + Copy>() -> Grid<T, ClampToEdgeCoordOps> {
 	Grid::new(CLAMP_TO_EDGE_COORD_OPS)
 }
@@ -151,6 +230,21 @@ where
 		}
 	}
 
+	/// Creates a grid with the given coord ops, calling `f` with the coordinates of every cell to create its value.
+	pub fn from_fn_with_coord_ops<F: FnMut(i32, i32) -> T>(
+		coord_ops: A,
+		width: usize,
+		height: usize,
+		mut f: F,
+	) -> Self {
+		let mut grid = Grid::new(coord_ops);
+		for x in 0..width {
+			let col = (0..height).map(|y| f(x as i32, y as i32)).collect();
+			grid.add_col(col);
+		}
+		grid
+	}
+
 	pub fn get(&self, x: i32, y: i32) -> &T {
 		let (x, y) = self.coord_ops.adjust_coords(x, y, self.width, self.height);
 		&self.vertices[x][y]
@@ -245,6 +339,11 @@ where
 		}
 	}
 
+	/// Returns the values of the grid as rows, where `rows[y][x]` is the value at `x`, `y`.
+	pub fn to_rows(&self) -> Vec<Vec<T>> {
+		(0..self.height as i32).map(|y| self.row(y)).collect()
+	}
+
 	pub fn map<B, F>(&self, f: F) -> Grid<B, A>
 	where
 		B: Clone + Copy,
@@ -261,6 +360,92 @@ where
 		grid
 	}
 
+	/// Copies a `width` x `height` window starting at `x`, `y` into a new grid.
+	/// Reads outside of the grid follow the coord ops of this grid.
+	pub fn sub_grid(&self, x: i32, y: i32, width: usize, height: usize) -> Grid<T, A> {
+		Grid::from_fn_with_coord_ops(self.coord_ops, width, height, |sub_x, sub_y| {
+			*self.get(x + sub_x, y + sub_y)
+		})
+	}
+
+	/// Writes all values of `src` into this grid, with the `src` origin placed at `dst_x`, `dst_y`.
+	/// Values that would land outside of this grid are skipped.
+	pub fn blit<B: CoordOpsFn>(&mut self, src: &Grid<T, B>, dst_x: i32, dst_y: i32) {
+		for src_x in 0..src.width {
+			let x = dst_x + src_x as i32;
+			if x < 0 || x >= self.width as i32 {
+				continue;
+			}
+			for src_y in 0..src.height {
+				let y = dst_y + src_y as i32;
+				if y < 0 || y >= self.height as i32 {
+					continue;
+				}
+				self.vertices[x as usize][y as usize] = src.vertices[src_x][src_y];
+			}
+		}
+	}
+
+	/// Returns a new grid rotated by 90 degrees clockwise, with width and height swapped.
+	/// Assumes 0,0 is the lower left corner.
+	pub fn rotate_cw(&self) -> Self {
+		let w = self.width;
+		Grid::from_fn_with_coord_ops(self.coord_ops, self.height, self.width, |x, y| {
+			self.vertices[w - 1 - y as usize][x as usize]
+		})
+	}
+
+	/// Returns a new grid rotated by 90 degrees counter clockwise, with width and height swapped.
+	/// Assumes 0,0 is the lower left corner.
+	pub fn rotate_ccw(&self) -> Self {
+		let h = self.height;
+		Grid::from_fn_with_coord_ops(self.coord_ops, self.height, self.width, |x, y| {
+			self.vertices[y as usize][h - 1 - x as usize]
+		})
+	}
+
+	/// Returns a new grid rotated by 180 degrees.
+	pub fn rotate_180(&self) -> Self {
+		self.flip_x().flip_y()
+	}
+
+	/// Returns a new grid mirrored horizontally, so that the first column becomes the last.
+	pub fn flip_x(&self) -> Self {
+		let w = self.width;
+		Grid::from_fn_with_coord_ops(self.coord_ops, self.width, self.height, |x, y| {
+			self.vertices[w - 1 - x as usize][y as usize]
+		})
+	}
+
+	/// Returns a new grid mirrored vertically, so that the first row becomes the last.
+	pub fn flip_y(&self) -> Self {
+		let h = self.height;
+		Grid::from_fn_with_coord_ops(self.coord_ops, self.width, self.height, |x, y| {
+			self.vertices[x as usize][h - 1 - y as usize]
+		})
+	}
+
+	/// Returns a new grid, extended by `border` cells on every side.
+	/// The grid must not be empty, unless the mode is [`PadMode::Constant`].
+	pub fn pad(&self, border: usize, mode: PadMode<T>) -> Self {
+		let b = border as i32;
+		let (w, h) = (self.width as i32, self.height as i32);
+		Grid::from_fn_with_coord_ops(
+			self.coord_ops,
+			self.width + 2 * border,
+			self.height + 2 * border,
+			|x, y| {
+				let (x, y) = (x - b, y - b);
+				if let PadMode::Constant(val) = mode {
+					if x < 0 || y < 0 || x >= w || y >= h {
+						return val;
+					}
+				}
+				self.vertices[pad_index(x, self.width, &mode)][pad_index(y, self.height, &mode)]
+			},
+		)
+	}
+
 	pub fn quad_count(&self) -> (usize, usize) {
 		let (circle_cols, circle_rows) = self.coord_ops.circle();
 		let w = if circle_cols {
@@ -314,42 +499,178 @@ where
 		grid
 	}
 
-	/// Get clockwise oriented quads. Grid quad orientation assumes 0,0 is the lower left corner.
-	pub fn to_ccw_quads<'a>(&self) -> Vec<[T; 4]> {
+	/// Corner coordinates of all quads, in the orientation of the public quad getters.
+	fn quad_coords(&self, clockwise: bool) -> Vec<[(i32, i32); 4]> {
 		let (w, h) = self.quad_count();
 		let mut quads = vec![];
 		for w_i in 0..w {
 			for h_i in 0..h {
 				let x = w_i as i32;
 				let y = h_i as i32;
-				quads.push([
-					*self.get(x, y),
-					*self.get(x + 1, y),
-					*self.get(x + 1, y + 1),
-					*self.get(x, y + 1),
-				])
+				let corners = if clockwise {
+					[(x, y), (x, y + 1), (x + 1, y + 1), (x + 1, y)]
+				} else {
+					[(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)]
+				};
+				quads.push(corners.map(|(x, y)| {
+					let (x, y) = self.coord_ops.adjust_coords(x, y, self.width, self.height);
+					(x as i32, y as i32)
+				}));
 			}
 		}
 		quads
 	}
 
+	fn quad_values(&self, coords: &[(i32, i32); 4]) -> [T; 4] {
+		coords.map(|(x, y)| *self.get(x, y))
+	}
+
+	/// Get counter-clockwise oriented quads. Grid quad orientation assumes 0,0 is the lower left corner.
+	pub fn to_ccw_quads(&self) -> Vec<[T; 4]> {
+		self.quad_coords(false)
+			.iter()
+			.map(|coords| self.quad_values(coords))
+			.collect()
+	}
+
 	/// Get clockwise oriented quads. Grid quad orientation assumes 0,0 is the lower left corner.
-	pub fn to_cw_quads<'a>(&self) -> Vec<[T; 4]> {
-		let (w, h) = self.quad_count();
-		let mut quads = vec![];
-		for w_i in 0..w {
-			for h_i in 0..h {
-				let x = w_i as i32;
-				let y = h_i as i32;
-				quads.push([
-					*self.get(x, y),
-					*self.get(x, y + 1),
-					*self.get(x + 1, y + 1),
-					*self.get(x + 1, y),
-				])
+	pub fn to_cw_quads(&self) -> Vec<[T; 4]> {
+		self.quad_coords(true)
+			.iter()
+			.map(|coords| self.quad_values(coords))
+			.collect()
+	}
+
+	/// Same quads as [`Grid::to_ccw_quads`], together with the grid coordinates of each corner.
+	/// Coordinates of wrapping grids are already adjusted to cells within the grid.
+	pub fn to_quads_with_coords(&self) -> Vec<QuadWithCoords<T>> {
+		self.quad_coords(false)
+			.into_iter()
+			.map(|coords| (self.quad_values(&coords), coords))
+			.collect()
+	}
+
+	/// Minimum accumulated cost from every cell to the nearest of the `goals`,
+	/// moving between orthogonally adjacent cells. See [`Grid::dijkstra_field_with_connectivity`].
+	pub fn dijkstra_field<F: Fn(&T) -> Option<f32>>(
+		&self,
+		goals: &[(i32, i32)],
+		cost: F,
+	) -> Grid<f32, A> {
+		self.dijkstra_field_with_connectivity(goals, Connectivity::VonNeumann, cost)
+	}
+
+	/// Minimum accumulated cost from every cell to the nearest of the `goals`.
+	/// `cost` returns the non negative cost of entering a cell, or `None` for impassable cells.
+	/// Impassable and unreachable cells get a distance of `f32::INFINITY`.
+	/// Neighbours and goal coordinates follow the coord ops, so e.g. toroidal grids wrap around.
+	pub fn dijkstra_field_with_connectivity<F: Fn(&T) -> Option<f32>>(
+		&self,
+		goals: &[(i32, i32)],
+		connectivity: Connectivity,
+		cost: F,
+	) -> Grid<f32, A> {
+		let mut field =
+			Grid::from_fn_with_coord_ops(self.coord_ops, self.width, self.height, |_, _| {
+				f32::INFINITY
+			});
+		let mut queue = BinaryHeap::new();
+
+		for (x, y) in goals {
+			let goal = self.vertex(*x, *y);
+			if cost(&goal.val).is_some() {
+				field.vertices[goal.x][goal.y] = 0.0;
+				queue.push(FieldEntry {
+					dist: 0.0,
+					x: goal.x,
+					y: goal.y,
+				});
 			}
 		}
-		quads
+
+		while let Some(FieldEntry { dist, x, y }) = queue.pop() {
+			if dist > field.vertices[x][y] {
+				continue;
+			}
+			for ((dx, dy), next) in self.vertex(x as i32, y as i32).steps(connectivity) {
+				let Some(step) = cost(&next.val) else {
+					continue;
+				};
+				let step = if dx != 0 && dy != 0 {
+					step * SQRT_2
+				} else {
+					step
+				};
+				let next_dist = dist + step;
+				if next_dist < field.vertices[next.x][next.y] {
+					field.vertices[next.x][next.y] = next_dist;
+					queue.push(FieldEntry {
+						dist: next_dist,
+						x: next.x,
+						y: next.y,
+					});
+				}
+			}
+		}
+
+		field
+	}
+}
+
+impl<T, A> Grid<T, A>
+where
+	T: Clone + Copy + Default,
+	A: CoordOpsFn,
+{
+	fn new_filled(coord_ops: A, width: usize, height: usize) -> Self {
+		Self::from_fn_with_coord_ops(coord_ops, width, height, |_, _| T::default())
+	}
+}
+
+impl<T> Grid<T, ClampToEdgeCoordOps>
+where
+	T: Clone + Copy,
+{
+	/// Creates a clamped grid, calling `f` with the coordinates of every cell to create its value.
+	pub fn from_fn<F: FnMut(i32, i32) -> T>(width: usize, height: usize, f: F) -> Self {
+		Self::from_fn_with_coord_ops(CLAMP_TO_EDGE_COORD_OPS, width, height, f)
+	}
+
+	/// Creates a clamped grid from a list of rows, where `rows[y][x]` becomes the value at `x`, `y`.
+	/// Panics if the rows are empty or not all of the same length.
+	pub fn from_rows(rows: &[&[T]]) -> Self {
+		assert!(
+			rows.iter().all(|row| !row.is_empty()),
+			"grid rows must not be empty"
+		);
+		let mut grid = make_grid();
+		for row in rows {
+			grid.add_row(row.to_vec());
+		}
+		grid
+	}
+}
+
+impl<T> Grid<T, ClampToEdgeCoordOps>
+where
+	T: Clone + Copy + Default,
+{
+	/// Creates a grid filled with default values, that clamps out of bounds coordinates
+	/// to the nearest edge, e.g. for image filters.
+	/// This is the same edge behaviour as used by [`make_grid`].
+	pub fn new_clamped(width: usize, height: usize) -> Self {
+		Self::new_filled(CLAMP_TO_EDGE_COORD_OPS, width, height)
+	}
+}
+
+impl<T> Grid<T, CircleAllCoordOps>
+where
+	T: Clone + Copy + Default,
+{
+	/// Creates a grid filled with default values, that wraps out of bounds coordinates
+	/// around in both directions, e.g. for cellular automata.
+	pub fn new_toroidal(width: usize, height: usize) -> Self {
+		Self::new_filled(CIRCLE_ALL_COORD_OPS, width, height)
 	}
 }
 
@@ -394,6 +715,80 @@ where
 	}
 }
 
+impl<A: CoordOpsFn> Grid<f32, A> {
+	/// Normalized direction from every cell of a distance field, e.g. from [`Grid::dijkstra_field`],
+	/// towards its orthogonal neighbour with the lowest distance.
+	/// Goals, local minima and unreachable cells get a zero vector.
+	pub fn flow_directions(&self) -> Grid<Vec2, A> {
+		self.flow_directions_with_connectivity(Connectivity::VonNeumann)
+	}
+
+	/// Like [`Grid::flow_directions`], considering the neighbours of the given connectivity.
+	pub fn flow_directions_with_connectivity(&self, connectivity: Connectivity) -> Grid<Vec2, A> {
+		self.map(|v| {
+			if !v.val.is_finite() {
+				return Vec2::ZERO;
+			}
+			v.steps(connectivity)
+				.into_iter()
+				.filter(|(_, next)| next.val < v.val)
+				.min_by(|(_, a), (_, b)| a.val.total_cmp(&b.val))
+				.map_or(Vec2::ZERO, |((dx, dy), _)| {
+					vec2(dx as f32, dy as f32).normalize()
+				})
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct GridData<T> {
+	width: usize,
+	height: usize,
+	values: Vec<T>,
+}
+
+/// Serializes width, height and all values column by column.
+/// The coord ops are not serialized, but recreated with their default on deserialization.
+impl<T, A> Serialize for Grid<T, A>
+where
+	T: Clone + Copy + Serialize,
+	A: CoordOpsFn,
+{
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		GridData {
+			width: self.width,
+			height: self.height,
+			values: self.vertices.iter().flatten().copied().collect(),
+		}
+		.serialize(serializer)
+	}
+}
+
+impl<'de, T, A> Deserialize<'de> for Grid<T, A>
+where
+	T: Clone + Copy + Deserialize<'de>,
+	A: CoordOpsFn + Default,
+{
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let data = GridData::<T>::deserialize(deserializer)?;
+		if data.values.len() != data.width * data.height {
+			return Err(D::Error::custom(format!(
+				"expected {} grid values for a {}x{} grid, got {}",
+				data.width * data.height,
+				data.width,
+				data.height,
+				data.values.len()
+			)));
+		}
+
+		let mut grid = Grid::new(A::default());
+		for x in 0..data.width {
+			grid.add_col(data.values[x * data.height..(x + 1) * data.height].to_vec());
+		}
+		Ok(grid)
+	}
+}
+
 impl<T: Copy, A: CoordOpsFn> PartialEq for Vertex<'_, T, A> {
 	fn eq(&self, other: &Self) -> bool {
 		self.x == other.x && self.y == other.y
@@ -427,6 +822,63 @@ where
 	pub fn bottom(&self) -> Option<Self> {
 		self.next(0, 1)
 	}
+
+	/// The up to 4 orthogonally adjacent vertices in the order top, left, right, bottom.
+	/// Edge behaviour depends on the grid coord ops, e.g. clamped grids return less neighbours at the edges.
+	pub fn von_neumann_neighbours(&self) -> Vec<Self> {
+		[self.top(), self.left(), self.right(), self.bottom()]
+			.into_iter()
+			.flatten()
+			.collect()
+	}
+
+	/// The up to 8 surrounding vertices, row by row from the top left to the bottom right.
+	/// Edge behaviour depends on the grid coord ops, e.g. clamped grids return less neighbours at the edges.
+	pub fn moore_neighbours(&self) -> Vec<Self> {
+		let top = self.top();
+		let bottom = self.bottom();
+		[
+			top.and_then(|v| v.left()),
+			top,
+			top.and_then(|v| v.right()),
+			self.left(),
+			self.right(),
+			bottom.and_then(|v| v.left()),
+			bottom,
+			bottom.and_then(|v| v.right()),
+		]
+		.into_iter()
+		.flatten()
+		.collect()
+	}
+
+	/// Neighbours of the connectivity, together with the offset of the step to reach them.
+	fn steps(&self, connectivity: Connectivity) -> Vec<((i32, i32), Self)> {
+		let orthogonal = [(0, -1), (-1, 0), (1, 0), (0, 1)]
+			.into_iter()
+			.filter_map(|(dx, dy)| self.next(dx, dy).map(|v| ((dx, dy), v)));
+		match connectivity {
+			Connectivity::VonNeumann => orthogonal.collect(),
+			Connectivity::Moore => {
+				orthogonal
+					.chain([(-1, -1), (1, -1), (-1, 1), (1, 1)].into_iter().filter_map(
+						|(dx, dy)| {
+							let v = self.next(dx, 0)?.next(0, dy)?;
+							Some(((dx, dy), v))
+						},
+					))
+					.collect()
+			}
+		}
+	}
+
+	/// Counts the moore neighbours whose value satisfies the predicate.
+	pub fn count_neighbours<F: Fn(&T) -> bool>(&self, predicate: F) -> usize {
+		self.moore_neighbours()
+			.iter()
+			.filter(|v| predicate(&v.val))
+			.count()
+	}
 }
 
 #[cfg(test)]
@@ -65,6 +65,74 @@ fn get_set_circle_all() {
 	assert_eq!(grid.get(1, 1), &Coord(7, 7));
 }
 
+#[test]
+fn new_clamped_and_toroidal() {
+	let mut clamped = Grid::<i32, _>::new_clamped(3, 3);
+	let mut toroidal = Grid::<i32, _>::new_toroidal(3, 3);
+
+	assert_eq!(clamped.width, 3);
+	assert_eq!(clamped.height, 3);
+	assert_eq!(toroidal.width, 3);
+	assert_eq!(toroidal.height, 3);
+	assert_eq!(*clamped.get(1, 2), 0);
+
+	for x in 0..3 {
+		for y in 0..3 {
+			clamped.set(x, y, x * 10 + y);
+			toroidal.set(x, y, x * 10 + y);
+		}
+	}
+
+	assert_eq!(*clamped.get(4, 4), 22);
+	assert_eq!(*clamped.get(-1, 1), 1);
+	assert_eq!(*toroidal.get(4, 4), 11);
+	assert_eq!(*toroidal.get(-1, 1), 21);
+}
+
+#[test]
+fn from_fn() {
+	let grid = Grid::from_fn(4, 3, |x, y| (x + y) % 2 == 0);
+
+	assert_eq!(grid.width, 4);
+	assert_eq!(grid.height, 3);
+	for x in 0..4 {
+		for y in 0..3 {
+			assert_eq!(*grid.get(x, y), (x + y) % 2 == 0);
+		}
+	}
+	assert_eq!(grid.row(0), [true, false, true, false]);
+	assert_eq!(grid.row(1), [false, true, false, true]);
+
+	let grid = Grid::from_fn_with_coord_ops(CIRCLE_ALL_COORD_OPS, 3, 3, Coord);
+	assert_eq!(grid.get(4, 4), &Coord(1, 1));
+}
+
+#[test]
+fn serialize_roundtrip() {
+	let grid = Grid::from_fn_with_coord_ops(CIRCLE_ALL_COORD_OPS, 3, 3, |x, y| x * 10 + y);
+
+	let json = serde_json::to_string(&grid).unwrap();
+	assert_eq!(
+		json,
+		r#"{"width":3,"height":3,"values":[0,1,2,10,11,12,20,21,22]}"#
+	);
+
+	let restored: Grid<i32, CircleAllCoordOps> = serde_json::from_str(&json).unwrap();
+	assert_eq!(restored.width, 3);
+	assert_eq!(restored.height, 3);
+	for x in 0..3 {
+		for y in 0..3 {
+			assert_eq!(restored.get(x, y), grid.get(x, y));
+		}
+	}
+	assert_eq!(restored.get(4, 4), &11);
+
+	let invalid = serde_json::from_str::<Grid<i32, ClampToEdgeCoordOps>>(
+		r#"{"width":2,"height":2,"values":[0,1,2]}"#,
+	);
+	assert!(invalid.is_err());
+}
+
 #[test]
 fn fill_grid_rows_cols() {
 	let mut grid1 = make_grid();
@@ -159,6 +227,108 @@ fn grid_vertices() {
 	assert_eq!(v.val, Coord(1, 1));
 }
 
+#[test]
+fn neighbours() {
+	let mut grid = Grid::<bool, _>::new_toroidal(5, 5);
+	// glider
+	for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+		grid.set(x, y, true);
+	}
+
+	let alive = |v: &bool| *v;
+
+	assert_eq!(grid.vertex(1, 1).count_neighbours(alive), 5);
+	assert_eq!(grid.vertex(0, 0).count_neighbours(alive), 1);
+	assert_eq!(grid.vertex(2, 2).count_neighbours(alive), 2);
+	assert_eq!(grid.vertex(1, 3).count_neighbours(alive), 3);
+	assert_eq!(grid.vertex(4, 4).count_neighbours(alive), 0);
+
+	assert_eq!(grid.vertex(0, 0).moore_neighbours().len(), 8);
+	assert_eq!(grid.vertex(0, 0).von_neumann_neighbours().len(), 4);
+
+	let v = grid.vertex(0, 0).von_neumann_neighbours();
+	assert_eq!((v[0].x, v[0].y), (0, 4));
+	assert_eq!((v[1].x, v[1].y), (4, 0));
+	assert_eq!((v[2].x, v[2].y), (1, 0));
+	assert_eq!((v[3].x, v[3].y), (0, 1));
+
+	let clamped = fill_grid(make_grid());
+	assert_eq!(clamped.vertex(0, 0).moore_neighbours().len(), 3);
+	assert_eq!(clamped.vertex(1, 0).moore_neighbours().len(), 5);
+	assert_eq!(clamped.vertex(1, 1).moore_neighbours().len(), 8);
+	assert_eq!(clamped.vertex(2, 2).von_neumann_neighbours().len(), 2);
+}
+
+#[test]
+fn sub_grid_and_blit() {
+	let mut grid = Grid::from_fn(4, 4, |x, y| x * 10 + y);
+
+	let sub = grid.sub_grid(1, 2, 2, 2);
+	assert_eq!(sub.width, 2);
+	assert_eq!(sub.height, 2);
+	assert_eq!(sub.col(0), &vec![12, 13]);
+	assert_eq!(sub.col(1), &vec![22, 23]);
+
+	grid.blit(&sub, 0, 0);
+	assert_eq!(grid.row(0), [12, 22, 20, 30]);
+	assert_eq!(grid.row(1), [13, 23, 21, 31]);
+	assert_eq!(grid.row(2), [2, 12, 22, 32]);
+
+	grid.blit(&sub, 3, -1);
+	assert_eq!(grid.row(0), [12, 22, 20, 13]);
+	assert_eq!(grid.row(1), [13, 23, 21, 31]);
+
+	let wrapped = Grid::from_fn_with_coord_ops(CIRCLE_ALL_COORD_OPS, 3, 3, |x, y| x * 10 + y);
+	let sub = wrapped.sub_grid(2, 2, 2, 2);
+	assert_eq!(sub.col(0), &vec![22, 20]);
+	assert_eq!(sub.col(1), &vec![2, 0]);
+}
+
+fn cols<A: CoordOpsFn>(grid: &Grid<i32, A>) -> Vec<Vec<i32>> {
+	(0..grid.width as i32)
+		.map(|x| grid.col(x).clone())
+		.collect()
+}
+
+#[test]
+fn rotate_and_flip() {
+	// 2x3, 0,0 in the lower left corner:
+	// 2 12
+	// 1 11
+	// 0 10
+	let grid = Grid::from_fn(2, 3, |x, y| x * 10 + y);
+
+	// 0  1  2
+	// 10 11 12
+	let cw = grid.rotate_cw();
+	assert_eq!((cw.width, cw.height), (3, 2));
+	assert_eq!(cols(&cw), [vec![10, 0], vec![11, 1], vec![12, 2]]);
+	assert_eq!(*cw.get(0, 1), *grid.get(0, 0));
+	assert_eq!(*cw.get(2, 1), *grid.get(0, 2));
+
+	// 12 11 10
+	// 2  1  0
+	let ccw = grid.rotate_ccw();
+	assert_eq!((ccw.width, ccw.height), (3, 2));
+	assert_eq!(cols(&ccw), [vec![2, 12], vec![1, 11], vec![0, 10]]);
+	assert_eq!(*ccw.get(2, 0), *grid.get(0, 0));
+	assert_eq!(*ccw.get(0, 0), *grid.get(0, 2));
+
+	assert_eq!(cols(&cw.rotate_ccw()), cols(&grid));
+	assert_eq!(cols(&ccw.rotate_cw()), cols(&grid));
+	assert_eq!(cols(&cw.rotate_cw()), cols(&grid.rotate_180()));
+	assert_eq!(cols(&grid.rotate_180()), [vec![12, 11, 10], vec![2, 1, 0]]);
+
+	let flipped_x = grid.flip_x();
+	assert_eq!((flipped_x.width, flipped_x.height), (2, 3));
+	assert_eq!(cols(&flipped_x), [vec![10, 11, 12], vec![0, 1, 2]]);
+
+	let flipped_y = grid.flip_y();
+	assert_eq!((flipped_y.width, flipped_y.height), (2, 3));
+	assert_eq!(cols(&flipped_y), [vec![2, 1, 0], vec![12, 11, 10]]);
+	assert_eq!(cols(&flipped_y.flip_y()), cols(&grid));
+}
+
 #[test]
 fn test_grid_map() {
 	let grid1 = fill_grid(make_grid());
@@ -303,3 +473,179 @@ fn subdivide() {
 	assert_eq!(*grid4.get(0, 2), CoordF(0.0, 1.0));
 	assert_eq!(*grid4.get(0, 3), CoordF(0.0, 0.5));
 }
+
+#[test]
+fn quads_with_coords() {
+	let grid = Grid::from_fn(2, 2, Coord);
+
+	let quads = grid.to_quads_with_coords();
+	assert_eq!(
+		quads,
+		vec![(
+			[Coord(0, 0), Coord(1, 0), Coord(1, 1), Coord(0, 1)],
+			[(0, 0), (1, 0), (1, 1), (0, 1)]
+		)]
+	);
+	assert_eq!(grid.to_ccw_quads(), vec![quads[0].0]);
+
+	let mut grid = Grid::from_fn_with_coord_ops(CIRCLE_COLS_COORD_OPS, 2, 2, Coord);
+	grid.set(1, 0, Coord(5, 5));
+	let quads = grid.to_quads_with_coords();
+	assert_eq!(quads.len(), 2);
+	assert_eq!(quads[1].1, [(1, 0), (0, 0), (0, 1), (1, 1)]);
+	assert_eq!(quads[1].0[0], Coord(5, 5));
+}
+
+#[test]
+fn pad() {
+	let grid = Grid::from_fn(3, 2, |x, y| x * 10 + y);
+
+	let padded = grid.pad(1, PadMode::Constant(-1));
+	assert_eq!(padded.width, 5);
+	assert_eq!(padded.height, 4);
+	assert_eq!(
+		cols(&padded),
+		vec![
+			vec![-1, -1, -1, -1],
+			vec![-1, 0, 1, -1],
+			vec![-1, 10, 11, -1],
+			vec![-1, 20, 21, -1],
+			vec![-1, -1, -1, -1],
+		]
+	);
+
+	let padded = grid.pad(1, PadMode::Edge);
+	assert_eq!((padded.width, padded.height), (5, 4));
+	assert_eq!(
+		cols(&padded),
+		vec![
+			vec![0, 0, 1, 1],
+			vec![0, 0, 1, 1],
+			vec![10, 10, 11, 11],
+			vec![20, 20, 21, 21],
+			vec![20, 20, 21, 21],
+		]
+	);
+
+	let padded = grid.pad(2, PadMode::Wrap);
+	assert_eq!((padded.width, padded.height), (7, 6));
+	assert_eq!(padded.col(0), &vec![10, 11, 10, 11, 10, 11]);
+	assert_eq!(padded.row(0), vec![10, 20, 0, 10, 20, 0, 10]);
+
+	let padded = grid.pad(2, PadMode::Reflect);
+	assert_eq!(padded.row(2), vec![20, 10, 0, 10, 20, 10, 0]);
+	assert_eq!(padded.col(2), &vec![0, 1, 0, 1, 0, 1]);
+
+	assert_eq!(cols(&grid.pad(0, PadMode::Edge)), cols(&grid));
+}
+
+#[test]
+fn from_rows() {
+	let grid = Grid::from_rows(&[&[1, 2, 3], &[4, 5, 6]]);
+	assert_eq!((grid.width, grid.height), (3, 2));
+	assert_eq!(grid.get(0, 0), &1);
+	assert_eq!(grid.get(2, 0), &3);
+	assert_eq!(grid.get(1, 1), &5);
+	assert_eq!(grid.to_rows(), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+	let rows = grid.to_rows();
+	let rows: Vec<&[i32]> = rows.iter().map(|r| r.as_slice()).collect();
+	assert_eq!(cols(&Grid::from_rows(&rows)), cols(&grid));
+}
+
+#[test]
+#[should_panic]
+fn from_ragged_rows() {
+	Grid::from_rows(&[&[1, 2, 3], &[4, 5]]);
+}
+
+#[test]
+#[should_panic]
+fn from_empty_first_row() {
+	Grid::from_rows(&[&[], &[1, 2, 3]]);
+}
+
+#[test]
+fn from_no_rows() {
+	let grid = Grid::<i32, _>::from_rows(&[]);
+	assert_eq!((grid.width, grid.height), (0, 0));
+}
+
+#[test]
+fn dijkstra_field() {
+	// 0 free, 1 wall, 2 slow
+	let map = Grid::from_rows(&[
+		&[0, 0, 0, 0, 0],
+		&[0, 1, 1, 1, 0],
+		&[0, 0, 0, 1, 0],
+		&[2, 1, 0, 1, 0],
+		&[0, 1, 0, 0, 0],
+	]);
+	let cost = |v: &i32| match v {
+		0 => Some(1.0),
+		2 => Some(5.0),
+		_ => None,
+	};
+
+	let field = map.dijkstra_field(&[(2, 2)], cost);
+	assert_eq!(field.get(2, 2), &0.0);
+	assert_eq!(field.get(2, 4), &2.0);
+	// routes around the wall, through the top row or the bottom corridor
+	assert_eq!(field.get(0, 0), &4.0);
+	assert_eq!(field.get(4, 0), &8.0);
+	assert_eq!(field.get(4, 2), &6.0);
+	// slow cell, and the cell behind it, reached from the corridor
+	assert_eq!(field.get(0, 3), &7.0);
+	assert_eq!(field.get(0, 4), &8.0);
+	for (x, y) in [(1, 1), (2, 1), (3, 1), (3, 2), (1, 3), (3, 3), (1, 4)] {
+		assert_eq!(field.get(x, y), &f32::INFINITY);
+	}
+
+	let field = map.dijkstra_field(&[(2, 2), (4, 0)], cost);
+	assert_eq!(field.get(4, 0), &0.0);
+	assert_eq!(field.get(4, 2), &2.0);
+	assert_eq!(field.get(0, 0), &4.0);
+
+	let moore = map.dijkstra_field_with_connectivity(&[(2, 2)], Connectivity::Moore, cost);
+	assert_eq!(moore.get(0, 3), &7.0);
+	assert!((moore.get(0, 1) - (1.0 + SQRT_2)).abs() < 1e-5);
+	assert!((moore.get(0, 0) - (2.0 + SQRT_2)).abs() < 1e-5);
+
+	assert!(map
+		.dijkstra_field(&[(1, 1)], cost)
+		.to_rows()
+		.iter()
+		.flatten()
+		.all(|d| d.is_infinite()));
+}
+
+#[test]
+fn dijkstra_field_wraps() {
+	let grid = Grid::<u8, _>::new_toroidal(5, 1);
+	let field = grid.dijkstra_field(&[(0, 0)], |_| Some(1.0));
+	assert_eq!(field.row(0), vec![0.0, 1.0, 2.0, 2.0, 1.0]);
+
+	let flow = field.flow_directions();
+	assert_eq!(flow.get(0, 0), &Vec2::ZERO);
+	assert_eq!(flow.get(1, 0), &vec2(-1.0, 0.0));
+	assert_eq!(flow.get(4, 0), &vec2(1.0, 0.0));
+}
+
+#[test]
+fn flow_directions() {
+	let map = Grid::from_rows(&[&[0, 0, 0], &[1, 1, 0], &[0, 0, 0]]);
+	let cost = |v: &i32| (*v == 0).then_some(1.0);
+	let field = map.dijkstra_field(&[(0, 2)], cost);
+
+	let flow = field.flow_directions();
+	assert_eq!(flow.get(0, 2), &Vec2::ZERO);
+	assert_eq!(flow.get(0, 1), &Vec2::ZERO);
+	assert_eq!(flow.get(0, 0), &vec2(1.0, 0.0));
+	assert_eq!(flow.get(2, 0), &vec2(0.0, 1.0));
+	assert_eq!(flow.get(2, 2), &vec2(-1.0, 0.0));
+
+	let field = map.dijkstra_field_with_connectivity(&[(0, 2)], Connectivity::Moore, cost);
+	let flow = field.flow_directions_with_connectivity(Connectivity::Moore);
+	let diagonal = vec2(-1.0, 1.0).normalize();
+	assert!(flow.get(2, 1).abs_diff_eq(diagonal, 1e-6));
+}
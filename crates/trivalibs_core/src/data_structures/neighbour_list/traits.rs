@@ -1,4 +1,4 @@
-use std::iter::Flatten;
+use std::{collections::VecDeque, iter::Flatten};
 
 // With Neighbour iterator
 
@@ -60,6 +60,117 @@ where
 	}
 }
 
+// Cyclic With Neighbour iterator
+
+pub struct WithNeighboursCyclic<T> {
+	items: Vec<T>,
+	idx: usize,
+}
+
+impl<T> WithNeighboursCyclic<T> {
+	fn new<I: Iterator<Item = T>>(iter: I) -> Self {
+		Self {
+			items: iter.collect(),
+			idx: 0,
+		}
+	}
+}
+
+impl<T: Clone> Iterator for WithNeighboursCyclic<T> {
+	type Item = (Option<T>, T, Option<T>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let len = self.items.len();
+		if self.idx >= len {
+			return None;
+		}
+
+		let i = self.idx;
+		self.idx += 1;
+
+		if len == 1 {
+			return Some((None, self.items[0].clone(), None));
+		}
+
+		Some((
+			Some(self.items[(i + len - 1) % len].clone()),
+			self.items[i].clone(),
+			Some(self.items[(i + 1) % len].clone()),
+		))
+	}
+}
+
+pub trait WithNeighboursCyclicTransform: Iterator + Sized {
+	/// Like `with_neighbours`, but the first element has the last one as prev neighbour
+	/// and the last element has the first one as next neighbour.
+	/// A single element has no neighbours.
+	fn with_neighbours_cyclic(self) -> WithNeighboursCyclic<Self::Item>;
+}
+
+impl<I> WithNeighboursCyclicTransform for I
+where
+	I: Iterator,
+{
+	fn with_neighbours_cyclic(self) -> WithNeighboursCyclic<Self::Item> {
+		WithNeighboursCyclic::new(self)
+	}
+}
+
+// Sliding window iterator
+
+pub struct Windows<I>
+where
+	I: Iterator,
+{
+	window: VecDeque<I::Item>,
+	size: usize,
+	iter: I,
+}
+
+impl<I: Iterator> Windows<I> {
+	fn new(iter: I, size: usize) -> Self {
+		assert!(size > 0, "window size must be greater than 0");
+		Self {
+			window: VecDeque::with_capacity(size),
+			size,
+			iter,
+		}
+	}
+}
+
+impl<T, I> Iterator for Windows<I>
+where
+	T: Clone,
+	I: Iterator<Item = T>,
+{
+	type Item = Vec<T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.window.len() == self.size {
+			self.window.pop_front();
+		}
+		while self.window.len() < self.size {
+			self.window.push_back(self.iter.next()?);
+		}
+		Some(self.window.iter().cloned().collect())
+	}
+}
+
+pub trait WindowsTransform: Iterator + Sized {
+	/// Yields all runs of `size` consecutive elements, like `slice::windows`.
+	/// Yields nothing if there are less than `size` elements.
+	fn windows_transform(self, size: usize) -> Windows<Self>;
+}
+
+impl<I> WindowsTransform for I
+where
+	I: Iterator,
+{
+	fn windows_transform(self, size: usize) -> Windows<Self> {
+		Windows::new(self, size)
+	}
+}
+
 // Map iterator
 
 pub struct NeighbourMap<I, F>
@@ -195,7 +306,10 @@ where
 
 #[cfg(test)]
 mod tests {
-	use super::{NeighbourFlatMapTransform, NeighbourMapTransform, WithNeighboursTransform};
+	use super::{
+		NeighbourFlatMapTransform, NeighbourMapTransform, WindowsTransform,
+		WithNeighboursCyclicTransform, WithNeighboursTransform,
+	};
 
 	#[test]
 	fn test_map_with_prev_next() {
@@ -304,4 +418,51 @@ mod tests {
 			]
 		);
 	}
+
+	#[test]
+	fn with_neighbours_cyclic() {
+		let v = [1, 2, 3, 4];
+
+		let res = v.iter().with_neighbours_cyclic().collect::<Vec<_>>();
+
+		assert_eq!(
+			res,
+			[
+				(Some(&4), &1, Some(&2)),
+				(Some(&1), &2, Some(&3)),
+				(Some(&2), &3, Some(&4)),
+				(Some(&3), &4, Some(&1))
+			]
+		);
+
+		let res = [1].iter().with_neighbours_cyclic().collect::<Vec<_>>();
+		assert_eq!(res, [(None, &1, None)]);
+
+		let empty: [i32; 0] = [];
+		assert_eq!(empty.iter().with_neighbours_cyclic().count(), 0);
+	}
+
+	#[test]
+	fn windows_transform() {
+		let v = [1, 2, 3, 4, 5];
+
+		let res = v.iter().windows_transform(3).collect::<Vec<_>>();
+		let expected = v
+			.windows(3)
+			.map(|w| w.iter().collect())
+			.collect::<Vec<Vec<_>>>();
+
+		assert_eq!(res, expected);
+		assert_eq!(res, [[&1, &2, &3], [&2, &3, &4], [&3, &4, &5]]);
+
+		assert_eq!(v.iter().windows_transform(6).count(), 0);
+		assert_eq!(
+			v.iter()
+				.with_neighbours()
+				.map(|(_, v, _)| v)
+				.windows_transform(5)
+				.count(),
+			1
+		);
+	}
 }
@@ -0,0 +1,118 @@
+use crate::rendering::transform::Transform;
+use glam::Vec3;
+use serde::Serialize;
+
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Aabb {
+	pub min: Vec3,
+	pub max: Vec3,
+}
+
+impl Aabb {
+	pub fn new(min: Vec3, max: Vec3) -> Self {
+		Self { min, max }
+	}
+
+	/// Smallest box containing all points. Returns a zero sized box at the origin for no points.
+	pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+		let mut points = points.into_iter();
+		let Some(first) = points.next() else {
+			return Self::new(Vec3::ZERO, Vec3::ZERO);
+		};
+		points.fold(Self::new(first, first), |bounds, p| {
+			Self::new(bounds.min.min(p), bounds.max.max(p))
+		})
+	}
+
+	/// Smallest box containing both boxes.
+	pub fn merge(&self, other: &Aabb) -> Self {
+		Self::new(self.min.min(other.min), self.max.max(other.max))
+	}
+
+	/// The 8 corners of the box.
+	pub fn corners(&self) -> [Vec3; 8] {
+		let (min, max) = (self.min, self.max);
+		[
+			Vec3::new(min.x, min.y, min.z),
+			Vec3::new(max.x, min.y, min.z),
+			Vec3::new(min.x, max.y, min.z),
+			Vec3::new(max.x, max.y, min.z),
+			Vec3::new(min.x, min.y, max.z),
+			Vec3::new(max.x, min.y, max.z),
+			Vec3::new(min.x, max.y, max.z),
+			Vec3::new(max.x, max.y, max.z),
+		]
+	}
+
+	/// Axis aligned box around the corners of this box, transformed by `transform`.
+	pub fn transformed(&self, transform: &Transform) -> Self {
+		Self::from_points(self.corners().map(|c| transform.transform_point(c)))
+	}
+
+	pub fn center(&self) -> Vec3 {
+		(self.min + self.max) * 0.5
+	}
+
+	pub fn size(&self) -> Vec3 {
+		self.max - self.min
+	}
+
+	/// Radius of the bounding sphere around the box center.
+	pub fn radius(&self) -> f32 {
+		self.size().length() * 0.5
+	}
+
+	/// Whether the point is inside the box, including its surface.
+	pub fn contains(&self, point: Vec3) -> bool {
+		point.cmpge(self.min).all() && point.cmple(self.max).all()
+	}
+
+	/// Whether the boxes overlap, including touching surfaces.
+	pub fn intersects(&self, other: &Aabb) -> bool {
+		self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::{vec3, Quat};
+	use std::f32::consts::FRAC_PI_4;
+
+	#[test]
+	fn transformed() {
+		let bounds = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+		let transform = Transform::from_translation(vec3(2.0, 0.0, 0.0))
+			.with_rotation(Quat::from_rotation_z(FRAC_PI_4));
+
+		let rotated = bounds.transformed(&transform);
+		let half_diagonal = 2.0_f32.sqrt();
+		assert!(rotated
+			.min
+			.abs_diff_eq(vec3(2.0 - half_diagonal, -half_diagonal, -1.0), 1e-5));
+		assert!(rotated
+			.max
+			.abs_diff_eq(vec3(2.0 + half_diagonal, half_diagonal, 1.0), 1e-5));
+	}
+
+	#[test]
+	fn merge_and_overlap() {
+		let a = Aabb::new(vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0));
+		let b = Aabb::new(vec3(3.0, -2.0, 0.5), vec3(4.0, -1.0, 2.0));
+		assert!(!a.intersects(&b));
+
+		let merged = a.merge(&b);
+		assert_eq!(merged, Aabb::new(vec3(0.0, -2.0, 0.0), vec3(4.0, 1.0, 2.0)));
+		assert!(merged.intersects(&a) && merged.intersects(&b));
+		assert!(a
+			.corners()
+			.iter()
+			.chain(&b.corners())
+			.all(|c| merged.contains(*c)));
+		assert!(!merged.contains(vec3(4.5, 0.0, 0.0)));
+
+		assert_eq!(Aabb::from_points(b.corners()), b);
+		assert!(a.intersects(&Aabb::new(vec3(1.0, 1.0, 1.0), vec3(2.0, 2.0, 2.0))));
+	}
+}
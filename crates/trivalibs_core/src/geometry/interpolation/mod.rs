@@ -1,3 +1,4 @@
+use crate::utils::smoothing::Smoothable;
 use glam::{Vec2, Vec3};
 
 pub trait Interpolate {
@@ -17,6 +18,20 @@ pub trait Interpolate {
 	/// `a` is the first point; `b` is the second point; `u` is the output tangent of `a` to the curve and `v` is the
 	/// input tangent of `b` to the curve.
 	fn cubic_bezier(t: f32, a: Self, u: Self, v: Self, b: Self) -> Self;
+
+	/// Uniform Catmull-Rom interpolation.
+	///
+	/// Interpolates between `b` and `c`; `a` and `d` are the neighbouring points, that define the tangents.
+	/// Defaults to the equivalent cubic Bézier curve, with the tangents built from affine combinations via `lerp`.
+	fn catmull_rom(t: f32, a: Self, b: Self, c: Self, d: Self) -> Self
+	where
+		Self: Sized + Copy,
+	{
+		// b + (c - a) / 6 and c - (d - b) / 6
+		let u = Self::lerp(1. / 6., Self::lerp(6. / 5., a, b), c);
+		let v = Self::lerp(1. / 6., Self::lerp(6. / 5., d, c), b);
+		Self::cubic_bezier(t, b, u, v, c)
+	}
 }
 
 #[macro_export]
@@ -47,6 +62,17 @@ macro_rules! impl_Interpolate {
 
 				a * one_t3 + (u * one_t2 * t + v * one_t * t2) * 3. + b * t2 * t
 			}
+
+			fn catmull_rom(t: f32, a: Self, b: Self, c: Self, d: Self) -> Self {
+				let t2 = t * t;
+				let t3 = t2 * t;
+
+				(b * 2.
+					+ (c - a) * t
+					+ (a * 2. - b * 5. + c * 4. - d) * t2
+					+ (b * 3. - a - c * 3. + d) * t3)
+					* 0.5
+			}
 		}
 	};
 }
@@ -54,3 +80,232 @@ macro_rules! impl_Interpolate {
 impl_Interpolate!(f32);
 impl_Interpolate!(Vec2);
 impl_Interpolate!(Vec3);
+
+/// Distance between two points, for arc length computations.
+pub trait Distance {
+	fn distance_to(self, other: Self) -> f32;
+}
+
+impl Distance for f32 {
+	fn distance_to(self, other: Self) -> f32 {
+		(self - other).abs()
+	}
+}
+
+impl Distance for Vec2 {
+	fn distance_to(self, other: Self) -> f32 {
+		self.distance(other)
+	}
+}
+
+impl Distance for Vec3 {
+	fn distance_to(self, other: Self) -> f32 {
+		self.distance(other)
+	}
+}
+
+/// Cubic Bézier curve from `p0` to `p3`, with the control points `p1` and `p2`.
+pub fn bezier_cubic<T: Interpolate>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+	T::cubic_bezier(t, p0, p1, p2, p3)
+}
+
+/// Catmull-Rom curve segment from `p1` to `p2`, with the neighbouring points `p0` and `p3`.
+pub fn catmull_rom<T: Interpolate + Copy>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T {
+	T::catmull_rom(t, p0, p1, p2, p3)
+}
+
+/// Catmull-Rom spline through all `points`, with `t` in `0..1` spanning all segments evenly.
+/// The first and last point are duplicated as outer control points, so the spline
+/// starts and ends exactly at them. A single point is returned as it is.
+///
+/// Panics if `points` is empty.
+pub fn catmull_rom_spline<T: Interpolate + Copy>(points: &[T], t: f32) -> T {
+	let segments = points.len() - 1;
+	if segments == 0 {
+		return points[0];
+	}
+
+	let t = t.clamp(0., 1.) * segments as f32;
+	let i = (t.floor() as usize).min(segments - 1);
+	let p0 = points[i.saturating_sub(1)];
+	let p3 = points[(i + 2).min(segments)];
+
+	T::catmull_rom(t - i as f32, p0, points[i], points[i + 1], p3)
+}
+
+/// Resamples the polyline through `samples` into `count` points, that are evenly spaced
+/// by arc length, including the first and last sample. The accuracy depends on how
+/// densely the samples follow the curve.
+pub fn arc_length_subdivide<T: Interpolate + Distance + Copy>(
+	samples: &[T],
+	count: usize,
+) -> Vec<T> {
+	if samples.len() < 2 || count < 2 {
+		return samples.iter().take(count).copied().collect();
+	}
+
+	let mut lengths = Vec::with_capacity(samples.len());
+	let mut total = 0.;
+	lengths.push(0.);
+	for w in samples.windows(2) {
+		total += w[0].distance_to(w[1]);
+		lengths.push(total);
+	}
+
+	let mut points = Vec::with_capacity(count);
+	let mut segment = 0;
+	for i in 0..count {
+		let target = total * i as f32 / (count - 1) as f32;
+		while segment < samples.len() - 2 && lengths[segment + 1] < target {
+			segment += 1;
+		}
+		let length = lengths[segment + 1] - lengths[segment];
+		let t = if length > 0. {
+			((target - lengths[segment]) / length).clamp(0., 1.)
+		} else {
+			0.
+		};
+		points.push(T::lerp(t, samples[segment], samples[segment + 1]));
+	}
+
+	points
+}
+
+// Easing functions, mapping a normalized time in `0..1` to an eased value with `0 -> 0` and `1 -> 1`.
+// See https://easings.net for their curves.
+
+pub fn ease_in_quad(t: f32) -> f32 {
+	t * t
+}
+
+pub fn ease_out_quad(t: f32) -> f32 {
+	1. - (1. - t) * (1. - t)
+}
+
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+	if t < 0.5 {
+		4. * t * t * t
+	} else {
+		1. - (-2. * t + 2.).powi(3) * 0.5
+	}
+}
+
+pub fn ease_in_out_sine(t: f32) -> f32 {
+	(1. - (t * std::f32::consts::PI).cos()) * 0.5
+}
+
+/// Overshoots and oscillates around 1 before settling.
+pub fn ease_out_elastic(t: f32) -> f32 {
+	if t <= 0. {
+		0.
+	} else if t >= 1. {
+		1.
+	} else {
+		let c4 = std::f32::consts::TAU / 3.;
+		2f32.powf(-10. * t) * ((t * 10. - 0.75) * c4).sin() + 1.
+	}
+}
+
+/// Bounces back from 1 with decreasing height, like a dropped ball.
+pub fn ease_out_bounce(t: f32) -> f32 {
+	let n1 = 7.5625;
+	let d1 = 2.75;
+
+	if t < 1. / d1 {
+		n1 * t * t
+	} else if t < 2. / d1 {
+		let t = t - 1.5 / d1;
+		n1 * t * t + 0.75
+	} else if t < 2.5 / d1 {
+		let t = t - 2.25 / d1;
+		n1 * t * t + 0.9375
+	} else {
+		let t = t - 2.625 / d1;
+		n1 * t * t + 0.984375
+	}
+}
+
+/// Damped spring, that follows a moving target, e.g. for smooth camera or UI motion.
+/// Steps are solved analytically, so they are stable for any `dt`,
+/// and two steps of `dt / 2` equal one step of `dt` towards a constant target.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Spring<T: Smoothable> {
+	pub position: T,
+	pub velocity: T,
+}
+
+impl<T: Smoothable> Spring<T> {
+	pub fn new(position: T) -> Self {
+		Self {
+			position,
+			velocity: T::default(),
+		}
+	}
+
+	/// Damping that reaches the target fastest without overshooting, for a given stiffness.
+	pub fn critical_damping(stiffness: f32) -> f32 {
+		2. * stiffness.max(0.).sqrt()
+	}
+
+	/// Advances the spring by `dt` towards `target`, and returns the new position.
+	/// Damping below [`Spring::critical_damping`] oscillates around the target, damping above approaches it slower.
+	pub fn update(&mut self, target: T, dt: f32, stiffness: f32, damping: f32) -> T {
+		let [p, q, r, s] = spring_step(dt, stiffness.max(0.), damping.max(0.));
+		let offset = self.position - target;
+		self.position = target + offset * p + self.velocity * q;
+		self.velocity = offset * r + self.velocity * s;
+		self.position
+	}
+}
+
+/// Coefficients of the solution of `x'' = -stiffness * x - damping * x'` after `dt`,
+/// as `x = x0 * p + v0 * q` and `v = x0 * r + v0 * s`.
+fn spring_step(dt: f32, stiffness: f32, damping: f32) -> [f32; 4] {
+	let omega = stiffness.sqrt();
+
+	if omega == 0. {
+		if damping == 0. {
+			return [1., dt, 0., 1.];
+		}
+		let decay = (-damping * dt).exp();
+		return [1., (1. - decay) / damping, 0., decay];
+	}
+
+	let zeta = damping / (2. * omega);
+
+	if (zeta - 1.).abs() < 1e-4 {
+		let decay = (-omega * dt).exp();
+		[
+			(1. + omega * dt) * decay,
+			dt * decay,
+			-omega * omega * dt * decay,
+			(1. - omega * dt) * decay,
+		]
+	} else if zeta < 1. {
+		let a = zeta * omega;
+		let wd = omega * (1. - zeta * zeta).sqrt();
+		let decay = (-a * dt).exp();
+		let (sin, cos) = (wd * dt).sin_cos();
+		[
+			decay * (cos + a / wd * sin),
+			decay * sin / wd,
+			-decay * omega * omega / wd * sin,
+			decay * (cos - a / wd * sin),
+		]
+	} else {
+		let root = omega * (zeta * zeta - 1.).sqrt();
+		let r1 = -zeta * omega + root;
+		let r2 = -zeta * omega - root;
+		let (e1, e2) = ((r1 * dt).exp(), (r2 * dt).exp());
+		let d = r2 - r1;
+		[
+			(r2 * e1 - r1 * e2) / d,
+			(e2 - e1) / d,
+			r1 * r2 * (e1 - e2) / d,
+			(r2 * e2 - r1 * e1) / d,
+		]
+	}
+}
+
+#[cfg(test)]
+mod tests;
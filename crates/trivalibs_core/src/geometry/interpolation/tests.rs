@@ -0,0 +1,183 @@
+use super::*;
+use glam::{vec2, vec3, Vec2, Vec3};
+
+fn approx(a: f32, b: f32) -> bool {
+	(a - b).abs() < 1e-5
+}
+
+#[test]
+fn easing_endpoints() {
+	let easings: [fn(f32) -> f32; 6] = [
+		ease_in_quad,
+		ease_out_quad,
+		ease_in_out_cubic,
+		ease_in_out_sine,
+		ease_out_elastic,
+		ease_out_bounce,
+	];
+	for ease in easings {
+		assert!(approx(ease(0.), 0.));
+		assert!(approx(ease(1.), 1.));
+	}
+}
+
+#[test]
+fn easing_midpoints() {
+	assert!(approx(ease_in_out_cubic(0.5), 0.5));
+	assert!(approx(ease_in_out_sine(0.5), 0.5));
+	assert!(approx(ease_in_quad(0.5), 0.25));
+	assert!(approx(ease_out_quad(0.5), 0.75));
+
+	for t in [0.1, 0.2, 0.3, 0.4] {
+		assert!(approx(ease_in_out_cubic(t) + ease_in_out_cubic(1. - t), 1.));
+		assert!(approx(ease_in_out_sine(t) + ease_in_out_sine(1. - t), 1.));
+		assert!(ease_in_quad(t) < t);
+		assert!(ease_out_quad(t) > t);
+	}
+
+	assert!((1..10).any(|i| ease_out_elastic(i as f32 / 20.) > 1.));
+	for i in 0..=100 {
+		let t = i as f32 / 100.;
+		assert!((0. ..=1.).contains(&ease_out_bounce(t)));
+	}
+}
+
+#[test]
+fn bezier_and_catmull_rom_endpoints() {
+	let [p0, p1, p2, p3] = [
+		vec3(0., 0., 0.),
+		vec3(1., 2., 0.),
+		vec3(3., 2., 1.),
+		vec3(4., 0., 1.),
+	];
+	assert!(bezier_cubic(p0, p1, p2, p3, 0.).abs_diff_eq(p0, 1e-6));
+	assert!(bezier_cubic(p0, p1, p2, p3, 1.).abs_diff_eq(p3, 1e-6));
+	assert!(catmull_rom(p0, p1, p2, p3, 0.).abs_diff_eq(p1, 1e-6));
+	assert!(catmull_rom(p0, p1, p2, p3, 1.).abs_diff_eq(p2, 1e-6));
+
+	// symmetric control points around x = 2
+	let [p0, p1, p2, p3] = [vec2(0., 0.), vec2(1., 2.), vec2(3., 2.), vec2(4., 0.)];
+	assert!(bezier_cubic(p0, p1, p2, p3, 0.5).abs_diff_eq(vec2(2., 1.5), 1e-6));
+	let mid = catmull_rom(p0, p1, p2, p3, 0.5);
+	assert!((mid.x - 2.).abs() < 1e-6);
+	assert!(mid.y > 2.);
+}
+
+/// Implements only the required methods, to test the default Catmull-Rom interpolation.
+#[derive(Clone, Copy)]
+struct Scalar(f32);
+
+impl Interpolate for Scalar {
+	fn lerp(t: f32, a: Self, b: Self) -> Self {
+		Scalar(f32::lerp(t, a.0, b.0))
+	}
+
+	fn cosine(t: f32, a: Self, b: Self) -> Self {
+		Scalar(f32::cosine(t, a.0, b.0))
+	}
+
+	fn quadratic_bezier(t: f32, a: Self, u: Self, b: Self) -> Self {
+		Scalar(f32::quadratic_bezier(t, a.0, u.0, b.0))
+	}
+
+	fn cubic_bezier(t: f32, a: Self, u: Self, v: Self, b: Self) -> Self {
+		Scalar(f32::cubic_bezier(t, a.0, u.0, v.0, b.0))
+	}
+}
+
+#[test]
+fn default_catmull_rom() {
+	let [p0, p1, p2, p3] = [1., 4., -2., 3.];
+	for i in 0..=10 {
+		let t = i as f32 / 10.;
+		let expected = catmull_rom(p0, p1, p2, p3, t);
+		let actual = catmull_rom(Scalar(p0), Scalar(p1), Scalar(p2), Scalar(p3), t);
+		assert!(approx(actual.0, expected));
+	}
+}
+
+#[test]
+fn catmull_rom_spline_through_points() {
+	let points = [vec2(0., 0.), vec2(1., 1.), vec2(2., 0.), vec2(3., 1.)];
+	assert!(catmull_rom_spline(&points, 0.).abs_diff_eq(points[0], 1e-6));
+	assert!(catmull_rom_spline(&points, 1.).abs_diff_eq(points[3], 1e-6));
+	assert!(catmull_rom_spline(&points, 1. / 3.).abs_diff_eq(points[1], 1e-6));
+	assert!(catmull_rom_spline(&points, 2. / 3.).abs_diff_eq(points[2], 1e-5));
+	assert_eq!(catmull_rom_spline(&[vec2(1., 2.)], 0.5), vec2(1., 2.));
+}
+
+#[test]
+fn arc_length_subdivision() {
+	// dense samples with uneven parameter spacing along a straight line
+	let samples = (0..=100)
+		.map(|i| {
+			let t = i as f32 / 100.;
+			Vec3::X * t * t * 10.
+		})
+		.collect::<Vec<_>>();
+	let points = arc_length_subdivide(&samples, 6);
+	assert_eq!(points.len(), 6);
+	for (i, p) in points.iter().enumerate() {
+		assert!((p.x - i as f32 * 2.).abs() < 1e-4);
+	}
+
+	let samples = (0..=200)
+		.map(|i| catmull_rom_spline(&[Vec2::ZERO, vec2(2., 3.), vec2(5., 0.)], i as f32 / 200.))
+		.collect::<Vec<_>>();
+	let points = arc_length_subdivide(&samples, 10);
+	let distances = points
+		.windows(2)
+		.map(|w| w[0].distance(w[1]))
+		.collect::<Vec<_>>();
+	let avg = distances.iter().sum::<f32>() / distances.len() as f32;
+	// chords are a bit shorter than the arc around the apex of the curve
+	assert!(distances.iter().all(|d| (d - avg).abs() < avg * 0.05));
+	assert_eq!(points[0], Vec2::ZERO);
+	assert!(points[9].abs_diff_eq(vec2(5., 0.), 1e-5));
+}
+
+#[test]
+fn spring_converges() {
+	let target = vec3(1., -2., 3.);
+	let stiffness = 40.;
+
+	for damping in [Spring::<Vec3>::critical_damping(stiffness), 4., 30.] {
+		let mut spring = Spring::new(Vec3::ZERO);
+		for _ in 0..600 {
+			spring.update(target, 1. / 60., stiffness, damping);
+		}
+		assert!(spring.position.abs_diff_eq(target, 1e-3));
+		assert!(spring.velocity.length() < 1e-2);
+	}
+
+	// critically damped springs don't overshoot
+	let mut spring = Spring::new(0f32);
+	let damping = Spring::<f32>::critical_damping(stiffness);
+	for _ in 0..200 {
+		assert!(spring.update(1., 1. / 60., stiffness, damping) <= 1.);
+	}
+
+	// large steps stay stable
+	let mut spring = Spring::new(Vec2::ZERO);
+	for _ in 0..10 {
+		let p = spring.update(vec2(5., 5.), 10., 500., 2.);
+		assert!(p.is_finite());
+	}
+	assert!(spring.position.abs_diff_eq(vec2(5., 5.), 1e-3));
+}
+
+#[test]
+fn spring_step_independence() {
+	for damping in [2., Spring::<f32>::critical_damping(25.), 20.] {
+		let mut once = Spring::new(0f32);
+		once.velocity = 3.;
+		let mut twice = once;
+
+		once.update(2., 0.2, 25., damping);
+		twice.update(2., 0.1, 25., damping);
+		twice.update(2., 0.1, 25., damping);
+
+		assert!((once.position - twice.position).abs() < 1e-5);
+		assert!((once.velocity - twice.velocity).abs() < 1e-4);
+	}
+}
@@ -1,6 +1,8 @@
 use super::{Line, LineData};
 use crate::{
-	data_structures::neighbour_list::traits::{NeighbourMapTransform, WithNeighboursTransform},
+	data_structures::neighbour_list::traits::{
+		NeighbourMapTransform, WithNeighboursCyclicTransform, WithNeighboursTransform,
+	},
 	rendering::buffered_geometry::{
 		create_buffered_geometry_layout, vert_type, BufferedGeometry, BufferedVertexData,
 		RenderingPrimitive,
@@ -11,6 +13,7 @@ use crate::{
 };
 use bytemuck::{Pod, Zeroable};
 use glam::{bool, Vec2};
+use std::f32::consts::FRAC_PI_2;
 
 #[repr(C)]
 #[derive(Pod, Copy, Clone, Zeroable)]
@@ -22,6 +25,12 @@ pub struct VertexData {
 	local_uv: Vec2,
 }
 
+impl VertexData {
+	pub fn position(&self) -> Vec2 {
+		self.position
+	}
+}
+
 impl BufferedVertexData for VertexData {
 	fn vertex_layout() -> Vec<VertexType> {
 		vec![
@@ -34,6 +43,92 @@ impl BufferedVertexData for VertexData {
 	}
 }
 
+/// Reusable vertex and index storage for line geometry,
+/// to rebuild animated lines every frame without allocating.
+#[derive(Default)]
+pub struct BufferedGeometryBuffers {
+	pub vertices: Vec<VertexData>,
+	pub indices: Vec<u32>,
+}
+
+impl BufferedGeometryBuffers {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn with_capacity(vertices: usize, indices: usize) -> Self {
+		Self {
+			vertices: Vec::with_capacity(vertices),
+			indices: Vec::with_capacity(indices),
+		}
+	}
+
+	/// Empties the buffers, keeping the allocated capacity.
+	pub fn clear(&mut self) {
+		self.vertices.clear();
+		self.indices.clear();
+	}
+
+	pub fn vertex_bytes(&self) -> &[u8] {
+		bytemuck::cast_slice(&self.vertices)
+	}
+
+	pub fn index_bytes(&self) -> &[u8] {
+		bytemuck::cast_slice(&self.indices)
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CapStyle {
+	/// The line ends exactly at the first and last point.
+	Butt,
+	/// The line ends are extended by the line width.
+	Square,
+	/// The line ends are rounded with a half circle, made of `cap_segments` segments per side.
+	Round,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JoinStyle {
+	/// Sharp corners, falling back to bevel if the mitre gets longer than `mitre_limit`.
+	Mitre,
+	/// Corners cut off with a straight edge.
+	Bevel,
+	/// Corners rounded with an arc, made of `join_segments` segments.
+	Round,
+}
+
+/// Alternating lengths of visible and invisible line segments, starting with a visible one.
+/// Holds up to [`DashPattern::MAX_LEN`] lengths inline, so that line props stay `Copy`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DashPattern {
+	lengths: [f32; DashPattern::MAX_LEN],
+	len: usize,
+}
+
+impl DashPattern {
+	pub const MAX_LEN: usize = 8;
+
+	/// Panics if `lengths` has more than [`DashPattern::MAX_LEN`] entries.
+	pub fn new(lengths: &[f32]) -> Self {
+		assert!(
+			lengths.len() <= Self::MAX_LEN,
+			"dash patterns have at most {} lengths",
+			Self::MAX_LEN
+		);
+		let mut pattern = Self {
+			lengths: [0.0; Self::MAX_LEN],
+			len: lengths.len(),
+		};
+		pattern.lengths[..lengths.len()].copy_from_slice(lengths);
+		pattern
+	}
+
+	pub fn lengths(&self) -> &[f32] {
+		&self.lengths[..self.len]
+	}
+}
+
 #[derive(Clone, Copy)]
 pub struct LineGeometryProps {
 	pub smouth_depth: u8,
@@ -44,6 +139,18 @@ pub struct LineGeometryProps {
 	pub prev_direction: Option<Vec2>,
 	pub next_direction: Option<Vec2>,
 	pub swap_texture_orientation: bool,
+	/// Caps are only applied to line ends without prev or next direction.
+	pub cap_style: CapStyle,
+	pub cap_segments: u8,
+	pub join_style: JoinStyle,
+	pub join_segments: u8,
+	/// Maximum ratio between mitre length and line width, before a mitre join is beveled.
+	pub mitre_limit: f32,
+	/// Every visible segment of the dash pattern gets its own caps.
+	pub dash_pattern: Option<DashPattern>,
+	/// Connects the last vertex with the first one, joining the seam like any other corner.
+	/// Closed lines have no caps.
+	pub closed: bool,
 }
 
 impl Default for LineGeometryProps {
@@ -57,6 +164,13 @@ impl Default for LineGeometryProps {
 			prev_direction: None,
 			next_direction: None,
 			swap_texture_orientation: false,
+			cap_style: CapStyle::Butt,
+			cap_segments: 4,
+			join_style: JoinStyle::Mitre,
+			join_segments: 4,
+			mitre_limit: 5.0,
+			dash_pattern: None,
+			closed: false,
 		}
 	}
 }
@@ -86,27 +200,210 @@ fn line_mitter_positions(pos: &Vec2, dir: &Vec2, width: f32, prev_dir: Option<&V
 	let prev_normal = get_normal(prev_dir.unwrap());
 	let normal = (next_normal + prev_normal).normalize();
 	let mitter_length = width / normal.dot(prev_normal);
-	let mitter_length = mitter_length.min(width * 5.0);
 	line_positions(*pos, normal, mitter_length)
 }
 
+/// Top and bottom outline points of a bevel or round join.
+/// Returns None, if the join is straight or a mitre within the mitre limit.
+fn join_positions(
+	pos: Vec2,
+	dir: Vec2,
+	prev_dir: Vec2,
+	width: f32,
+	props: &LineGeometryProps,
+) -> Option<(Vec<Vec2>, Vec<Vec2>)> {
+	if dir == prev_dir {
+		return None;
+	}
+
+	let prev_normal = get_normal(&prev_dir);
+	let next_normal = get_normal(&dir);
+	let normal = (next_normal + prev_normal).normalize_or_zero();
+	let mitre_ratio = 1.0 / normal.dot(prev_normal);
+	let within_limit = mitre_ratio.is_finite() && mitre_ratio <= props.mitre_limit;
+
+	if props.join_style == JoinStyle::Mitre && within_limit {
+		return None;
+	}
+
+	// the top side is on the outside of left turns
+	let top_outer = cross_2d(prev_dir, dir) > 0.0;
+	let side = if top_outer { 1.0 } else { -1.0 };
+	let from = prev_normal * side;
+	let to = next_normal * side;
+
+	let outer = match props.join_style {
+		JoinStyle::Round => {
+			let mut angle = from.perp_dot(to).atan2(from.dot(to));
+			// the arc has to pass in front of the corner
+			if Vec2::from_angle(angle * 0.5).rotate(from).dot(prev_dir) < 0.0 {
+				angle = -angle;
+			}
+			let segments = props.join_segments.max(1);
+			(0..=segments)
+				.map(|i| {
+					let a = angle * i as f32 / segments as f32;
+					pos + Vec2::from_angle(a).rotate(from) * width
+				})
+				.collect()
+		}
+		_ => vec![pos + from * width, pos + to * width],
+	};
+
+	let inner = if within_limit {
+		vec![pos - normal * side * width * mitre_ratio]
+	} else {
+		vec![pos - from * width, pos - to * width]
+	};
+
+	Some(if top_outer {
+		(outer, inner)
+	} else {
+		(inner, outer)
+	})
+}
+
+/// Top and bottom outline points of a line start cap, ordered in line direction.
+/// For end caps, pass the inverted direction and swap and reverse the result.
+fn cap_positions(
+	pos: Vec2,
+	dir: Vec2,
+	width: f32,
+	style: CapStyle,
+	segments: u8,
+) -> (Vec<Vec2>, Vec<Vec2>) {
+	match style {
+		CapStyle::Butt => (vec![pos], vec![pos]),
+		CapStyle::Square => {
+			let p = pos - dir * width;
+			(vec![p], vec![p])
+		}
+		CapStyle::Round => {
+			let normal = get_normal(&dir);
+			let segments = segments.max(1);
+			let arc = |side: Vec2| {
+				(0..segments)
+					.map(|i| {
+						let angle = i as f32 / segments as f32 * FRAC_PI_2;
+						pos + (-dir * angle.cos() + side * angle.sin()) * width
+					})
+					.collect::<Vec<_>>()
+			};
+			(arc(normal), arc(-normal))
+		}
+	}
+}
+
 fn cross_2d(v1: Vec2, v2: Vec2) -> f32 {
 	v1.x * v2.y - v1.y * v2.x
 }
 
 impl Line {
-	pub fn to_buffered_geometry_with(&self, props: LineGeometryProps) -> BufferedGeometry {
-		let mut top_line = LineData::<f32>::new(self.default_width);
-		let mut bottom_line = LineData::<f32>::new(self.default_width);
-		let mut line_length = self.len_offset;
+	/// Writes the line geometry into the given buffers, replacing their content.
+	/// The rendering primitive is a triangle strip, as in [`Line::to_buffered_geometry_with`].
+	pub fn write_buffered_geometry_into(
+		&self,
+		buf: &mut BufferedGeometryBuffers,
+		props: LineGeometryProps,
+	) {
+		buf.clear();
+
+		let Some(pattern) = props.dash_pattern else {
+			self.append_buffered_geometry(buf, &props);
+			return;
+		};
+
+		let line = if props.closed {
+			self.close()
+		} else {
+			self.clone()
+		};
+
+		let dash_props = LineGeometryProps {
+			total_length: Some(props.total_length.unwrap_or(line.len_offset + line.len)),
+			closed: false,
+			..props
+		};
+
+		for dash in line.split_dashes(pattern.lengths()) {
+			let start = buf.indices.len();
+			dash.append_buffered_geometry(buf, &dash_props);
+
+			// connect the dashes with degenerate triangles
+			if start > 0 && buf.indices.len() > start {
+				let last = buf.indices[start - 1];
+				let first = buf.indices[start];
+				let mut bridge = vec![last, first];
+				// keep the winding order of the next dash
+				if start % 2 == 1 {
+					bridge.insert(0, last);
+				}
+				buf.indices.splice(start..start, bridge);
+			}
+		}
+	}
 
-		for (prev, v, next) in self.iter().with_neighbours() {
+	/// Appends the line as triangle strip to the buffers.
+	fn append_buffered_geometry(
+		&self,
+		buf: &mut BufferedGeometryBuffers,
+		props: &LineGeometryProps,
+	) {
+		let buffer = &mut buf.vertices;
+		let indices = &mut buf.indices;
+		let index_offset = buffer.len() as u32;
+
+		// A closed line is a loop, where the last vertex points to the first one.
+		let closed = props.closed && self.vert_count() > 2;
+		let closed_line;
+		let line = if closed {
+			let mut l = self.close();
+			l.list.pop();
+			closed_line = l;
+			&closed_line
+		} else {
+			self
+		};
+
+		let neighbours: Vec<_> = if closed {
+			line.iter().with_neighbours_cyclic().collect()
+		} else {
+			line.iter().with_neighbours().collect()
+		};
+
+		let mut seam: Option<(Vec<Vec2>, Vec<Vec2>, f32)> = None;
+
+		let mut top_line = LineData::<f32>::new(line.default_width);
+		let mut bottom_line = LineData::<f32>::new(line.default_width);
+		let mut line_length = line.len_offset;
+
+		for (prev, v, next) in neighbours {
 			let mut new_points =
 				line_mitter_positions(&v.pos, &v.dir, v.width, prev.map(|x| &x.dir));
 
+			let cap_start = prev.is_none() && props.prev_direction.is_none();
+			let cap_end = next.is_none() && props.next_direction.is_none();
+			let cap_style = if cap_start || cap_end {
+				props.cap_style
+			} else {
+				CapStyle::Butt
+			};
+
 			if prev.is_none() {
-				top_line.add_width_data(v.pos, v.width, line_length);
-				bottom_line.add_width_data(v.pos, v.width, line_length);
+				let (top, bottom) =
+					cap_positions(v.pos, v.dir, v.width, cap_style, props.cap_segments);
+				for p in top {
+					top_line.add_width_data(p, v.width, line_length);
+				}
+				for p in bottom {
+					bottom_line.add_width_data(p, v.width, line_length);
+				}
+			}
+
+			if cap_style == CapStyle::Square {
+				let offset = if cap_start { -v.dir } else { v.dir } * v.width;
+				new_points[0] += offset;
+				new_points[1] += offset;
 			}
 
 			// adjust first vertex
@@ -142,17 +439,43 @@ impl Line {
 				}
 			}
 
-			top_line.add_width_data(new_points[0], v.width, line_length);
-			bottom_line.add_width_data(new_points[1], v.width, line_length);
+			let (top, bottom) = prev
+				.and_then(|p| join_positions(v.pos, v.dir, p.dir, v.width, props))
+				.unwrap_or_else(|| (vec![new_points[0]], vec![new_points[1]]));
+			for p in &top {
+				top_line.add_width_data(*p, v.width, line_length);
+			}
+			for p in &bottom {
+				bottom_line.add_width_data(*p, v.width, line_length);
+			}
+			if closed && seam.is_none() {
+				seam = Some((top, bottom, v.width));
+			}
 
 			if next.is_none() {
-				top_line.add_width_data(v.pos, v.width, line_length);
-				bottom_line.add_width_data(v.pos, v.width, line_length);
+				let (bottom, top) =
+					cap_positions(v.pos, -v.dir, v.width, cap_style, props.cap_segments);
+				for p in top.into_iter().rev() {
+					top_line.add_width_data(p, v.width, line_length);
+				}
+				for p in bottom.into_iter().rev() {
+					bottom_line.add_width_data(p, v.width, line_length);
+				}
 			}
 
 			line_length += v.len;
 		}
 
+		// repeat the first corner at the end of the loop
+		if let Some((top, bottom, width)) = seam {
+			for p in top {
+				top_line.add_width_data(p, width, line_length);
+			}
+			for p in bottom {
+				bottom_line.add_width_data(p, width, line_length);
+			}
+		}
+
 		if props.smouth_depth > 0 {
 			for _ in 0..props.smouth_depth {
 				top_line = top_line.smouth_edges_threshold(
@@ -168,14 +491,11 @@ impl Line {
 			}
 		}
 
-		let mut buffer = vec![];
-		let mut indices: Vec<u32> = vec![];
-
 		let total_length = props.total_length.unwrap_or(line_length);
 
-		let mut top_idx: u32 = 0;
-		let mut bottom_idx: u32 = 0;
-		let mut next_idx: u32 = 0;
+		let mut top_idx: u32 = index_offset;
+		let mut bottom_idx: u32 = index_offset;
+		let mut next_idx: u32 = index_offset;
 
 		let mut top_length: f32 = 0.;
 		let mut bottom_length: f32 = 0.;
@@ -192,7 +512,7 @@ impl Line {
 				let top = top_opt.unwrap();
 				top_length = top.data;
 
-				let v = if top_i == 0 || top_i == top_line.vert_count() - 1 {
+				let v = if !closed && (top_i == 0 || top_i == top_line.vert_count() - 1) {
 					0.5
 				} else {
 					if props.swap_texture_orientation {
@@ -202,7 +522,7 @@ impl Line {
 					}
 				};
 				let top_uv = Vec2::new(top_length / total_length, v);
-				let top_local_uv = Vec2::new((top_length - self.len_offset) / self.len, v);
+				let top_local_uv = Vec2::new((top_length - line.len_offset) / line.len, v);
 				let top_vertex = VertexData {
 					position: top.pos,
 					width: top.width,
@@ -224,7 +544,7 @@ impl Line {
 			if bottom_opt.is_some() && balance >= 0. {
 				let bottom = bottom_opt.unwrap();
 				bottom_length = bottom.data;
-				let v = if bottom_i == 0 || bottom_i == bottom_line.vert_count() - 1 {
+				let v = if !closed && (bottom_i == 0 || bottom_i == bottom_line.vert_count() - 1) {
 					0.5
 				} else {
 					if props.swap_texture_orientation {
@@ -234,7 +554,7 @@ impl Line {
 					}
 				};
 				let bottom_uv = Vec2::new(bottom_length / total_length, v);
-				let bottom_local_uv = Vec2::new((bottom_length - self.len_offset) / self.len, v);
+				let bottom_local_uv = Vec2::new((bottom_length - line.len_offset) / line.len, v);
 				let bottom_vertex = VertexData {
 					position: bottom.pos,
 					width: bottom.width,
@@ -255,19 +575,25 @@ impl Line {
 
 			balance = top_length - bottom_length;
 		}
+	}
 
-		let indices_len = indices.len();
+	pub fn to_buffered_geometry_with(&self, props: LineGeometryProps) -> BufferedGeometry {
+		let mut buf = BufferedGeometryBuffers::new();
+		self.write_buffered_geometry_into(&mut buf, props);
 
 		let geom_layout = create_buffered_geometry_layout(VertexData::vertex_layout());
 
 		BufferedGeometry {
-			buffer: bytemuck::cast_slice(&buffer).to_vec(),
+			buffer: buf.vertex_bytes().to_vec(),
 			rendering_primitive: RenderingPrimitive::TriangleStrip,
-			indices: Some(bytemuck::cast_slice(&indices).to_vec()),
+			indices: Some(buf.index_bytes().to_vec()),
 			vertex_size: geom_layout.vertex_size,
-			vertex_count: indices_len as u32,
+			vertex_count: buf.indices.len() as u32,
 			vertex_layout: geom_layout.vertex_layout,
+			bounds_min: None,
+			bounds_max: None,
 		}
+		.with_bounds()
 	}
 
 	pub fn to_buffered_geometry(&self) -> BufferedGeometry {
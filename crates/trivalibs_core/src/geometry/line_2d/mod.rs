@@ -163,6 +163,24 @@ where
 		line
 	}
 
+	/// Builds a line with uniform `width`, computing the direction and length
+	/// of every segment from the consecutive points.
+	pub fn from_points(points: impl IntoIterator<Item = Vec2>, width: f32) -> Self {
+		Self::from_vecs(width, points)
+	}
+
+	/// Like [`LineData::from_points`], but with a width for every point.
+	/// The first width is used as default width of the line.
+	pub fn from_points_with_widths(points: impl IntoIterator<Item = (Vec2, f32)>) -> Self {
+		let mut points = points.into_iter().peekable();
+		let width = points.peek().map_or(1.0, |(_, width)| *width);
+		let mut line = LineData::<T>::new(width);
+		for (pos, width) in points {
+			line.add_width(pos, width);
+		}
+		line
+	}
+
 	pub fn line_length(&self) -> f32 {
 		self.len
 	}
@@ -234,6 +252,19 @@ where
 		&self.list[self.list.len() - 1]
 	}
 
+	/// Returns a copy of the line, that ends at its first vertex again.
+	/// Lines that already end at their start position are returned unchanged.
+	pub fn close(&self) -> Self {
+		let mut line = self.clone();
+		if line.list.len() > 1 && line.last().pos.distance_squared(line.first().pos) > f32::EPSILON
+		{
+			let mut first = line.list[0];
+			first.len = 0.0;
+			line.add_vert(first);
+		}
+		line
+	}
+
 	pub fn split_at_angle(&self, angle_threshold: f32) -> Vec<Self> {
 		let mut lines = vec![];
 		let mut line = LineData::<T>::new(self.default_width);
@@ -265,6 +296,64 @@ where
 		lines
 	}
 
+	/// Splits the line into dashes, alternating between on and off lengths from `pattern`,
+	/// starting with an on length. The pattern repeats along the whole line.
+	/// The dashes keep their length offset within this line.
+	pub fn split_dashes(&self, pattern: &[f32]) -> Vec<Self> {
+		let pattern_len: f32 = pattern.iter().sum();
+		if pattern.is_empty() || pattern_len <= 0.0 || self.list.len() < 2 {
+			return vec![self.clone()];
+		}
+
+		let mut dashes = vec![];
+		let mut pattern_idx = 0;
+		let mut remaining = pattern[0];
+		let mut on = true;
+		let mut travelled = self.len_offset;
+
+		let mut dash = LineData::new_offset(self.default_width, travelled);
+		dash.add_vert(self.list[0]);
+
+		for i in 0..self.list.len() - 1 {
+			let a = self.list[i];
+			let b = self.list[i + 1];
+			let mut pos = 0.0;
+
+			while a.len - pos > remaining {
+				pos += remaining;
+				let v = a.lerp(b, pos / a.len);
+
+				if on {
+					if dash.last().pos != v.pos {
+						dash.add_vert(v);
+					}
+					dashes.push(dash);
+					dash = LineData::new_offset(self.default_width, travelled + pos);
+				} else {
+					dash = LineData::new_offset(self.default_width, travelled + pos);
+					dash.add_vert(v);
+				}
+
+				on = !on;
+				pattern_idx = (pattern_idx + 1) % pattern.len();
+				remaining = pattern[pattern_idx];
+			}
+
+			remaining -= a.len - pos;
+			travelled += a.len;
+
+			if on && dash.vert_count() > 0 && dash.last().pos != b.pos {
+				dash.add_vert(b);
+			}
+		}
+
+		if on && dash.vert_count() > 1 {
+			dashes.push(dash);
+		}
+
+		dashes
+	}
+
 	pub fn flat_map_with_prev_next<
 		F: Fn(
 			&LineVertexData<T>,
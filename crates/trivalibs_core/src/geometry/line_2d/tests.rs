@@ -1,5 +1,12 @@
-use super::{Line, LineVertex};
-use glam::vec2;
+use super::{
+	buffered_geometry::{
+		BufferedGeometryBuffers, CapStyle, DashPattern, JoinStyle, LineGeometryProps, VertexData,
+	},
+	Line, LineVertex,
+};
+use crate::rendering::buffered_geometry::RenderingPrimitive;
+use crate::utils::default;
+use glam::{vec2, Vec2};
 
 #[test]
 fn vert_point_to() {
@@ -51,6 +58,34 @@ fn from_vecs() {
 	assert_eq!(line.vert_count(), 4);
 }
 
+#[test]
+fn from_points() {
+	let line = Line::from_points([vec2(0.0, 0.0), vec2(3.0, 4.0), vec2(3.0, 6.0)], 2.0);
+
+	assert_eq!(line.vert_count(), 3);
+	assert_eq!(line.line_length(), 7.0);
+	assert_eq!(line.get(0).dir, vec2(0.6, 0.8));
+	assert_eq!(line.get(0).len, 5.0);
+	assert_eq!(line.get(1).dir, vec2(0.0, 1.0));
+	assert_eq!(line.get(1).len, 2.0);
+	assert_eq!(line.get(2).dir, vec2(0.0, 1.0));
+	assert_eq!(line.get(2).len, 0.0);
+	assert!(line.iter().all(|v| v.width == 2.0));
+
+	let line = Line::from_points_with_widths([
+		(vec2(0.0, 0.0), 1.0),
+		(vec2(3.0, 4.0), 2.0),
+		(vec2(3.0, 6.0), 3.0),
+	]);
+
+	assert_eq!(line.line_length(), 7.0);
+	assert_eq!(line.get(0).dir, vec2(0.6, 0.8));
+	assert_eq!(
+		line.iter().map(|v| v.width).collect::<Vec<_>>(),
+		vec![1.0, 2.0, 3.0]
+	);
+}
+
 #[test]
 fn cleanup_vertices() {
 	let line1 = Line::from_vecs(
@@ -92,3 +127,286 @@ fn cleanup_vertices() {
 	let cleaned2_4 = line2.cleanup_vertices(0.1, 0.001, 0.001);
 	assert_eq!(cleaned2_4.vert_count(), 6);
 }
+
+#[test]
+fn buffered_geometry() {
+	let line = Line::from_vecs(2.0, [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)]);
+
+	let geom = line.to_buffered_geometry_with(LineGeometryProps::default());
+
+	// each side has the three line points, plus the start and end points on the center line
+	assert_eq!(geom.buffer_vertex_count(), 10);
+	assert_eq!(geom.buffer.len(), 10 * geom.vertex_size as usize);
+	assert_eq!(geom.rendering_primitive, RenderingPrimitive::TriangleStrip);
+	assert_eq!(geom.vertex_count, 10);
+	assert_eq!(geom.validate_indices(), Ok(()));
+}
+
+#[test]
+fn buffered_geometry_bounds() {
+	let line = Line::from_vecs(2.0, [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)]);
+	let geom = line.to_buffered_geometry();
+
+	// 2D positions have a z of 0, the line extends by its width around the points
+	assert_eq!(geom.bounds_min, Some([0.0, -2.0, 0.0]));
+	assert_eq!(geom.bounds_max, Some([12.0, 10.0, 0.0]));
+}
+
+#[test]
+fn write_buffered_geometry_into() {
+	let line1 = Line::from_vecs(2.0, [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)]);
+	let line2 = Line::from_vecs(1.0, [vec2(0.0, 0.0), vec2(5.0, 5.0)]);
+
+	let mut buf = BufferedGeometryBuffers::new();
+
+	line1.write_buffered_geometry_into(&mut buf, LineGeometryProps::default());
+	let geom = line1.to_buffered_geometry();
+	assert_eq!(buf.vertex_bytes(), geom.buffer.as_slice());
+	assert_eq!(Some(buf.index_bytes()), geom.indices.as_deref());
+
+	let vertex_capacity = buf.vertices.capacity();
+	let index_capacity = buf.indices.capacity();
+	let vertex_ptr = buf.vertices.as_ptr();
+
+	line2.write_buffered_geometry_into(&mut buf, LineGeometryProps::default());
+	let geom = line2.to_buffered_geometry();
+	assert_eq!(buf.vertex_bytes(), geom.buffer.as_slice());
+	assert_eq!(Some(buf.index_bytes()), geom.indices.as_deref());
+
+	assert_eq!(buf.vertices.capacity(), vertex_capacity);
+	assert_eq!(buf.indices.capacity(), index_capacity);
+	assert_eq!(buf.vertices.as_ptr(), vertex_ptr);
+}
+
+fn positions(buf: &BufferedGeometryBuffers) -> Vec<Vec2> {
+	buf.vertices.iter().map(VertexData::position).collect()
+}
+
+fn contains_approx(positions: &[Vec2], p: Vec2) -> bool {
+	positions.iter().any(|q| q.distance(p) < 1e-4)
+}
+
+#[test]
+fn cap_styles() {
+	let line = Line::from_vecs(1.0, [vec2(0.0, 0.0), vec2(10.0, 0.0)]);
+	let mut buf = BufferedGeometryBuffers::new();
+
+	line.write_buffered_geometry_into(&mut buf, LineGeometryProps::default());
+	let butt = positions(&buf);
+	assert_eq!(butt.len(), 8);
+	assert_eq!(butt.iter().map(|p| p.x).fold(f32::MAX, f32::min), 0.0);
+	assert_eq!(butt.iter().map(|p| p.x).fold(f32::MIN, f32::max), 10.0);
+
+	line.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			cap_style: CapStyle::Square,
+			..default()
+		},
+	);
+	let square = positions(&buf);
+	assert_eq!(square.len(), 8);
+	assert_eq!(square.iter().map(|p| p.x).fold(f32::MAX, f32::min), -1.0);
+	assert_eq!(square.iter().map(|p| p.x).fold(f32::MIN, f32::max), 11.0);
+
+	line.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			cap_style: CapStyle::Round,
+			cap_segments: 4,
+			..default()
+		},
+	);
+	let round = positions(&buf);
+	// every side gets 3 additional arc points per cap
+	assert_eq!(round.len(), 8 + 4 * 3);
+	for p in &round {
+		let center = vec2(p.x.clamp(0.0, 10.0), 0.0);
+		assert!(p.distance(center) <= 1.0 + 1e-5);
+	}
+	assert_eq!(round.iter().map(|p| p.x).fold(f32::MAX, f32::min), -1.0);
+
+	line.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			cap_style: CapStyle::Round,
+			cap_segments: 8,
+			..default()
+		},
+	);
+	assert_eq!(buf.vertices.len(), 8 + 4 * 7);
+}
+
+#[test]
+fn join_styles() {
+	let hairpin = Line::from_vecs(1.0, [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 1.0)]);
+	let mut buf = BufferedGeometryBuffers::new();
+
+	hairpin.write_buffered_geometry_into(&mut buf, LineGeometryProps::default());
+	let mitre = positions(&buf);
+	// the mitre limit is exceeded, so both sides get two points instead of a spike
+	assert_eq!(mitre.len(), 10 + 2);
+	assert!(mitre.iter().all(|p| p.x <= 10.0 + 1.0 + 1e-5));
+
+	hairpin.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			join_style: JoinStyle::Bevel,
+			..default()
+		},
+	);
+	assert_eq!(positions(&buf), mitre);
+
+	hairpin.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			join_style: JoinStyle::Round,
+			join_segments: 4,
+			..default()
+		},
+	);
+	let round = positions(&buf);
+	assert_eq!(round.len(), 10 + 2 + 3);
+	assert!(round.iter().all(|p| p.x <= 10.0 + 1.0 + 1e-5));
+	assert!(round.iter().any(|p| p.x > 10.9));
+
+	let corner = Line::from_vecs(1.0, [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)]);
+
+	corner.write_buffered_geometry_into(&mut buf, LineGeometryProps::default());
+	assert_eq!(buf.vertices.len(), 10);
+	assert!(contains_approx(&positions(&buf), vec2(11.0, -1.0)));
+
+	corner.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			join_style: JoinStyle::Bevel,
+			..default()
+		},
+	);
+	let bevel = positions(&buf);
+	// one additional outer point, the inner side keeps the mitre
+	assert_eq!(bevel.len(), 11);
+	assert!(contains_approx(&bevel, vec2(10.0, -1.0)));
+	assert!(contains_approx(&bevel, vec2(11.0, 0.0)));
+	assert!(contains_approx(&bevel, vec2(9.0, 1.0)));
+}
+
+#[test]
+fn split_dashes() {
+	let line = Line::from_vecs(
+		1.0,
+		[
+			vec2(0.0, 0.0),
+			vec2(3.0, 0.0),
+			vec2(6.0, 0.0),
+			vec2(9.0, 0.0),
+			vec2(12.0, 0.0),
+		],
+	);
+
+	let dashes = line.split_dashes(&[4.0, 2.0]);
+	assert_eq!(dashes.len(), 2);
+
+	let points = |l: &Line| l.iter().map(|v| v.pos.x).collect::<Vec<_>>();
+	assert_eq!(points(&dashes[0]), [0.0, 3.0, 4.0]);
+	assert_eq!(points(&dashes[1]), [6.0, 9.0, 10.0]);
+	assert_eq!(dashes[0].line_length(), 4.0);
+	assert_eq!(dashes[1].line_length(), 4.0);
+
+	assert_eq!(line.split_dashes(&[5.0, 1.0]).len(), 2);
+	assert_eq!(line.split_dashes(&[1.0, 1.0]).len(), 6);
+	assert_eq!(line.split_dashes(&[]).len(), 1);
+}
+
+#[test]
+fn dash_pattern_lengths() {
+	let pattern = DashPattern::new(&[4.0, 2.0, 1.0]);
+	assert_eq!(pattern.lengths(), [4.0, 2.0, 1.0]);
+	assert!(DashPattern::new(&[]).lengths().is_empty());
+
+	// props holding a pattern can be reused by value
+	let props = LineGeometryProps {
+		dash_pattern: Some(pattern),
+		..default()
+	};
+	let copy = props;
+	assert_eq!(props.dash_pattern, copy.dash_pattern);
+}
+
+#[test]
+#[should_panic]
+fn dash_pattern_max_len() {
+	DashPattern::new(&[1.0; DashPattern::MAX_LEN + 1]);
+}
+
+#[test]
+fn dashed_buffered_geometry() {
+	let line = Line::from_vecs(1.0, [vec2(0.0, 0.0), vec2(12.0, 0.0)]);
+	let mut buf = BufferedGeometryBuffers::new();
+
+	line.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			dash_pattern: Some(DashPattern::new(&[4.0, 2.0])),
+			..default()
+		},
+	);
+
+	// two dashes with 8 vertices each
+	let xs = positions(&buf).iter().map(|p| p.x).collect::<Vec<_>>();
+	assert_eq!(xs.len(), 16);
+	assert!(xs
+		.iter()
+		.all(|x| (0.0..=4.0).contains(x) || (6.0..=10.0).contains(x)));
+
+	// the dashes are joined by degenerate triangles
+	let max_index = *buf.indices.iter().max().unwrap();
+	assert_eq!(max_index, 15);
+	assert_eq!(buf.indices.len(), 8 + 2 + 8);
+}
+
+#[test]
+fn closed_line() {
+	let corners = [
+		vec2(0.0, 0.0),
+		vec2(10.0, 0.0),
+		vec2(10.0, 10.0),
+		vec2(0.0, 10.0),
+	];
+	let line = Line::from_vecs(1.0, corners);
+	let mut buf = BufferedGeometryBuffers::new();
+
+	line.write_buffered_geometry_into(
+		&mut buf,
+		LineGeometryProps {
+			closed: true,
+			cap_style: CapStyle::Round,
+			..default()
+		},
+	);
+	let ps = positions(&buf);
+
+	// one mitred corner per vertex plus the repeated seam, no cap vertices
+	assert_eq!(ps.len(), 2 * 5);
+	assert!(corners.iter().all(|c| !contains_approx(&ps, *c)));
+	for p in [
+		vec2(-1.0, -1.0),
+		vec2(1.0, 1.0),
+		vec2(11.0, -1.0),
+		vec2(9.0, 1.0),
+		vec2(11.0, 11.0),
+		vec2(9.0, 9.0),
+		vec2(-1.0, 11.0),
+		vec2(1.0, 9.0),
+	] {
+		assert!(contains_approx(&ps, p));
+	}
+
+	// the seam is closed with the first corner
+	assert!(ps[0].distance(ps[8]) < 0.001);
+	assert!(ps[1].distance(ps[9]) < 0.001);
+	assert!(contains_approx(&ps[..2], vec2(-1.0, -1.0)));
+	assert!(contains_approx(&ps[..2], vec2(1.0, 1.0)));
+	assert_eq!(line.line_length(), 30.0);
+	assert_eq!(line.close().line_length(), 40.0);
+}
@@ -3,14 +3,17 @@ use crate::{
 	data_structures::grid::{CoordOpsFn, Grid},
 	rendering::{
 		buffered_geometry::{
-			create_buffered_geometry_layout, BufferedGeometry, BufferedVertexData,
-			OverrideAttributesWith, RenderingPrimitive, VertexFormat, VertexType,
+			create_buffered_geometry_layout, decode_indices, AttributeType, BufferedGeometry,
+			BufferedVertexData, FromAttributes, GeometryError, OverrideAttributesWith,
+			RenderingPrimitive, VertexFormat, VertexType,
 		},
+		objects::Axis,
 		RenderableBuffer,
 	},
 	utils::default,
 };
-use glam::Vec3;
+use glam::{Vec2, Vec3, Vec4};
+use lerp::Lerp;
 use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug)]
@@ -21,6 +24,7 @@ where
 	pub vertices: Vec<usize>,
 	pub face_normal: Option<Vec3>,
 	pub data: Option<V>,
+	pub smoothing_group: Option<u32>,
 }
 
 impl<V> Face<V>
@@ -33,6 +37,7 @@ where
 		v3_idx: usize,
 		normal: Option<Vec3>,
 		data: Option<V>,
+		smoothing_group: Option<u32>,
 	) -> Face<V> {
 		if v1_idx == v2_idx || v1_idx == v3_idx || v2_idx == v3_idx {
 			panic!("Face must have 3 unique vertices");
@@ -46,6 +51,7 @@ where
 			face_normal: normal,
 			vertices,
 			data,
+			smoothing_group,
 		}
 	}
 
@@ -56,6 +62,7 @@ where
 		v4_idx: usize,
 		normal: Option<Vec3>,
 		data: Option<V>,
+		smoothing_group: Option<u32>,
 	) -> Face<V> {
 		if v1_idx == v2_idx
 			|| v1_idx == v3_idx
@@ -76,22 +83,116 @@ where
 			face_normal: normal,
 			vertices,
 			data,
+			smoothing_group,
 		}
 	}
+
+	/// Reverses the winding order and negates the face normal.
+	pub fn flip(&mut self) {
+		self.vertices.reverse();
+		self.face_normal = self.face_normal.map(|n| -n);
+	}
 }
 
 pub trait Position3D {
 	fn position(&self) -> Vec3;
 }
 
-#[derive(PartialEq)]
+/// Writable position of a vertex, for operations that create moved copies of vertices.
+pub trait SetPosition3D {
+	fn set_position(&mut self, pos: Vec3);
+}
+
+#[derive(PartialEq, Clone, Copy)]
 pub enum MeshBufferType {
 	NoNormals,
 	VertexNormals,
 	VertexNormalFaceData,
 	FaceNormals,
+	/// Vertex normals, that only average adjacent faces within `angle_threshold` (in radians)
+	/// of the face normal. Vertices on sharper edges are split, to keep the edges hard.
+	SmoothNormals {
+		angle_threshold: f32,
+	},
+}
+
+/// Texture coordinates of a vertex, for tangent generation.
+pub trait HasUv {
+	fn uv(&self) -> Vec2;
+}
+
+/// Writable texture coordinates of a vertex, for UV projection.
+pub trait SetUv {
+	fn set_uv(&mut self, uv: Vec2);
+}
+
+/// Per vertex tangents of a triangle list, following Lengyel's method.
+/// The tangents point along the U axis and are orthogonalized against the normals.
+/// `w` holds the handedness of the bitangent, which is `normal.cross(tangent) * w`.
+pub fn generate_tangents(
+	positions: &[Vec3],
+	normals: &[Vec3],
+	uvs: &[Vec2],
+	indices: &[u32],
+) -> Vec<Vec4> {
+	let mut tan1 = vec![Vec3::ZERO; positions.len()];
+	let mut tan2 = vec![Vec3::ZERO; positions.len()];
+
+	for tri in indices.chunks_exact(3) {
+		let [i1, i2, i3] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+
+		let x1 = positions[i2] - positions[i1];
+		let x2 = positions[i3] - positions[i1];
+		let w1 = uvs[i2] - uvs[i1];
+		let w2 = uvs[i3] - uvs[i1];
+
+		let det = w1.x * w2.y - w2.x * w1.y;
+		if det.abs() <= f32::EPSILON {
+			continue;
+		}
+		let r = 1.0 / det;
+
+		let sdir = (x1 * w2.y - x2 * w1.y) * r;
+		let tdir = (x2 * w1.x - x1 * w2.x) * r;
+
+		for i in [i1, i2, i3] {
+			tan1[i] += sdir;
+			tan2[i] += tdir;
+		}
+	}
+
+	normals
+		.iter()
+		.enumerate()
+		.map(|(i, n)| {
+			let t = (tan1[i] - *n * n.dot(tan1[i])).normalize_or_zero();
+			let w = if n.cross(tan1[i]).dot(tan2[i]) < 0.0 {
+				-1.0
+			} else {
+				1.0
+			};
+			t.extend(w)
+		})
+		.collect()
 }
 
+/// Joint indices and blend weights of a vertex, for skinned meshes.
+pub trait HasSkinning {
+	fn joints(&self) -> [u8; 4];
+	fn weights(&self) -> [f32; 4];
+}
+
+/// Scales the weights to sum up to 1. Weights that are all zero are kept.
+pub fn normalize_weights(weights: [f32; 4]) -> [f32; 4] {
+	let sum: f32 = weights.iter().sum();
+	if sum <= 0.0 {
+		return weights;
+	}
+	weights.map(|w| w / sum)
+}
+
+type SkinWriter<V> = fn(&V, &mut Vec<u8>);
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct SectionIndex {
 	pub section: usize,
@@ -124,6 +225,45 @@ where
 			.map(|section_index| section_index.index)
 			.collect()
 	}
+
+	fn smoothing_group_faces(
+		&self,
+		faces: &[Face<V>],
+		section: usize,
+		smoothing_group: Option<u32>,
+	) -> Vec<usize> {
+		self.section_faces(section)
+			.into_iter()
+			.filter(|i| faces[*i].smoothing_group == smoothing_group)
+			.collect()
+	}
+}
+
+/// Statistics collected while building and exporting a [`MeshGeometry`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MeshStats {
+	/// Number of added face vertices that were merged into an existing vertex with the same position.
+	pub welded_vertices: usize,
+	/// Number of quads that were split into two triangles.
+	pub triangulated_quads: usize,
+	/// Number of zero area triangles skipped during the last export.
+	pub degenerate_faces: usize,
+	/// Number of vertices in the last exported buffer.
+	pub final_vertex_count: u32,
+	/// Number of indices in the last exported buffer.
+	pub final_index_count: u32,
+}
+
+/// Plain parallel arrays of an exported mesh, for libraries that don't use [`BufferedGeometry`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshArrays {
+	pub positions: Vec<Vec3>,
+	/// `None` for [`MeshBufferType::NoNormals`].
+	pub normals: Option<Vec<Vec3>>,
+	/// Read from a `uv` attribute of the vertex layout, if there is one.
+	pub uvs: Option<Vec<Vec2>>,
+	/// Triangle list indices. Buffer types without index buffer get sequential indices.
+	pub indices: Vec<u32>,
 }
 
 pub struct MeshGeometry<V>
@@ -134,6 +274,8 @@ where
 	faces: BTreeMap<usize, Vec<Face<V>>>,
 	next_index: usize,
 	vertex_indices: HashMap<VertIdx3f, usize>,
+	stats: MeshStats,
+	triangulated: bool,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -144,6 +286,9 @@ where
 	pub normal: Option<Vec3>,
 	pub data: Option<V>,
 	pub section: Option<usize>,
+	/// Vertex normals are only averaged across faces of the same smoothing group.
+	/// Faces without a smoothing group are smoothed together.
+	pub smoothing_group: Option<u32>,
 }
 
 impl<V> Default for FaceDataProps<V>
@@ -155,6 +300,7 @@ where
 			normal: None,
 			data: None,
 			section: None,
+			smoothing_group: None,
 		}
 	}
 }
@@ -175,6 +321,10 @@ where
 		self.section = Some(section);
 		self
 	}
+	pub fn with_smoothing_group(&mut self, smoothing_group: u32) -> &mut Self {
+		self.smoothing_group = Some(smoothing_group);
+		self
+	}
 }
 
 pub fn face_normal<V>(normal: Vec3) -> FaceDataProps<V>
@@ -185,6 +335,7 @@ where
 		normal: Some(normal),
 		data: None,
 		section: None,
+		smoothing_group: None,
 	}
 }
 
@@ -196,6 +347,7 @@ where
 		normal: None,
 		data: Some(data),
 		section: None,
+		smoothing_group: None,
 	}
 }
 
@@ -207,6 +359,19 @@ where
 		normal: None,
 		data: None,
 		section: Some(section),
+		smoothing_group: None,
+	}
+}
+
+pub fn face_smoothing_group<V>(smoothing_group: u32) -> FaceDataProps<V>
+where
+	V: OverrideAttributesWith + Position3D,
+{
+	FaceDataProps {
+		normal: None,
+		data: None,
+		section: None,
+		smoothing_group: Some(smoothing_group),
 	}
 }
 
@@ -220,13 +385,15 @@ where
 			faces: BTreeMap::new(),
 			next_index: 0,
 			vertex_indices: HashMap::new(),
+			stats: MeshStats::default(),
+			triangulated: true,
 		}
 	}
 
 	pub fn add_face3_data(&mut self, v1: V, v2: V, v3: V, data: FaceDataProps<V>) {
-		let v1_idx = self.get_vertex_index(v1.position());
-		let v2_idx = self.get_vertex_index(v2.position());
-		let v3_idx = self.get_vertex_index(v3.position());
+		let v1_idx = self.weld_vertex_index(v1.position());
+		let v2_idx = self.weld_vertex_index(v2.position());
+		let v3_idx = self.weld_vertex_index(v3.position());
 
 		let section = data.section.unwrap_or(0);
 		let faces = self.faces.entry(section).or_insert_with(Vec::new);
@@ -236,7 +403,14 @@ where
 			section,
 		};
 
-		let face = Face::face3(v1_idx, v2_idx, v3_idx, data.normal, data.data);
+		let face = Face::face3(
+			v1_idx,
+			v2_idx,
+			v3_idx,
+			data.normal,
+			data.data,
+			data.smoothing_group,
+		);
 		faces.push(face);
 
 		self.add_vertex(v1_idx, face_idx, v1);
@@ -245,10 +419,10 @@ where
 	}
 
 	pub fn add_face4_data(&mut self, v1: V, v2: V, v3: V, v4: V, data: FaceDataProps<V>) {
-		let v1_idx = self.get_vertex_index(v1.position());
-		let v2_idx = self.get_vertex_index(v2.position());
-		let v3_idx = self.get_vertex_index(v3.position());
-		let v4_idx = self.get_vertex_index(v4.position());
+		let v1_idx = self.weld_vertex_index(v1.position());
+		let v2_idx = self.weld_vertex_index(v2.position());
+		let v3_idx = self.weld_vertex_index(v3.position());
+		let v4_idx = self.weld_vertex_index(v4.position());
 
 		let section = data.section.unwrap_or(0);
 		let faces = self.faces.entry(section).or_insert_with(Vec::new);
@@ -258,7 +432,17 @@ where
 			section,
 		};
 
-		let face = Face::face4(v1_idx, v2_idx, v3_idx, v4_idx, data.normal, data.data);
+		self.triangulated = false;
+
+		let face = Face::face4(
+			v1_idx,
+			v2_idx,
+			v3_idx,
+			v4_idx,
+			data.normal,
+			data.data,
+			data.smoothing_group,
+		);
 		faces.push(face);
 
 		self.add_vertex(v1_idx, face_idx, v1);
@@ -312,20 +496,174 @@ where
 		&self.faces.get(&i.section).unwrap()[i.index]
 	}
 
-	fn triangulate(&mut self) {
+	/// Statistics about welded vertices and triangulated quads,
+	/// and about the result of the last `to_*_by_type` export.
+	pub fn stats(&self) -> MeshStats {
+		self.stats
+	}
+
+	/// Min and max corners of the box around all vertex positions. Faces are not considered.
+	/// Returns a zero sized box at the origin for meshes without vertices.
+	pub fn bounding_box(&self) -> (Vec3, Vec3) {
+		if self.vertices.is_empty() {
+			return (Vec3::ZERO, Vec3::ZERO);
+		}
+		self.vertices
+			.iter()
+			.map(|v| v.data.position())
+			.fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), pos| {
+				(min.min(pos), max.max(pos))
+			})
+	}
+
+	/// Center of the bounding box.
+	pub fn center(&self) -> Vec3 {
+		let (min, max) = self.bounding_box();
+		(min + max) * 0.5
+	}
+
+	/// Center and radius of a sphere around the bounding box center, containing all vertices.
+	pub fn bounding_sphere(&self) -> (Vec3, f32) {
+		let center = self.center();
+		let radius = self
+			.vertices
+			.iter()
+			.map(|v| v.data.position().distance(center))
+			.fold(0.0, f32::max);
+		(center, radius)
+	}
+
+	/// Reverses the winding order of all faces, turning them inside out.
+	pub fn flip_faces(&mut self) {
+		self.faces.values_mut().flatten().for_each(Face::flip);
+	}
+
+	/// Reverses the winding order of all faces in a section.
+	pub fn flip_section(&mut self, section: usize) {
+		if let Some(faces) = self.faces.get_mut(&section) {
+			faces.iter_mut().for_each(Face::flip);
+		}
+	}
+
+	/// Flips faces, so that neighbouring faces have a consistent winding order.
+	/// The orientation spreads from the first face of each connected patch across shared edges.
+	/// Afterwards, patches enclosing a negative volume are flipped entirely,
+	/// so that closed meshes end up with outward pointing face normals.
+	pub fn make_normals_consistent(&mut self) {
+		let face_keys = self
+			.faces
+			.iter()
+			.flat_map(|(section, faces)| (0..faces.len()).map(|index| (*section, index)))
+			.collect::<Vec<_>>();
+		let face_vertices = |faces: &BTreeMap<usize, Vec<Face<V>>>,
+		                     (section, index): (usize, usize)| {
+			faces[&section][index].vertices.clone()
+		};
+		let edges = |verts: &[usize]| {
+			(0..verts.len())
+				.map(|i| (verts[i], verts[(i + 1) % verts.len()]))
+				.collect::<Vec<_>>()
+		};
+
+		let mut edge_faces = HashMap::<(usize, usize), Vec<usize>>::new();
+		for (f, key) in face_keys.iter().enumerate() {
+			for (a, b) in edges(&face_vertices(&self.faces, *key)) {
+				edge_faces.entry((a.min(b), a.max(b))).or_default().push(f);
+			}
+		}
+
+		let mut flip = vec![false; face_keys.len()];
+		let mut visited = vec![false; face_keys.len()];
+
+		for start in 0..face_keys.len() {
+			if visited[start] {
+				continue;
+			}
+			visited[start] = true;
+			let mut patch = vec![start];
+			let mut queue = std::collections::VecDeque::from([start]);
+
+			while let Some(f) = queue.pop_front() {
+				let mut verts = face_vertices(&self.faces, face_keys[f]);
+				if flip[f] {
+					verts.reverse();
+				}
+				for (a, b) in edges(&verts) {
+					for &n in &edge_faces[&(a.min(b), a.max(b))] {
+						if visited[n] {
+							continue;
+						}
+						// a consistent neighbour traverses the shared edge in opposite direction
+						let neighbour_edges = edges(&face_vertices(&self.faces, face_keys[n]));
+						flip[n] = neighbour_edges.contains(&(a, b));
+						visited[n] = true;
+						patch.push(n);
+						queue.push_back(n);
+					}
+				}
+			}
+
+			let volume: f32 = patch
+				.iter()
+				.map(|f| {
+					let mut verts = face_vertices(&self.faces, face_keys[*f]);
+					if flip[*f] {
+						verts.reverse();
+					}
+					let pos = verts
+						.iter()
+						.map(|v| self.vertices[*v].data.position())
+						.collect::<Vec<_>>();
+					(1..pos.len() - 1)
+						.map(|i| pos[0].dot(pos[i].cross(pos[i + 1])))
+						.sum::<f32>()
+				})
+				.sum();
+
+			if volume < 0.0 {
+				for f in patch {
+					flip[f] = !flip[f];
+				}
+			}
+		}
+
+		for (f, (section, index)) in face_keys.into_iter().enumerate() {
+			if flip[f] {
+				self.faces.get_mut(&section).unwrap()[index].flip();
+			}
+		}
+	}
+
+	/// Splits all quads into two triangles. Does nothing if no quads were added since the last call.
+	/// Buffer exports triangulate the mesh automatically.
+	pub fn triangulate(&mut self) {
+		if self.triangulated {
+			return;
+		}
+		self.triangulated = true;
+
 		let vertices = &mut self.vertices;
 		for (section, faces) in self.faces.iter_mut() {
 			let quads = faces
 				.iter()
 				.enumerate()
 				.filter(|(_, f)| f.vertices.len() == 4)
-				.map(|(i, f)| (i, f.vertices.clone(), f.face_normal, f.data))
+				.map(|(i, f)| {
+					(
+						i,
+						f.vertices.clone(),
+						f.face_normal,
+						f.data,
+						f.smoothing_group,
+					)
+				})
 				.rev()
 				.collect::<Vec<_>>();
 
 			let section = *section;
+			self.stats.triangulated_quads += quads.len();
 
-			for (i, verts, normal, data) in quads {
+			for (i, verts, normal, data, group) in quads {
 				Self::remove_face_internal(faces, vertices, SectionIndex { section, index: i });
 
 				let face_idx1 = SectionIndex {
@@ -333,7 +671,7 @@ where
 					index: faces.len(),
 				};
 
-				let f = Face::face3(verts[0], verts[1], verts[2], normal, data);
+				let f = Face::face3(verts[0], verts[1], verts[2], normal, data, group);
 				faces.push(f);
 
 				Self::add_vertex_face(vertices, verts[0], face_idx1);
@@ -345,7 +683,7 @@ where
 					index: faces.len(),
 				};
 
-				let f = Face::face3(verts[0], verts[2], verts[3], normal, data);
+				let f = Face::face3(verts[0], verts[2], verts[3], normal, data, group);
 				faces.push(f);
 
 				Self::add_vertex_face(vertices, verts[0], face_idx2);
@@ -366,6 +704,94 @@ where
 		}
 	}
 
+	/// Merges vertices within `epsilon` distance of each other into the first of them,
+	/// and rewires faces and position lookups to the merged vertex.
+	/// Vertices that share a face are never merged, so that no face collapses.
+	/// Returns the number of removed vertices.
+	///
+	/// # Panics
+	///
+	/// Panics if `epsilon` is not positive.
+	pub fn weld_vertices(&mut self, epsilon: f32) -> usize {
+		assert!(
+			epsilon > 0.0,
+			"weld epsilon must be positive, got {}",
+			epsilon
+		);
+		// i64 cells don't overflow for far away vertices, as i32 cells would.
+		let cell = |pos: Vec3| {
+			pos.to_array()
+				.map(|v| (v as f64 / epsilon as f64).floor() as i64)
+		};
+
+		let mut mapping = (0..self.vertices.len()).collect::<Vec<_>>();
+		let mut cells = HashMap::<[i64; 3], Vec<usize>>::new();
+
+		for i in 0..self.vertices.len() {
+			let pos = self.vertices[i].data.position();
+			let c = cell(pos);
+
+			let shares_face = |r: usize, mapping: &[usize]| {
+				self.vertices[i].faces.iter().any(|f| {
+					self.faces[&f.section][f.index]
+						.vertices
+						.iter()
+						.any(|v| *v != i && mapping[*v] == r)
+				})
+			};
+
+			let offsets = |c: i64| (-1..=1).filter_map(move |d| c.checked_add(d));
+			let rep = offsets(c[0])
+				.flat_map(|x| {
+					offsets(c[1]).flat_map(move |y| offsets(c[2]).map(move |z| [x, y, z]))
+				})
+				.filter_map(|neighbour| cells.get(&neighbour))
+				.flatten()
+				.copied()
+				.find(|r| {
+					self.vertices[*r].data.position().distance(pos) <= epsilon
+						&& !shares_face(*r, &mapping)
+				});
+
+			match rep {
+				Some(r) => mapping[i] = r,
+				None => cells.entry(c).or_default().push(i),
+			}
+		}
+
+		let old_vertices = std::mem::take(&mut self.vertices);
+		let mut new_index = vec![0; old_vertices.len()];
+		for (i, vertex) in old_vertices.into_iter().enumerate() {
+			if mapping[i] == i {
+				new_index[i] = self.vertices.len();
+				self.vertices.push(vertex);
+			} else {
+				new_index[i] = new_index[mapping[i]];
+				self.vertices[new_index[i]].faces.extend(vertex.faces);
+			}
+		}
+
+		for face in self.faces.values_mut().flatten() {
+			for v in face.vertices.iter_mut() {
+				*v = new_index[*v];
+			}
+		}
+
+		for idx in self.vertex_indices.values_mut() {
+			*idx = new_index[*idx];
+		}
+		self.next_index = self.vertices.len();
+
+		mapping.len() - self.vertices.len()
+	}
+
+	fn weld_vertex_index(&mut self, pos: Vec3) -> usize {
+		if self.vertex_indices.contains_key(&pos.into()) {
+			self.stats.welded_vertices += 1;
+		}
+		self.get_vertex_index(pos)
+	}
+
 	fn remove_face_internal(
 		faces: &mut Vec<Face<V>>,
 		vertices: &mut Vec<MeshVertex<V>>,
@@ -412,13 +838,76 @@ where
 		)
 	}
 
+	/// Panics in debug builds if vertices and faces don't reference each other consistently,
+	/// e.g. after a face removal left a stale face index on a vertex. Does nothing in release builds.
+	pub fn debug_assert_integrity(&self) {
+		if cfg!(debug_assertions) {
+			if let Some(error) = self.integrity_error() {
+				panic!("MeshGeometry integrity violated: {}", error);
+			}
+		}
+	}
+
+	fn integrity_error(&self) -> Option<String> {
+		for (i, vertex) in self.vertices.iter().enumerate() {
+			for (j, face_idx) in vertex.faces.iter().enumerate() {
+				let Some(face) = self
+					.faces
+					.get(&face_idx.section)
+					.and_then(|faces| faces.get(face_idx.index))
+				else {
+					return Some(format!(
+						"vertex {} references missing face {:?}",
+						i, face_idx
+					));
+				};
+				if !face.vertices.contains(&i) {
+					return Some(format!(
+						"vertex {} references face {:?}, which does not contain it",
+						i, face_idx
+					));
+				}
+				if vertex.faces[..j].contains(face_idx) {
+					return Some(format!("vertex {} references face {:?} twice", i, face_idx));
+				}
+			}
+		}
+
+		for (section, faces) in &self.faces {
+			for (index, face) in faces.iter().enumerate() {
+				let face_idx = SectionIndex {
+					section: *section,
+					index,
+				};
+				for v in &face.vertices {
+					let Some(vertex) = self.vertices.get(*v) else {
+						return Some(format!(
+							"face {:?} references missing vertex {}",
+							face_idx, v
+						));
+					};
+					if !vertex.faces.contains(&face_idx) {
+						return Some(format!(
+							"face {:?} is missing in the faces of vertex {}",
+							face_idx, v
+						));
+					}
+				}
+			}
+		}
+
+		None
+	}
+
 	pub fn set_vertex(&mut self, vertex_idx: usize, data: V) {
 		if let Some(vertex) = self.vertices.get_mut(vertex_idx) {
 			vertex.data = data
 		}
 	}
 
-	fn generate_face_normals(&mut self) {
+	/// Calculates the normals of all faces that don't have a normal yet.
+	/// Buffer exports generate missing face normals automatically.
+	pub fn generate_face_normals(&mut self) {
 		for (_, faces) in self.faces.iter_mut() {
 			for face in faces.iter_mut() {
 				if face.face_normal.is_none() {
@@ -445,7 +934,7 @@ where
 					}
 
 					let normal = v2.cross(v1);
-					face.face_normal = Some(normal.normalize());
+					face.face_normal = Some(normal.normalize_or_zero());
 				}
 			}
 		}
@@ -474,38 +963,332 @@ where
 		v.faces.push(face_idx);
 	}
 
+	fn is_degenerate_face(vertices: &[MeshVertex<V>], face: &Face<V>) -> bool {
+		let pos0 = vertices[face.vertices[0]].data.position();
+		let pos1 = vertices[face.vertices[1]].data.position();
+		let pos2 = vertices[face.vertices[2]].data.position();
+		let edge1 = pos1 - pos0;
+		let edge2 = pos2 - pos0;
+		// Compares the sine of the corner angle, so that the check doesn't depend on the mesh scale.
+		edge1.cross(edge2).length_squared()
+			<= 1e-12 * edge1.length_squared() * edge2.length_squared()
+	}
+
 	fn calculate_vertex_normal(faces: &Vec<Face<V>>, face_indices: &Vec<usize>) -> Vec3 {
 		let mut normal = Vec3::ZERO;
 		for face_idx in face_indices {
 			let face = &faces[*face_idx];
-			normal += face.face_normal.unwrap();
+			normal += face.face_normal.unwrap_or_default();
 		}
 		normal.normalize_or_zero()
 	}
 }
 
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + SetPosition3D,
+{
+	/// Extrudes the faces of a section by `offset` into a solid.
+	/// The faces are duplicated and moved by `offset`, and side walls are stitched
+	/// between the boundary edges of the original and the moved faces.
+	/// Edges shared by two faces of the section don't get walls.
+	/// The faces on the side facing away from `offset` are flipped, so that the solid is closed
+	/// with outward pointing normals. All new faces are added to the same section.
+	pub fn extrude(&mut self, section: usize, offset: Vec3) {
+		let Some(faces) = self.faces.get(&section) else {
+			return;
+		};
+
+		let moved = |data: V| {
+			let mut data = data;
+			data.set_position(data.position() + offset);
+			data
+		};
+
+		let mut edge_counts: HashMap<(usize, usize), usize> = HashMap::new();
+		let mut area_normal = Vec3::ZERO;
+		for face in faces {
+			let verts = &face.vertices;
+			for i in 0..verts.len() {
+				let (a, b) = (verts[i], verts[(i + 1) % verts.len()]);
+				*edge_counts.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+				let (pa, pb) = (
+					self.vertices[a].data.position(),
+					self.vertices[b].data.position(),
+				);
+				area_normal += pa.cross(pb);
+			}
+		}
+		// Faces pointing along the offset become the top of the solid, otherwise the bottom.
+		let along_normal = area_normal.dot(offset) >= 0.0;
+
+		let mut caps = vec![];
+		let mut walls = vec![];
+		for face in faces {
+			let mut verts = face.vertices.clone();
+			let mut normal = face.face_normal;
+			if !along_normal {
+				verts.reverse();
+				normal = normal.map(|n| -n);
+			}
+
+			for i in 0..verts.len() {
+				let (a, b) = (verts[i], verts[(i + 1) % verts.len()]);
+				if edge_counts[&(a.min(b), a.max(b))] == 1 {
+					let (a, b) = (self.vertices[a].data, self.vertices[b].data);
+					walls.push([a, b, moved(b), moved(a)]);
+				}
+			}
+
+			let cap: Vec<V> = verts
+				.iter()
+				.map(|i| moved(self.vertices[*i].data))
+				.collect();
+			let props = FaceDataProps {
+				normal,
+				data: face.data,
+				section: Some(section),
+				smoothing_group: face.smoothing_group,
+			};
+			caps.push((cap, props));
+		}
+
+		if along_normal {
+			self.flip_section(section);
+		}
+
+		for (cap, props) in caps {
+			match cap[..] {
+				[v1, v2, v3] => self.add_face3_data(v1, v2, v3, props),
+				[v1, v2, v3, v4] => self.add_face4_data(v1, v2, v3, v4, props),
+				_ => {}
+			}
+		}
+
+		for [v1, v2, v3, v4] in walls {
+			self.add_face4_data(v1, v2, v3, v4, face_section(section));
+		}
+	}
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + SetUv,
+{
+	/// Sets the UVs of all vertices by projecting their positions onto the plane perpendicular to `axis`.
+	/// U and V map to X and Y along the Z axis, to Y and Z along the X axis and to X and Z along the Y axis,
+	/// normalized to 0..1 over the bounding box.
+	pub fn project_uv_planar(&mut self, axis: Axis) {
+		let (min, max) = self.bounding_box();
+		let size = max - min;
+		let to_uv = |p: Vec3| match axis {
+			Axis::X => Vec2::new(p.y, p.z),
+			Axis::Y => Vec2::new(p.x, p.z),
+			Axis::Z => Vec2::new(p.x, p.y),
+		};
+		let (min, size) = (to_uv(min), to_uv(size));
+		let inv_size = Vec2::select(size.cmpgt(Vec2::ZERO), size.recip(), Vec2::ZERO);
+
+		for vertex in self.vertices.iter_mut() {
+			let uv = (to_uv(vertex.data.position()) - min) * inv_size;
+			vertex.data.set_uv(uv);
+		}
+	}
+
+	/// Sets the UVs of all vertices from their direction to the bounding box center.
+	/// U runs around the Y axis, starting at the negative X axis, V runs from the bottom to the top pole.
+	pub fn project_uv_spherical(&mut self) {
+		let center = self.center();
+		for vertex in self.vertices.iter_mut() {
+			let dir = (vertex.data.position() - center).normalize_or_zero();
+			let u = dir.z.atan2(dir.x) / std::f32::consts::TAU + 0.5;
+			let v = dir.y.clamp(-1.0, 1.0).asin() / std::f32::consts::PI + 0.5;
+			vertex.data.set_uv(Vec2::new(u, v));
+		}
+	}
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + Lerp<f32>,
+{
+	/// Splits every triangle into four, by adding a vertex at the middle of each edge.
+	/// Quads are triangulated first. Midpoint vertices are interpolated between the edge vertices
+	/// and shared between adjacent faces. Face data, normals, sections and smoothing groups
+	/// are kept for all new faces.
+	pub fn subdivide(&mut self, iterations: u32) {
+		for _ in 0..iterations {
+			self.triangulate();
+
+			let vertices = std::mem::take(&mut self.vertices);
+			let faces = std::mem::take(&mut self.faces);
+			self.next_index = 0;
+			self.vertex_indices.clear();
+			let stats = self.stats;
+
+			for (section, faces) in faces {
+				for face in faces {
+					let [v1, v2, v3] = [0, 1, 2].map(|i| vertices[face.vertices[i]].data);
+					let [m12, m23, m31] =
+						[(v1, v2), (v2, v3), (v3, v1)].map(|(a, b)| a.lerp(b, 0.5));
+
+					let props = FaceDataProps {
+						normal: face.face_normal,
+						data: face.data,
+						section: Some(section),
+						smoothing_group: face.smoothing_group,
+					};
+
+					self.add_face3_data(v1, m12, m31, props);
+					self.add_face3_data(m12, v2, m23, props);
+					self.add_face3_data(m31, m23, v3, props);
+					self.add_face3_data(m12, m23, m31, props);
+				}
+			}
+
+			self.stats = stats;
+		}
+	}
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + FromAttributes,
+{
+	/// Rebuilds a mesh from a triangle geometry, e.g. one exported with `to_buffered_geometry_by_type`.
+	/// Vertices are read back with `FromAttributes` and welded by position,
+	/// so split vertices of flat shaded exports are merged again.
+	/// Triangles that collapse to less than three distinct positions are skipped.
+	pub fn from_buffered_geometry(geom: &BufferedGeometry) -> Result<Self, GeometryError> {
+		if geom.rendering_primitive != RenderingPrimitive::Triangles {
+			return Err(GeometryError::UnsupportedPrimitive {
+				primitive: geom.rendering_primitive,
+			});
+		}
+		geom.validate_indices()?;
+
+		let vertices = (0..geom.buffer_vertex_count() as usize)
+			.map(|i| V::from_attributes(&geom.vertex_attributes(i)))
+			.collect::<Vec<_>>();
+		let indices = geom
+			.decoded_indices()
+			.unwrap_or_else(|| (0..vertices.len() as u32).collect());
+
+		let mut mesh = MeshGeometry::new();
+		for triangle in indices.chunks_exact(3) {
+			let [v1, v2, v3] = [0, 1, 2].map(|i| vertices[triangle[i] as usize]);
+			let [p1, p2, p3] = [v1, v2, v3].map(|v| VertIdx3f::from(v.position()));
+			if p1 == p2 || p2 == p3 || p3 == p1 {
+				continue;
+			}
+			mesh.add_face3(v1, v2, v3);
+		}
+
+		Ok(mesh)
+	}
+}
+
 impl<V> MeshGeometry<V>
 where
 	V: OverrideAttributesWith + Position3D + Copy + bytemuck::Pod,
 {
+	/// Meshes without faces result in an empty buffer with zero counts and no index buffer,
+	/// even if they still contain vertices.
 	pub fn to_renderable_buffer_by_type(&mut self, geom_type: MeshBufferType) -> RenderableBuffer {
+		self.build_renderable_buffer(geom_type, None, None)
+	}
+
+	/// One buffer per section, e.g. to render sections with different materials.
+	/// Vertices and indices are local to each buffer.
+	pub fn to_renderable_buffers_by_section(
+		&mut self,
+		geom_type: MeshBufferType,
+	) -> BTreeMap<usize, RenderableBuffer> {
+		let sections: Vec<usize> = self.faces.keys().copied().collect();
+		let mut stats = self.stats;
+		stats.degenerate_faces = 0;
+		stats.final_vertex_count = 0;
+		stats.final_index_count = 0;
+
+		let buffers = sections
+			.into_iter()
+			.map(|section| {
+				let buffer = self.build_renderable_buffer(geom_type, None, Some(section));
+				stats.degenerate_faces += self.stats.degenerate_faces;
+				stats.final_vertex_count += self.stats.final_vertex_count;
+				stats.final_index_count += self.stats.final_index_count;
+				(section, buffer)
+			})
+			.collect();
+
+		self.stats = stats;
+		buffers
+	}
+
+	fn section_faces(
+		faces: &BTreeMap<usize, Vec<Face<V>>>,
+		section: Option<usize>,
+	) -> impl Iterator<Item = (&usize, &Vec<Face<V>>)> {
+		faces
+			.iter()
+			.filter(move |(s, _)| section.is_none() || section == Some(**s))
+	}
+
+	/// Builds the buffer of a single section, or of all sections if `section` is `None`.
+	fn build_renderable_buffer(
+		&mut self,
+		geom_type: MeshBufferType,
+		skin: Option<SkinWriter<V>>,
+		section: Option<usize>,
+	) -> RenderableBuffer {
 		let mut buffer = vec![];
 		let mut indices = vec![];
 		let mut vertex_count = 0;
+		let mut degenerate_faces = 0;
+
+		if Self::section_faces(&self.faces, section).all(|(_, faces)| faces.is_empty()) {
+			self.stats.degenerate_faces = 0;
+			self.stats.final_vertex_count = 0;
+			self.stats.final_index_count = 0;
+
+			return RenderableBuffer {
+				vertex_buffer: buffer,
+				index_buffer: None,
+				vertex_count,
+				index_count: 0,
+			};
+		}
 
 		match geom_type {
 			MeshBufferType::NoNormals => {
 				self.triangulate();
 
-				for vertex in self.vertices.iter() {
-					buffer.extend(bytemuck::bytes_of(&vertex.data));
-					vertex_count += 1;
+				// All vertices are exported in order for the whole mesh,
+				// single sections only export the vertices they use.
+				if section.is_none() {
+					for vertex in self.vertices.iter() {
+						buffer.extend(bytemuck::bytes_of(&vertex.data));
+						vertex_count += 1;
+					}
 				}
+				let mut section_vert_indices = HashMap::<usize, u32>::new();
 
-				for (_, faces) in self.faces.iter() {
+				for (_, faces) in Self::section_faces(&self.faces, section) {
 					for face in faces {
+						if Self::is_degenerate_face(&self.vertices, face) {
+							degenerate_faces += 1;
+							continue;
+						}
 						for v in &face.vertices {
-							let i = *v as u32;
+							let i = if section.is_none() {
+								*v as u32
+							} else {
+								*section_vert_indices.entry(*v).or_insert_with(|| {
+									buffer.extend(bytemuck::bytes_of(&self.vertices[*v].data));
+									vertex_count += 1;
+									vertex_count - 1
+								})
+							};
 							indices.extend(bytemuck::bytes_of(&i))
 						}
 					}
@@ -516,56 +1299,85 @@ where
 				self.generate_face_normals();
 				self.triangulate();
 
-				let mut section_vert_indices = BTreeMap::<usize, BTreeMap<usize, usize>>::new();
-				let mut section_vertices = BTreeMap::<usize, Vec<usize>>::new();
+				for (section, faces) in Self::section_faces(&self.faces, section) {
+					// buffer index of each vertex per smoothing group within this section
+					let mut group_vert_indices = HashMap::<(usize, Option<u32>), u32>::new();
 
-				for (i, vertex) in self.vertices.iter().enumerate() {
-					for face_idx in &vertex.faces {
-						let section = face_idx.section;
-
-						if !section_vertices.contains_key(&section) {
-							section_vertices.insert(section, vec![]);
+					for face in faces {
+						if Self::is_degenerate_face(&self.vertices, face) {
+							degenerate_faces += 1;
+							continue;
 						}
-
-						if !section_vert_indices.contains_key(&i) {
-							section_vert_indices.insert(i, BTreeMap::new());
+						for v in &face.vertices {
+							let key = (*v, face.smoothing_group);
+
+							let i = *group_vert_indices.entry(key).or_insert_with(|| {
+								let vertex = &self.vertices[*v];
+								let normal = Self::calculate_vertex_normal(
+									faces,
+									&vertex.smoothing_group_faces(
+										faces,
+										*section,
+										face.smoothing_group,
+									),
+								);
+
+								buffer.extend(bytemuck::bytes_of(&vertex.data));
+								buffer.extend(bytemuck::bytes_of(&normal));
+								if let Some(skin) = skin {
+									skin(&vertex.data, &mut buffer);
+								}
+								vertex_count += 1;
+								vertex_count - 1
+							});
+
+							indices.extend(bytemuck::bytes_of(&i));
 						}
-
-						let index = section_vertices.get(&section).unwrap().len();
-
-						section_vert_indices
-							.get_mut(&i)
-							.unwrap()
-							.insert(section, index);
-
-						section_vertices.get_mut(&section).unwrap().push(i);
 					}
 				}
+			}
 
-				let mut idx_offset = 0;
-
-				for (section, faces) in self.faces.iter() {
-					for v_idx in section_vertices.get(&section).unwrap() {
-						let vertex = &self.vertices[*v_idx];
+			MeshBufferType::SmoothNormals { angle_threshold } => {
+				self.generate_face_normals();
+				self.triangulate();
 
-						let normal =
-							Self::calculate_vertex_normal(faces, &vertex.section_faces(*section));
+				let cos_threshold = angle_threshold.cos();
 
-						buffer.extend(bytemuck::bytes_of(&vertex.data));
-						buffer.extend(bytemuck::bytes_of(&normal));
-						vertex_count += 1;
-					}
+				for (section, faces) in Self::section_faces(&self.faces, section) {
+					// buffer index of each vertex per set of averaged faces within this section
+					let mut smooth_vert_indices = HashMap::<(usize, Vec<usize>), u32>::new();
 
 					for face in faces {
+						if Self::is_degenerate_face(&self.vertices, face) {
+							degenerate_faces += 1;
+							continue;
+						}
+						let face_normal = face.face_normal.unwrap_or_default();
+
 						for v in &face.vertices {
-							let section_idx =
-								section_vert_indices.get(v).unwrap().get(&section).unwrap();
-							let i = section_idx + idx_offset;
-							indices.extend(bytemuck::bytes_of(&(i as u32)));
+							let vertex = &self.vertices[*v];
+							let smooth_faces = vertex
+								.smoothing_group_faces(faces, *section, face.smoothing_group)
+								.into_iter()
+								.filter(|i| {
+									faces[*i].face_normal.unwrap_or_default().dot(face_normal)
+										>= cos_threshold
+								})
+								.collect::<Vec<_>>();
+
+							let i = *smooth_vert_indices
+								.entry((*v, smooth_faces))
+								.or_insert_with_key(|(_, smooth_faces)| {
+									let normal = Self::calculate_vertex_normal(faces, smooth_faces);
+									buffer.extend(bytemuck::bytes_of(&vertex.data));
+									buffer.extend(bytemuck::bytes_of(&normal));
+									vertex_count += 1;
+									vertex_count - 1
+								});
+
+							indices.extend(bytemuck::bytes_of(&i));
 						}
 					}
-
-					idx_offset += section_vertices.get(&section).unwrap().len();
 				}
 			}
 
@@ -573,13 +1385,21 @@ where
 				self.generate_face_normals();
 				self.triangulate();
 
-				for (section, faces) in self.faces.iter() {
+				for (section, faces) in Self::section_faces(&self.faces, section) {
 					for face in faces {
+						if Self::is_degenerate_face(&self.vertices, face) {
+							degenerate_faces += 1;
+							continue;
+						}
 						for v in &face.vertices {
-							let vertex = &mut self.vertices[*v];
+							let vertex = &self.vertices[*v];
 							let normal = Self::calculate_vertex_normal(
 								faces,
-								&vertex.section_faces(*section),
+								&vertex.smoothing_group_faces(
+									faces,
+									*section,
+									face.smoothing_group,
+								),
 							);
 							let mut data = vertex.data;
 							if face.data.is_some() {
@@ -596,9 +1416,13 @@ where
 			MeshBufferType::FaceNormals => {
 				self.generate_face_normals();
 				self.triangulate();
-				for (_, faces) in self.faces.iter() {
+				for (_, faces) in Self::section_faces(&self.faces, section) {
 					for face in faces {
-						let normal = face.face_normal.unwrap();
+						if Self::is_degenerate_face(&self.vertices, face) {
+							degenerate_faces += 1;
+							continue;
+						}
+						let normal = face.face_normal.unwrap_or_default();
 						for v in &face.vertices {
 							let mut data = self.vertices[*v].data;
 							if face.data.is_some() {
@@ -615,6 +1439,10 @@ where
 
 		let indices_len = indices.len();
 
+		self.stats.degenerate_faces = degenerate_faces;
+		self.stats.final_vertex_count = vertex_count;
+		self.stats.final_index_count = (indices_len / 4) as u32;
+
 		RenderableBuffer {
 			vertex_buffer: buffer,
 			index_buffer: if indices_len == 0 {
@@ -632,18 +1460,20 @@ impl<V> MeshGeometry<V>
 where
 	V: BufferedVertexData + OverrideAttributesWith + Position3D,
 {
-	pub fn to_buffered_geometry_by_type(&mut self, geom_type: MeshBufferType) -> BufferedGeometry {
+	fn buffer_layout(geom_type: &MeshBufferType) -> Vec<VertexType> {
 		let mut layout: Vec<VertexType> = V::vertex_layout();
 
-		if geom_type != MeshBufferType::NoNormals {
+		if *geom_type != MeshBufferType::NoNormals {
 			layout.push(VertexType {
 				name: "normal",
 				format: VertexFormat::Float32x3,
 			})
 		}
 
-		let buffer = self.to_renderable_buffer_by_type(geom_type);
+		layout
+	}
 
+	fn to_buffered_geometry(layout: Vec<VertexType>, buffer: RenderableBuffer) -> BufferedGeometry {
 		let geom_layout = create_buffered_geometry_layout(layout);
 
 		BufferedGeometry {
@@ -657,10 +1487,172 @@ where
 			vertex_layout: geom_layout.vertex_layout,
 			buffer: buffer.vertex_buffer,
 			indices: buffer.index_buffer,
+			bounds_min: None,
+			bounds_max: None,
+		}
+		.with_bounds()
+	}
+
+	/// Meshes without faces result in a geometry with the full vertex layout,
+	/// but an empty buffer, no indices and a vertex count of zero.
+	pub fn to_buffered_geometry_by_type(&mut self, geom_type: MeshBufferType) -> BufferedGeometry {
+		let layout = Self::buffer_layout(&geom_type);
+		let buffer = self.to_renderable_buffer_by_type(geom_type);
+		Self::to_buffered_geometry(layout, buffer)
+	}
+
+	/// Exports the mesh like `to_renderable_buffer_by_type`,
+	/// and decodes the buffer back into typed arrays.
+	pub fn to_arrays(&mut self, geom_type: MeshBufferType) -> MeshArrays {
+		let has_normals = geom_type != MeshBufferType::NoNormals;
+		let buffer = self.to_renderable_buffer_by_type(geom_type);
+
+		let data_size = std::mem::size_of::<V>();
+		let stride = data_size
+			+ if has_normals {
+				std::mem::size_of::<Vec3>()
+			} else {
+				0
+			};
+		let uv_offset = create_buffered_geometry_layout(V::vertex_layout())
+			.vertex_layout
+			.iter()
+			.find(|attr| {
+				attr.name == "uv" && attr.size == 2 && attr.attr_type == AttributeType::Float
+			})
+			.map(|attr| attr.offset as usize);
+
+		let vertices = buffer.vertex_buffer.chunks_exact(stride);
+
+		let positions = vertices
+			.clone()
+			.map(|v| bytemuck::pod_read_unaligned::<V>(&v[..data_size]).position())
+			.collect();
+
+		let normals = has_normals.then(|| {
+			vertices
+				.clone()
+				.map(|v| bytemuck::pod_read_unaligned::<Vec3>(&v[data_size..stride]))
+				.collect()
+		});
+
+		let uvs = uv_offset.map(|offset| {
+			vertices
+				.clone()
+				.map(|v| bytemuck::pod_read_unaligned::<Vec2>(&v[offset..offset + 8]))
+				.collect()
+		});
+
+		let indices = match buffer.index_buffer {
+			Some(bytes) => decode_indices(&bytes),
+			None => (0..buffer.vertex_count).collect(),
+		};
+
+		MeshArrays {
+			positions,
+			normals,
+			uvs,
+			indices,
+		}
+	}
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + bytemuck::Pod + HasSkinning,
+{
+	/// Vertex buffer with vertex normals, followed by `joints` and `weights` attributes.
+	/// Weights are normalized to sum up to 1.
+	pub fn to_skinned_renderable_buffer(&mut self) -> RenderableBuffer {
+		self.build_renderable_buffer(
+			MeshBufferType::VertexNormals,
+			Some(|vertex: &V, buffer: &mut Vec<u8>| {
+				buffer.extend(vertex.joints());
+				buffer.extend(bytemuck::bytes_of(&normalize_weights(vertex.weights())));
+			}),
+			None,
+		)
+	}
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: BufferedVertexData + OverrideAttributesWith + Position3D + HasSkinning,
+{
+	pub fn to_skinned_buffered_geometry(&mut self) -> BufferedGeometry {
+		let mut layout = Self::buffer_layout(&MeshBufferType::VertexNormals);
+		layout.push(VertexType {
+			name: "joints",
+			format: VertexFormat::Uint8x4,
+		});
+		layout.push(VertexType {
+			name: "weights",
+			format: VertexFormat::Float32x4,
+		});
+		let buffer = self.to_skinned_renderable_buffer();
+		Self::to_buffered_geometry(layout, buffer)
+	}
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + bytemuck::Pod + HasUv,
+{
+	/// Vertex buffer with vertex normals, followed by a `tangent` attribute with the handedness in `w`.
+	pub fn to_tangent_renderable_buffer(&mut self) -> RenderableBuffer {
+		let buffer = self.build_renderable_buffer(MeshBufferType::VertexNormals, None, None);
+
+		let data_size = std::mem::size_of::<V>();
+		let stride = data_size + std::mem::size_of::<Vec3>();
+		let (data, normals): (Vec<V>, Vec<Vec3>) = buffer
+			.vertex_buffer
+			.chunks_exact(stride)
+			.map(|v| {
+				(
+					bytemuck::pod_read_unaligned::<V>(&v[..data_size]),
+					bytemuck::pod_read_unaligned::<Vec3>(&v[data_size..]),
+				)
+			})
+			.unzip();
+
+		let indices = match &buffer.index_buffer {
+			Some(bytes) => decode_indices(bytes),
+			None => (0..buffer.vertex_count).collect(),
+		};
+		let positions = data.iter().map(|v| v.position()).collect::<Vec<_>>();
+		let uvs = data.iter().map(|v| v.uv()).collect::<Vec<_>>();
+		let tangents = generate_tangents(&positions, &normals, &uvs, &indices);
+
+		let mut vertex_buffer =
+			Vec::with_capacity(buffer.vertex_buffer.len() + tangents.len() * 16);
+		for ((data, normal), tangent) in data.iter().zip(normals).zip(tangents) {
+			vertex_buffer.extend(bytemuck::bytes_of(data));
+			vertex_buffer.extend(bytemuck::bytes_of(&normal));
+			vertex_buffer.extend(bytemuck::bytes_of(&tangent));
+		}
+
+		RenderableBuffer {
+			vertex_buffer,
+			..buffer
 		}
 	}
 }
 
+impl<V> MeshGeometry<V>
+where
+	V: BufferedVertexData + OverrideAttributesWith + Position3D + HasUv,
+{
+	pub fn to_tangent_buffered_geometry(&mut self) -> BufferedGeometry {
+		let mut layout = Self::buffer_layout(&MeshBufferType::VertexNormals);
+		layout.push(VertexType {
+			name: "tangent",
+			format: VertexFormat::Float32x4,
+		});
+		let buffer = self.to_tangent_renderable_buffer();
+		Self::to_buffered_geometry(layout, buffer)
+	}
+}
+
 impl<V> Position3D for MeshVertex<V>
 where
 	V: OverrideAttributesWith + Position3D,
@@ -670,5 +1662,8 @@ where
 	}
 }
 
+mod obj;
+pub use obj::ObjError;
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,236 @@
+use super::{FaceDataProps, MeshGeometry, Position3D};
+use crate::rendering::buffered_geometry::OverrideAttributesWith;
+use glam::{vec3, Vec3};
+use std::{
+	collections::HashMap,
+	io::{BufRead, BufReader, Read, Write},
+};
+
+#[derive(Debug)]
+pub enum ObjError {
+	Io(std::io::Error),
+	/// Malformed statement at the given 1-based line number.
+	Parse {
+		line: usize,
+		message: String,
+	},
+}
+
+impl std::fmt::Display for ObjError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ObjError::Io(err) => write!(f, "failed to read obj data: {}", err),
+			ObjError::Parse { line, message } => write!(f, "obj line {}: {}", line, message),
+		}
+	}
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<std::io::Error> for ObjError {
+	fn from(err: std::io::Error) -> Self {
+		ObjError::Io(err)
+	}
+}
+
+fn parse_vec3<'a>(
+	mut values: impl Iterator<Item = &'a str>,
+	line: usize,
+) -> Result<Vec3, ObjError> {
+	let mut next = || {
+		values
+			.next()
+			.and_then(|v| v.parse::<f32>().ok())
+			.ok_or_else(|| ObjError::Parse {
+				line,
+				message: "expected three numbers".to_string(),
+			})
+	};
+	Ok(vec3(next()?, next()?, next()?))
+}
+
+/// Resolves a 1-based or negative (relative to the end) obj index.
+fn resolve_index(value: &str, len: usize, line: usize) -> Result<Option<usize>, ObjError> {
+	if value.is_empty() {
+		return Ok(None);
+	}
+	let err = || ObjError::Parse {
+		line,
+		message: format!("invalid index '{}'", value),
+	};
+	let index = value.parse::<i64>().map_err(|_| err())?;
+	let resolved = if index < 0 {
+		len as i64 + index
+	} else {
+		index - 1
+	};
+	if resolved < 0 || resolved >= len as i64 {
+		return Err(err());
+	}
+	Ok(Some(resolved as usize))
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + From<Vec3>,
+{
+	/// Parses Wavefront OBJ data. Vertices are created from the `v` positions.
+	/// Faces with `vn` normals get the averaged normal as face normal.
+	/// Polygons with more than four vertices are split into a triangle fan.
+	/// Each `o` or `g` name is mapped to its own section, in order of appearance.
+	/// Materials and texture coordinates are ignored.
+	pub fn from_obj<R: Read>(reader: R) -> Result<Self, ObjError> {
+		let mut geom = MeshGeometry::new();
+
+		let mut positions: Vec<Vec3> = vec![];
+		let mut normals: Vec<Vec3> = vec![];
+		let mut sections = HashMap::<String, usize>::new();
+		let mut section = 0;
+		let mut has_ungrouped_faces = false;
+
+		for (i, line) in BufReader::new(reader).lines().enumerate() {
+			let line_nr = i + 1;
+			let line = line?;
+			let mut tokens = line.split_whitespace();
+
+			match tokens.next() {
+				Some("v") => positions.push(parse_vec3(tokens, line_nr)?),
+				Some("vn") => normals.push(parse_vec3(tokens, line_nr)?),
+				Some("o") | Some("g") => {
+					let name = tokens.collect::<Vec<_>>().join(" ");
+					// section 0 is kept for faces before the first group
+					let next = sections.len() + usize::from(has_ungrouped_faces);
+					section = *sections.entry(name).or_insert(next);
+				}
+				Some("f") => {
+					let mut face_positions = vec![];
+					let mut face_normals = vec![];
+					for vertex in tokens {
+						let mut refs = vertex.split('/');
+						let pos = refs.next().unwrap_or_default();
+						let pos =
+							resolve_index(pos, positions.len(), line_nr)?.ok_or_else(|| {
+								ObjError::Parse {
+									line: line_nr,
+									message: "missing vertex index".to_string(),
+								}
+							})?;
+						face_positions.push(positions[pos]);
+
+						let normal = refs.nth(1).unwrap_or_default();
+						if let Some(n) = resolve_index(normal, normals.len(), line_nr)? {
+							face_normals.push(normals[n]);
+						}
+					}
+
+					if face_positions.len() < 3 {
+						return Err(ObjError::Parse {
+							line: line_nr,
+							message: "faces need at least three vertices".to_string(),
+						});
+					}
+					for (j, p) in face_positions.iter().enumerate() {
+						if face_positions[..j].contains(p) {
+							return Err(ObjError::Parse {
+								line: line_nr,
+								message: "face vertices must have unique positions".to_string(),
+							});
+						}
+					}
+
+					let normal = (face_normals.len() == face_positions.len())
+						.then(|| face_normals.iter().sum::<Vec3>().normalize_or_zero());
+					let props = FaceDataProps {
+						normal,
+						section: Some(section),
+						..FaceDataProps::default()
+					};
+
+					let v = face_positions.into_iter().map(V::from).collect::<Vec<_>>();
+					if v.len() == 4 {
+						geom.add_face4_data(v[0], v[1], v[2], v[3], props);
+					} else {
+						for j in 1..v.len() - 1 {
+							geom.add_face3_data(v[0], v[j], v[j + 1], props);
+						}
+					}
+					if sections.is_empty() {
+						has_ungrouped_faces = true;
+					}
+				}
+				_ => {}
+			}
+		}
+
+		Ok(geom)
+	}
+}
+
+impl<V> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy,
+{
+	/// Writes the mesh as Wavefront OBJ, with all vertex positions, one normal per face
+	/// and a group per section. Quads are kept as quads.
+	pub fn to_obj<W: Write>(&mut self, mut writer: W) -> std::io::Result<()> {
+		self.generate_face_normals();
+
+		for vertex in &self.vertices {
+			let p = vertex.data.position();
+			writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+		}
+
+		for face in self.faces.values().flatten() {
+			let n = face.face_normal.unwrap_or_default();
+			writeln!(writer, "vn {} {} {}", n.x, n.y, n.z)?;
+		}
+
+		let mut normal_idx = 0;
+		for (section, faces) in &self.faces {
+			writeln!(writer, "g section_{}", section)?;
+			for face in faces {
+				normal_idx += 1;
+				write!(writer, "f")?;
+				for v in &face.vertices {
+					write!(writer, " {}//{}", v + 1, normal_idx)?;
+				}
+				writeln!(writer)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Writes vertex positions and faces as binary little endian PLY.
+	/// PLY has no face normals, sections are not preserved.
+	pub fn to_ply_binary<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+		let face_count: usize = self.faces.values().map(|faces| faces.len()).sum();
+
+		write!(
+			writer,
+			"ply\nformat binary_little_endian 1.0\n\
+			element vertex {}\n\
+			property float x\nproperty float y\nproperty float z\n\
+			element face {}\n\
+			property list uchar int vertex_indices\n\
+			end_header\n",
+			self.vertices.len(),
+			face_count
+		)?;
+
+		for vertex in &self.vertices {
+			for c in vertex.data.position().to_array() {
+				writer.write_all(&c.to_le_bytes())?;
+			}
+		}
+
+		for face in self.faces.values().flatten() {
+			writer.write_all(&[face.vertices.len() as u8])?;
+			for v in &face.vertices {
+				writer.write_all(&(*v as i32).to_le_bytes())?;
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -1,11 +1,22 @@
 use crate::{
-	geometry::mesh_geometry_3d::{Face, MeshGeometry},
-	rendering::buffered_geometry::{BufferedVertexData, NoAttributeOverride, VertexType},
+	geometry::mesh_geometry_3d::{
+		face_section, face_smoothing_group, normalize_weights, Face, FaceDataProps, HasSkinning,
+		HasUv, MeshArrays, MeshBufferType, MeshGeometry, MeshStats, ObjError, SectionIndex, SetUv,
+	},
+	rendering::{
+		buffered_geometry::{
+			vert_type, BufferedVertexData, FromAttributes, GeometryError, NoAttributeOverride,
+			RenderingPrimitive, VertexAttributes, VertexFormat, VertexType,
+		},
+		objects::Axis,
+	},
+	utils::default,
 };
 use bytemuck::{Pod, Zeroable};
-use glam::{vec3, Vec3};
+use glam::{vec2, vec3, vec4, Vec2, Vec3, Vec4};
+use lerp::Lerp;
 
-use super::Position3D;
+use super::{Position3D, SetPosition3D};
 
 #[repr(C)]
 #[derive(Debug, PartialEq, Clone, Copy, Pod, Zeroable)]
@@ -23,6 +34,23 @@ impl Position3D for Vert {
 	}
 }
 impl NoAttributeOverride for Vert {}
+impl SetPosition3D for Vert {
+	fn set_position(&mut self, pos: Vec3) {
+		self.pos = pos;
+	}
+}
+impl From<Vec3> for Vert {
+	fn from(pos: Vec3) -> Self {
+		Vert { pos }
+	}
+}
+impl Lerp<f32> for Vert {
+	fn lerp(self, other: Self, t: f32) -> Self {
+		Vert {
+			pos: self.pos.lerp(other.pos, t),
+		}
+	}
+}
 
 fn vert(x: f32, y: f32, z: f32) -> Vert {
 	Vert { pos: vec3(x, y, z) }
@@ -122,6 +150,99 @@ fn remove_face() {
 	assert_eq!(geom.vertex(4).faces, []);
 }
 
+#[test]
+fn integrity_after_adds_and_removes() {
+	let mut geom = MeshGeometry::new();
+	for (i, q) in cube_quads().into_iter().enumerate() {
+		geom.add_face4_data(q[0], q[1], q[2], q[3], face_section(i % 2));
+	}
+	geom.add_face3(
+		vert(0.0, 0.0, 5.0),
+		vert(1.0, 0.0, 5.0),
+		vert(0.0, 1.0, 5.0),
+	);
+	assert_eq!(geom.integrity_error(), None);
+
+	geom.remove_face(SectionIndex {
+		section: 1,
+		index: 0,
+	});
+	geom.remove_face(0);
+	geom.debug_assert_integrity();
+
+	geom.triangulate();
+	geom.debug_assert_integrity();
+
+	geom.add_face3(
+		vert(0.0, 0.0, 6.0),
+		vert(1.0, 0.0, 6.0),
+		vert(0.0, 1.0, 6.0),
+	);
+	geom.remove_face(SectionIndex {
+		section: 1,
+		index: 3,
+	});
+	geom.remove_face(1);
+	geom.remove_face(0);
+	assert_eq!(geom.integrity_error(), None);
+
+	geom.weld_vertices(0.01);
+	geom.debug_assert_integrity();
+
+	while !geom.faces[&0].is_empty() {
+		geom.remove_face(0);
+		geom.debug_assert_integrity();
+	}
+}
+
+#[test]
+fn integrity_swap_remove_remap() {
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(1.0, 0.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	geom.add_face3(
+		vert(2.0, 0.0, 0.0),
+		vert(3.0, 0.0, 0.0),
+		vert(2.0, 1.0, 0.0),
+	);
+	geom.add_face3(
+		vert(4.0, 0.0, 0.0),
+		vert(5.0, 0.0, 0.0),
+		vert(4.0, 1.0, 0.0),
+	);
+
+	// the last face moves into the slot of the removed one, and its vertices need to follow
+	geom.remove_face(0);
+	assert_eq!(geom.face(0).vertices, [6, 7, 8]);
+	assert_eq!(geom.vertex(6).faces, [0.into()]);
+	assert_eq!(geom.vertex(3).faces, [1.into()]);
+	assert_eq!(geom.vertex(0).faces, []);
+	assert_eq!(geom.integrity_error(), None);
+
+	geom.vertices[6].faces = vec![2.into()];
+	assert!(geom.integrity_error().unwrap().contains("missing face"));
+
+	geom.vertices[6].faces = vec![1.into()];
+	assert!(geom.integrity_error().is_some());
+}
+
+#[test]
+#[should_panic(expected = "MeshGeometry integrity violated")]
+#[cfg(debug_assertions)]
+fn integrity_panics_in_debug() {
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(1.0, 0.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	geom.vertices[1].faces.clear();
+	geom.debug_assert_integrity();
+}
+
 #[test]
 fn triangulate() {
 	let mut geom = MeshGeometry::new();
@@ -177,3 +298,934 @@ fn triangulate() {
 		.find(|f| { f.vertices == [0, 5, 3] })
 		.is_some());
 }
+
+fn cube_quads() -> [[Vert; 4]; 6] {
+	[
+		[
+			vert(0.0, 0.0, 0.0),
+			vert(0.0, 1.0, 0.0),
+			vert(1.0, 1.0, 0.0),
+			vert(1.0, 0.0, 0.0),
+		],
+		[
+			vert(0.0, 0.0, 1.0),
+			vert(1.0, 0.0, 1.0),
+			vert(1.0, 1.0, 1.0),
+			vert(0.0, 1.0, 1.0),
+		],
+		[
+			vert(0.0, 0.0, 0.0),
+			vert(0.0, 0.0, 1.0),
+			vert(0.0, 1.0, 1.0),
+			vert(0.0, 1.0, 0.0),
+		],
+		[
+			vert(1.0, 0.0, 0.0),
+			vert(1.0, 1.0, 0.0),
+			vert(1.0, 1.0, 1.0),
+			vert(1.0, 0.0, 1.0),
+		],
+		[
+			vert(0.0, 0.0, 0.0),
+			vert(1.0, 0.0, 0.0),
+			vert(1.0, 0.0, 1.0),
+			vert(0.0, 0.0, 1.0),
+		],
+		[
+			vert(0.0, 1.0, 0.0),
+			vert(0.0, 1.0, 1.0),
+			vert(1.0, 1.0, 1.0),
+			vert(1.0, 1.0, 0.0),
+		],
+	]
+}
+
+fn buffer_normals(buffer: &[u8]) -> Vec<Vec3> {
+	bytemuck::cast_slice::<u8, [Vec3; 2]>(buffer)
+		.iter()
+		.map(|[_, normal]| *normal)
+		.collect()
+}
+
+#[test]
+fn smoothing_groups() {
+	let mut geom = MeshGeometry::new();
+	for (i, [v1, v2, v3, v4]) in cube_quads().into_iter().enumerate() {
+		geom.add_face4_data(v1, v2, v3, v4, face_smoothing_group(i as u32));
+	}
+
+	let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::VertexNormals);
+	assert_eq!(buffer.vertex_count, 24);
+	assert_eq!(buffer.index_count, 36);
+	for normal in buffer_normals(&buffer.vertex_buffer) {
+		assert_eq!(normal.abs().max_element(), 1.0);
+	}
+
+	let mut geom = MeshGeometry::new();
+	for [v1, v2, v3, v4] in cube_quads() {
+		geom.add_face4_data(v1, v2, v3, v4, face_smoothing_group(0));
+	}
+
+	let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::VertexNormals);
+	assert_eq!(buffer.vertex_count, 8);
+	assert_eq!(buffer.index_count, 36);
+	for normal in buffer_normals(&buffer.vertex_buffer) {
+		assert!(normal.abs().max_element() < 1.0);
+	}
+}
+
+#[test]
+fn mesh_stats() {
+	let mut geom = MeshGeometry::new();
+	let a = vert(0.0, 0.0, 0.0);
+	let b = vert(1.0, 0.0, 0.0);
+	let c = vert(1.0, 1.0, 0.0);
+	let d = vert(0.0, 1.0, 0.0);
+	let e = vert(2.0, 0.0, 0.0);
+	let f = vert(2.0, 1.0, 0.0);
+
+	geom.add_face4(a, b, c, d);
+	geom.add_face4(b, e, f, c);
+	// zero area triangle along the bottom edge
+	geom.add_face3(a, b, e);
+
+	assert_eq!(geom.stats().welded_vertices, 5);
+	assert_eq!(geom.stats().triangulated_quads, 0);
+
+	let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::NoNormals);
+
+	assert_eq!(
+		geom.stats(),
+		MeshStats {
+			welded_vertices: 5,
+			triangulated_quads: 2,
+			degenerate_faces: 1,
+			final_vertex_count: 6,
+			final_index_count: 12,
+		}
+	);
+	assert_eq!(buffer.vertex_count, 6);
+	assert_eq!(buffer.index_count, 12);
+
+	geom.to_renderable_buffer_by_type(MeshBufferType::FaceNormals);
+
+	assert_eq!(geom.stats().triangulated_quads, 2);
+	assert_eq!(geom.stats().degenerate_faces, 1);
+	assert_eq!(geom.stats().final_vertex_count, 12);
+	assert_eq!(geom.stats().final_index_count, 0);
+}
+
+#[test]
+fn degenerate_faces_independent_of_scale() {
+	let mut tiny = MeshGeometry::new();
+	tiny.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(1e-4, 0.0, 0.0),
+		vert(0.0, 1e-4, 0.0),
+	);
+	tiny.to_renderable_buffer_by_type(MeshBufferType::FaceNormals);
+	assert_eq!(tiny.stats().degenerate_faces, 0);
+
+	let mut huge = MeshGeometry::new();
+	huge.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(1e4, 0.0, 0.0),
+		vert(2e4, 1e-4, 0.0),
+	);
+	huge.to_renderable_buffer_by_type(MeshBufferType::FaceNormals);
+	assert_eq!(huge.stats().degenerate_faces, 1);
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Clone, Copy, Pod, Zeroable)]
+struct SkinnedVert {
+	pos: Vec3,
+	blend: f32,
+}
+impl BufferedVertexData for SkinnedVert {
+	fn vertex_layout() -> Vec<VertexType> {
+		vec![
+			vert_type("position", VertexFormat::Float32x3),
+			vert_type("blend", VertexFormat::Float32),
+		]
+	}
+}
+impl Position3D for SkinnedVert {
+	fn position(&self) -> Vec3 {
+		self.pos
+	}
+}
+impl HasSkinning for SkinnedVert {
+	fn joints(&self) -> [u8; 4] {
+		[1, 2, 3, 0]
+	}
+	fn weights(&self) -> [f32; 4] {
+		[self.blend * 5.0, self.blend * 3.0, self.blend * 2.0, 0.0]
+	}
+}
+impl NoAttributeOverride for SkinnedVert {}
+
+#[test]
+fn skinned_buffer() {
+	assert_eq!(
+		normalize_weights([0.5, 0.3, 0.2, 0.0]),
+		[0.5, 0.3, 0.2, 0.0]
+	);
+	assert_eq!(
+		normalize_weights([2.0, 1.0, 1.0, 0.0]),
+		[0.5, 0.25, 0.25, 0.0]
+	);
+	assert_eq!(normalize_weights([0.0; 4]), [0.0; 4]);
+
+	let skinned = |pos: Vec3| SkinnedVert { pos, blend: 0.2 };
+
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		skinned(vec3(0.0, 0.0, 0.0)),
+		skinned(vec3(1.0, 0.0, 0.0)),
+		skinned(vec3(0.0, 1.0, 0.0)),
+	);
+
+	let buffered = geom.to_skinned_buffered_geometry();
+
+	let names = buffered
+		.vertex_layout
+		.iter()
+		.map(|a| (a.name, a.offset))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		names,
+		[
+			("position", 0),
+			("blend", 12),
+			("normal", 16),
+			("joints", 28),
+			("weights", 32)
+		]
+	);
+	assert_eq!(buffered.vertex_size, 48);
+	assert_eq!(buffered.buffer_vertex_count(), 3);
+
+	let vertex = &buffered.buffer[0..48];
+	assert_eq!(&vertex[28..32], &[1, 2, 3, 0]);
+	let weights: [f32; 4] = bytemuck::pod_read_unaligned(&vertex[32..48]);
+	assert_eq!(weights, [0.5, 0.3, 0.2, 0.0]);
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct UvVert {
+	pos: Vec3,
+	uv: Vec2,
+}
+impl BufferedVertexData for UvVert {
+	fn vertex_layout() -> Vec<VertexType> {
+		vec![
+			vert_type("position", VertexFormat::Float32x3),
+			vert_type("uv", VertexFormat::Float32x2),
+		]
+	}
+}
+impl Position3D for UvVert {
+	fn position(&self) -> Vec3 {
+		self.pos
+	}
+}
+impl HasUv for UvVert {
+	fn uv(&self) -> Vec2 {
+		self.uv
+	}
+}
+impl SetUv for UvVert {
+	fn set_uv(&mut self, uv: Vec2) {
+		self.uv = uv;
+	}
+}
+impl NoAttributeOverride for UvVert {}
+impl FromAttributes for UvVert {
+	fn from_attributes(attributes: &VertexAttributes) -> Self {
+		UvVert {
+			pos: attributes.vec3("position").unwrap(),
+			uv: attributes.vec2("uv").unwrap_or_default(),
+		}
+	}
+}
+
+#[test]
+fn to_arrays() {
+	let mut geom = MeshGeometry::new();
+	for [v1, v2, v3, v4] in cube_quads() {
+		let [v1, v2, v3, v4] = [v1, v2, v3, v4].map(|v| UvVert {
+			pos: v.pos,
+			uv: vec2(v.pos.x, v.pos.y),
+		});
+		geom.add_face4(v1, v2, v3, v4);
+	}
+
+	let arrays = geom.to_arrays(MeshBufferType::VertexNormals);
+	assert_eq!(arrays.positions.len(), 8);
+	assert_eq!(arrays.normals.as_ref().unwrap().len(), 8);
+	assert_eq!(arrays.uvs.as_ref().unwrap().len(), 8);
+	assert_eq!(arrays.indices.len(), 36);
+	assert!(arrays
+		.indices
+		.iter()
+		.all(|i| (*i as usize) < arrays.positions.len()));
+	for (pos, uv) in arrays.positions.iter().zip(arrays.uvs.unwrap()) {
+		assert_eq!(uv, vec2(pos.x, pos.y));
+	}
+
+	let arrays = geom.to_arrays(MeshBufferType::FaceNormals);
+	assert_eq!(arrays.positions.len(), 36);
+	assert_eq!(arrays.indices, (0..36).collect::<Vec<_>>());
+	assert!(arrays
+		.normals
+		.unwrap()
+		.iter()
+		.all(|n| n.abs().max_element() == 1.0));
+
+	let mut geom = MeshGeometry::new();
+	for [v1, v2, v3, v4] in cube_quads() {
+		geom.add_face4(v1, v2, v3, v4);
+	}
+	let MeshArrays {
+		positions,
+		normals,
+		uvs,
+		indices,
+	} = geom.to_arrays(MeshBufferType::NoNormals);
+	assert_eq!(positions.len(), 8);
+	assert_eq!(normals, None);
+	assert_eq!(uvs, None);
+	assert!(indices.iter().all(|i| (*i as usize) < positions.len()));
+}
+
+#[test]
+fn from_buffered_geometry() {
+	let mut geom = MeshGeometry::new();
+	for [v1, v2, v3, v4] in cube_quads() {
+		let [v1, v2, v3, v4] = [v1, v2, v3, v4].map(|v| UvVert {
+			pos: v.pos,
+			uv: vec2(v.pos.x, v.pos.y),
+		});
+		geom.add_face4(v1, v2, v3, v4);
+	}
+	geom.triangulate();
+	let face_count = geom.faces[&0].len();
+	let vertex_count = geom.vertices.len();
+
+	for geom_type in [MeshBufferType::VertexNormals, MeshBufferType::FaceNormals] {
+		let buffered = geom.to_buffered_geometry_by_type(geom_type);
+		let imported = MeshGeometry::<UvVert>::from_buffered_geometry(&buffered).unwrap();
+
+		assert_eq!(imported.faces[&0].len(), face_count);
+		assert_eq!(imported.vertices.len(), vertex_count);
+		for v in &imported.vertices {
+			assert_eq!(v.data.uv, vec2(v.data.pos.x, v.data.pos.y));
+		}
+	}
+
+	let mut lines = geom.to_buffered_geometry_by_type(MeshBufferType::NoNormals);
+	lines.rendering_primitive = RenderingPrimitive::Lines;
+	assert!(matches!(
+		MeshGeometry::<UvVert>::from_buffered_geometry(&lines),
+		Err(GeometryError::UnsupportedPrimitive { .. })
+	));
+}
+
+#[test]
+fn buffered_geometry_bounds() {
+	let mut geom = MeshGeometry::new();
+	for quad in cube_quads() {
+		let [v1, v2, v3, v4] = quad.map(|v| UvVert {
+			pos: v.pos * 2.0 + vec3(-1.0, 2.0, 3.0),
+			uv: Vec2::ZERO,
+		});
+		geom.add_face4(v1, v2, v3, v4);
+	}
+
+	for geom_type in [
+		MeshBufferType::NoNormals,
+		MeshBufferType::VertexNormals,
+		MeshBufferType::VertexNormalFaceData,
+		MeshBufferType::FaceNormals,
+	] {
+		let buffered = geom.to_buffered_geometry_by_type(geom_type);
+		assert_eq!(buffered.bounds_min, Some([-1.0, 2.0, 3.0]));
+		assert_eq!(buffered.bounds_max, Some([1.0, 4.0, 5.0]));
+	}
+
+	let empty =
+		MeshGeometry::<UvVert>::new().to_buffered_geometry_by_type(MeshBufferType::NoNormals);
+	assert_eq!(empty.bounds_min, None);
+	assert_eq!(empty.bounds_max, None);
+}
+
+#[test]
+fn smooth_normals() {
+	let mut geom = MeshGeometry::new();
+	for [v1, v2, v3, v4] in cube_quads() {
+		geom.add_face4(v1, v2, v3, v4);
+	}
+
+	let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::SmoothNormals {
+		angle_threshold: 30f32.to_radians(),
+	});
+	// every corner is split into 3 vertices with distinct axis aligned normals
+	assert_eq!(buffer.vertex_count, 24);
+	assert_eq!(buffer.index_count, 36);
+	let normals = buffer_normals(&buffer.vertex_buffer);
+	for normal in &normals {
+		assert_eq!(normal.abs().max_element(), 1.0);
+	}
+	let corner_normals = bytemuck::cast_slice::<u8, [Vec3; 2]>(&buffer.vertex_buffer)
+		.iter()
+		.filter(|[pos, _]| *pos == Vec3::ZERO)
+		.map(|[_, normal]| *normal)
+		.collect::<Vec<_>>();
+	assert_eq!(corner_normals.len(), 3);
+	assert!(corner_normals[0] != corner_normals[1]);
+	assert!(corner_normals[1] != corner_normals[2]);
+	assert!(corner_normals[0] != corner_normals[2]);
+
+	let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::SmoothNormals {
+		angle_threshold: 100f32.to_radians(),
+	});
+	assert_eq!(buffer.vertex_count, 8);
+	for normal in buffer_normals(&buffer.vertex_buffer) {
+		assert!(normal.abs().max_element() < 1.0);
+	}
+}
+
+#[test]
+fn tangents() {
+	let quad = |uv: fn(Vec3) -> Vec2| {
+		let mut geom = MeshGeometry::new();
+		let [v1, v2, v3, v4] = [
+			vec3(0.0, 0.0, 0.0),
+			vec3(1.0, 0.0, 0.0),
+			vec3(1.0, 1.0, 0.0),
+			vec3(0.0, 1.0, 0.0),
+		]
+		.map(|pos| UvVert { pos, uv: uv(pos) });
+		geom.add_face4(v1, v2, v3, v4);
+		geom
+	};
+
+	let geom = quad(|p| vec2(p.x, p.y)).to_tangent_buffered_geometry();
+	assert_eq!(geom.vertex_size, 20 + 12 + 16);
+	let tangent = geom.vertex_layout.last().unwrap();
+	assert_eq!(tangent.name, "tangent");
+	assert_eq!(tangent.offset, 32);
+
+	let vertices = bytemuck::cast_slice::<u8, [f32; 12]>(&geom.buffer);
+	assert_eq!(vertices.len(), 4);
+	for v in vertices {
+		assert_eq!(Vec3::from_slice(&v[5..8]), Vec3::Z);
+		assert_eq!(Vec4::from_slice(&v[8..12]), vec4(1.0, 0.0, 0.0, 1.0));
+	}
+
+	// mirrored texture coordinates flip tangent and handedness
+	let buffer = quad(|p| vec2(1.0 - p.x, p.y)).to_tangent_renderable_buffer();
+	for v in bytemuck::cast_slice::<u8, [f32; 12]>(&buffer.vertex_buffer) {
+		assert_eq!(Vec4::from_slice(&v[8..12]), vec4(-1.0, 0.0, 0.0, -1.0));
+	}
+}
+
+#[test]
+fn subdivide() {
+	let mut geom = MeshGeometry::new();
+	geom.add_face3_data(
+		vert(0.0, 0.0, 0.0),
+		vert(2.0, 0.0, 0.0),
+		vert(0.0, 2.0, 0.0),
+		FaceDataProps {
+			section: Some(3),
+			smoothing_group: Some(1),
+			data: Some(vert(1.0, 1.0, 1.0)),
+			..default()
+		},
+	);
+
+	geom.subdivide(1);
+	assert_eq!(geom.vertices.len(), 6);
+	let faces = geom.faces.get(&3).unwrap();
+	assert_eq!(faces.len(), 4);
+	assert!(faces
+		.iter()
+		.all(|f| f.smoothing_group == Some(1) && f.data == Some(vert(1.0, 1.0, 1.0))));
+	assert!(geom
+		.vertices
+		.iter()
+		.any(|v| v.data.pos == vec3(1.0, 1.0, 0.0)));
+
+	geom.subdivide(1);
+	assert_eq!(geom.vertices.len(), 15);
+	assert_eq!(geom.faces.get(&3).unwrap().len(), 16);
+
+	let mut geom = MeshGeometry::new();
+	for [v1, v2, v3, v4] in cube_quads() {
+		geom.add_face4(v1, v2, v3, v4);
+	}
+	geom.subdivide(1);
+	// 8 corners, 18 edges including the quad diagonals, 48 triangles
+	assert_eq!(geom.vertices.len(), 8 + 18);
+	assert_eq!(geom.faces.get(&0).unwrap().len(), 48);
+}
+
+#[test]
+fn bounding_box() {
+	let mut geom = MeshGeometry::new();
+	assert_eq!(geom.bounding_box(), (Vec3::ZERO, Vec3::ZERO));
+
+	geom.add_face3(
+		vert(-1.0, 0.0, 2.0),
+		vert(3.0, 1.0, 0.0),
+		vert(0.0, -2.0, 1.0),
+	);
+	geom.add_face4(
+		vert(0.0, 0.0, -4.0),
+		vert(1.0, 0.0, -4.0),
+		vert(1.0, 5.0, -4.0),
+		vert(0.0, 5.0, -4.0),
+	);
+
+	assert_eq!(
+		geom.bounding_box(),
+		(vec3(-1.0, -2.0, -4.0), vec3(3.0, 5.0, 2.0))
+	);
+	assert_eq!(geom.center(), vec3(1.0, 1.5, -1.0));
+
+	let (center, radius) = geom.bounding_sphere();
+	assert_eq!(center, geom.center());
+	assert!(geom
+		.vertices
+		.iter()
+		.all(|v| v.data.pos.distance(center) <= radius));
+	assert_eq!(radius, vec3(-1.0, 3.5, -3.0).length());
+}
+
+const CUBE_OBJ: &str = "
+# unit cube
+o Cube
+v 0 0 0
+v 0 1 0
+v 1 1 0
+v 1 0 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+vn 0 0 -1
+vn 0 0 1
+usemtl Material
+f 1//1 2//1 3//1 4//1
+f 5//2 6//2 7//2 8//2
+f 1 5 8 2
+f 4 3 7 6
+g bottom
+f 1/1 4/1 6/1 5/1
+g top
+f -7 -1 -2 -6
+";
+
+#[test]
+fn from_obj() {
+	let geom = MeshGeometry::<Vert>::from_obj(CUBE_OBJ.as_bytes()).unwrap();
+
+	assert_eq!(geom.vertices.len(), 8);
+	assert_eq!(geom.faces.get(&0).unwrap().len(), 4);
+	assert_eq!(geom.faces.get(&1).unwrap().len(), 1);
+	assert_eq!(geom.faces.get(&2).unwrap().len(), 1);
+	let faces = geom.faces.values().flatten().collect::<Vec<_>>();
+	assert_eq!(faces.len(), 6);
+	assert!(faces.iter().all(|f| f.vertices.len() == 4));
+
+	assert_eq!(faces[0].face_normal, Some(vec3(0.0, 0.0, -1.0)));
+	assert_eq!(faces[2].face_normal, None);
+	let top = &geom.faces.get(&2).unwrap()[0];
+	assert!(top
+		.vertices
+		.iter()
+		.all(|i| geom.vertex(*i).data.pos.y == 1.0));
+
+	let mut geom = MeshGeometry::<Vert>::from_obj(
+		"v 0 0 0\nv 2 0 0\nv 3 1 0\nv 1 2 0\nv -1 1 0\nf 1 2 3 4 5".as_bytes(),
+	)
+	.unwrap();
+	let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::NoNormals);
+	assert_eq!(buffer.index_count, 9);
+
+	let err = MeshGeometry::<Vert>::from_obj("v 0 0 0\nf 1 2 3".as_bytes()).err();
+	assert!(matches!(err, Some(ObjError::Parse { line: 2, .. })));
+}
+
+#[test]
+fn make_normals_consistent() {
+	for reversed in [0, 3] {
+		let mut geom = MeshGeometry::new();
+		for (i, [v1, v2, v3, v4]) in cube_quads().into_iter().enumerate() {
+			if i == reversed {
+				geom.add_face4(v4, v3, v2, v1);
+			} else {
+				geom.add_face4(v1, v2, v3, v4);
+			}
+		}
+
+		geom.make_normals_consistent();
+
+		let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::FaceNormals);
+		for [pos, normal] in bytemuck::cast_slice::<u8, [Vec3; 2]>(&buffer.vertex_buffer) {
+			assert!(normal.dot(*pos - Vec3::splat(0.5)) > 0.0);
+		}
+	}
+}
+
+#[test]
+fn obj_round_trip() {
+	let mut geom = MeshGeometry::new();
+	for (i, [v1, v2, v3, v4]) in cube_quads().into_iter().enumerate() {
+		geom.add_face4_data(v1, v2, v3, v4, face_section(i / 3));
+	}
+
+	let mut obj = vec![];
+	geom.to_obj(&mut obj).unwrap();
+	let text = String::from_utf8(obj.clone()).unwrap();
+	assert_eq!(text.lines().filter(|l| l.starts_with("vn ")).count(), 6);
+	assert_eq!(text.lines().filter(|l| l.starts_with("g ")).count(), 2);
+
+	let imported = MeshGeometry::<Vert>::from_obj(obj.as_slice()).unwrap();
+	assert_eq!(imported.vertices.len(), geom.vertices.len());
+	assert_eq!(imported.faces.len(), 2);
+	for (section, faces) in &imported.faces {
+		assert_eq!(faces.len(), 3);
+		for (face, original) in faces.iter().zip(&geom.faces[section]) {
+			assert_eq!(face.vertices, original.vertices);
+			assert_eq!(face.face_normal, original.face_normal);
+		}
+	}
+
+	let mut ply = vec![];
+	geom.to_ply_binary(&mut ply).unwrap();
+	let header = b"end_header\n";
+	let body_start = ply.windows(header.len()).position(|w| w == header).unwrap() + header.len();
+	assert!(ply.starts_with(b"ply\nformat binary_little_endian 1.0\nelement vertex 8\n"));
+	assert_eq!(ply.len() - body_start, 8 * 12 + 6 * (1 + 4 * 4));
+}
+
+#[test]
+fn weld_vertices() {
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(1.0, 0.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	geom.add_face3(
+		vert(1.0001, 0.0, 0.0),
+		vert(1.0, 1.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	assert_eq!(geom.vertices.len(), 5);
+
+	assert_eq!(geom.weld_vertices(0.00001), 0);
+	assert_eq!(geom.weld_vertices(0.001), 1);
+	assert_eq!(geom.vertices.len(), 4);
+
+	let faces = &geom.faces[&0];
+	assert_eq!(faces[0].vertices, vec![0, 1, 2]);
+	assert_eq!(faces[1].vertices, vec![1, 3, 2]);
+	assert_eq!(geom.vertex(1).faces.len(), 2);
+
+	// the noisy position resolves to the welded vertex
+	assert_eq!(geom.get_vertex_index(vec3(1.0001, 0.0, 0.0)), 1);
+	assert_eq!(geom.get_vertex_index(vec3(5.0, 0.0, 0.0)), 4);
+
+	// vertices of the same face are kept apart
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(0.0001, 0.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	assert_eq!(geom.weld_vertices(0.001), 0);
+	assert_eq!(geom.faces[&0][0].vertices, vec![0, 1, 2]);
+}
+
+#[test]
+fn weld_far_vertices() {
+	// cells far beyond the i32 range for this epsilon
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		vert(3e9, 0.0, 0.0),
+		vert(3e9, 1e9, 0.0),
+		vert(-3e9, 0.0, -3e9),
+	);
+	geom.add_face3(
+		vert(4e9, 0.0, 0.0),
+		vert(3e9, 1e9, 0.0),
+		vert(3e9, 1e9, 1e9),
+	);
+	assert_eq!(geom.weld_vertices(0.01), 0);
+	assert_eq!(geom.vertices.len(), 5);
+}
+
+#[test]
+#[should_panic]
+fn weld_zero_epsilon() {
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(1.0, 0.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	geom.weld_vertices(0.0);
+}
+
+#[test]
+fn flip_faces() {
+	let triangle = || {
+		let mut geom = MeshGeometry::new();
+		geom.add_face3(
+			vert(0.0, 0.0, 0.0),
+			vert(1.0, 0.0, 0.0),
+			vert(0.0, 1.0, 0.0),
+		);
+		geom.add_face3_data(
+			vert(0.0, 0.0, 1.0),
+			vert(1.0, 0.0, 1.0),
+			vert(0.0, 1.0, 1.0),
+			face_section(1),
+		);
+		geom
+	};
+	let normals = |geom: &mut MeshGeometry<Vert>| {
+		buffer_normals(
+			&geom
+				.to_renderable_buffer_by_type(MeshBufferType::FaceNormals)
+				.vertex_buffer,
+		)
+	};
+
+	// generated normals follow the flipped winding
+	let mut geom = triangle();
+	geom.flip_faces();
+	assert_eq!(geom.face(0).vertices, vec![2, 1, 0]);
+	assert!(normals(&mut geom).iter().all(|n| *n == -Vec3::Z));
+	for i in 0..3 {
+		assert_eq!(geom.vertex(i).faces, vec![SectionIndex::from(0)]);
+	}
+
+	// cached normals are negated
+	let mut geom = triangle();
+	assert!(normals(&mut geom).iter().all(|n| *n == Vec3::Z));
+	geom.flip_section(1);
+	assert_eq!(geom.face(0).vertices, vec![0, 1, 2]);
+	assert_eq!(geom.faces[&1][0].vertices, vec![5, 4, 3]);
+	assert_eq!(geom.faces[&1][0].face_normal, Some(-Vec3::Z));
+	assert_eq!(&normals(&mut geom)[..3], &[Vec3::Z; 3]);
+	assert_eq!(&normals(&mut geom)[3..], &[-Vec3::Z; 3]);
+}
+
+#[test]
+fn project_uv() {
+	let mut geom = MeshGeometry::new();
+	let [v1, v2, v3, v4] = [
+		vec3(-1.0, 2.0, 3.0),
+		vec3(3.0, 2.0, 3.0),
+		vec3(3.0, 4.0, 3.0),
+		vec3(-1.0, 4.0, 3.0),
+	]
+	.map(|pos| UvVert {
+		pos,
+		uv: Vec2::ZERO,
+	});
+	geom.add_face4(v1, v2, v3, v4);
+
+	geom.project_uv_planar(Axis::Z);
+	let uvs: Vec<Vec2> = geom.vertices.iter().map(|v| v.data.uv).collect();
+	assert_eq!(
+		uvs,
+		vec![
+			vec2(0.0, 0.0),
+			vec2(1.0, 0.0),
+			vec2(1.0, 1.0),
+			vec2(0.0, 1.0)
+		]
+	);
+
+	geom.project_uv_planar(Axis::Y);
+	let uvs: Vec<Vec2> = geom.vertices.iter().map(|v| v.data.uv).collect();
+	assert_eq!(
+		uvs,
+		vec![
+			vec2(0.0, 0.0),
+			vec2(1.0, 0.0),
+			vec2(1.0, 0.0),
+			vec2(0.0, 0.0)
+		]
+	);
+
+	geom.project_uv_spherical();
+	assert!(geom
+		.vertices
+		.iter()
+		.all(|v| (0.0..=1.0).contains(&v.data.uv.x) && (0.0..=1.0).contains(&v.data.uv.y)));
+	assert!(geom.vertices[2].data.uv.y > 0.5);
+	assert!(geom.vertices[0].data.uv.y < 0.5);
+}
+
+#[test]
+fn extrude() {
+	for offset in [vec3(0.0, 0.0, 2.0), vec3(0.0, 0.0, -2.0)] {
+		let mut geom = MeshGeometry::new();
+		geom.add_face4(
+			vert(0.0, 0.0, 0.0),
+			vert(1.0, 0.0, 0.0),
+			vert(1.0, 1.0, 0.0),
+			vert(0.0, 1.0, 0.0),
+		);
+		geom.extrude(0, offset);
+
+		assert_eq!(geom.faces[&0].len(), 6);
+		assert_eq!(geom.vertices.len(), 8);
+
+		geom.generate_face_normals();
+		let center = geom.center();
+		for face in &geom.faces[&0] {
+			let face_center =
+				face.vertices
+					.iter()
+					.map(|i| geom.vertices[*i].data.pos)
+					.sum::<Vec3>() / 4.0;
+			assert!(face.face_normal.unwrap().dot(face_center - center) > 0.0);
+		}
+	}
+
+	// Only the outer edges of two adjacent quads get walls.
+	let mut geom = MeshGeometry::new();
+	geom.add_face4(
+		vert(0.0, 0.0, 0.0),
+		vert(1.0, 0.0, 0.0),
+		vert(1.0, 1.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	geom.add_face4(
+		vert(1.0, 0.0, 0.0),
+		vert(2.0, 0.0, 0.0),
+		vert(2.0, 1.0, 0.0),
+		vert(1.0, 1.0, 0.0),
+	);
+	geom.extrude(0, vec3(0.0, 0.0, 1.0));
+	assert_eq!(geom.faces[&0].len(), 2 + 2 + 6);
+	assert_eq!(geom.vertices.len(), 12);
+}
+
+#[test]
+fn export_empty_mesh() {
+	let modes = || {
+		[
+			MeshBufferType::NoNormals,
+			MeshBufferType::VertexNormals,
+			MeshBufferType::VertexNormalFaceData,
+			MeshBufferType::FaceNormals,
+		]
+	};
+
+	let mut geom = MeshGeometry::<UvVert>::new();
+	for mode in modes() {
+		let vertex_size = if mode == MeshBufferType::NoNormals {
+			20
+		} else {
+			32
+		};
+		let buffered = geom.to_buffered_geometry_by_type(mode);
+		assert_eq!(buffered.vertex_count, 0);
+		assert_eq!(buffered.vertex_size, vertex_size);
+		assert!(buffered.buffer.is_empty());
+		assert!(buffered.indices.is_none());
+	}
+
+	// Vertices left over from removed faces are not exported.
+	let mut geom = MeshGeometry::new();
+	geom.add_face3(
+		vert(0.0, 0.0, 0.0),
+		vert(1.0, 0.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	);
+	geom.remove_face(0);
+
+	for mode in modes() {
+		let buffer = geom.to_renderable_buffer_by_type(mode);
+		assert!(buffer.vertex_buffer.is_empty());
+		assert!(buffer.index_buffer.is_none());
+		assert_eq!((buffer.vertex_count, buffer.index_count), (0, 0));
+	}
+	assert_eq!(geom.stats().final_vertex_count, 0);
+}
+
+#[test]
+fn renderable_buffers_by_section() {
+	let mut geom = MeshGeometry::new();
+	let [v1, v2, v3, v4] = [
+		vert(0.0, 0.0, 0.0),
+		vert(1.0, 0.0, 0.0),
+		vert(1.0, 1.0, 0.0),
+		vert(0.0, 1.0, 0.0),
+	];
+	geom.add_face4_data(v1, v2, v3, v4, face_section(2));
+	geom.add_face3_data(v2, vert(2.0, 0.0, 0.0), v3, face_section(5));
+
+	for geom_type in [MeshBufferType::NoNormals, MeshBufferType::VertexNormals] {
+		let buffers = geom.to_renderable_buffers_by_section(geom_type);
+		assert_eq!(buffers.keys().copied().collect::<Vec<_>>(), vec![2, 5]);
+
+		let quad = &buffers[&2];
+		let tri = &buffers[&5];
+		assert_eq!((quad.vertex_count, quad.index_count), (4, 6));
+		assert_eq!((tri.vertex_count, tri.index_count), (3, 3));
+
+		for buffer in [quad, tri] {
+			let indices: &[u32] = bytemuck::cast_slice(buffer.index_buffer.as_ref().unwrap());
+			assert!(indices.iter().all(|i| *i < buffer.vertex_count));
+		}
+	}
+	assert_eq!(geom.stats().final_vertex_count, 7);
+}
+
+#[test]
+fn triangulate_twice() {
+	let mut geom = MeshGeometry::new();
+	for [v1, v2, v3, v4] in cube_quads() {
+		geom.add_face4(v1, v2, v3, v4);
+	}
+
+	geom.triangulate();
+	assert_eq!(geom.faces[&0].len(), 12);
+	assert_eq!(geom.stats().triangulated_quads, 6);
+
+	geom.triangulate();
+	geom.to_renderable_buffer_by_type(MeshBufferType::FaceNormals);
+	assert_eq!(geom.faces[&0].len(), 12);
+	assert_eq!(geom.stats().triangulated_quads, 6);
+
+	let normals: Vec<_> = geom.faces[&0].iter().map(|f| f.face_normal).collect();
+	assert!(normals.iter().all(|n| n.is_some()));
+	geom.generate_face_normals();
+	assert_eq!(
+		geom.faces[&0]
+			.iter()
+			.map(|f| f.face_normal)
+			.collect::<Vec<_>>(),
+		normals
+	);
+
+	geom.add_face4(
+		vert(0.0, 0.0, 5.0),
+		vert(1.0, 0.0, 5.0),
+		vert(1.0, 1.0, 5.0),
+		vert(0.0, 1.0, 5.0),
+	);
+	geom.triangulate();
+	assert_eq!(geom.faces[&0].len(), 14);
+}
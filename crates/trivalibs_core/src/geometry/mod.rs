@@ -1,4 +1,9 @@
+pub mod aabb;
 pub mod interpolation;
 pub mod line_2d;
 pub mod mesh_geometry_3d;
+pub mod primitives;
+pub mod triangulate;
 pub mod vertex_index;
+
+pub use aabb::Aabb;
@@ -0,0 +1,78 @@
+use super::mesh_geometry_3d::{MeshGeometry, Position3D};
+use crate::rendering::buffered_geometry::OverrideAttributesWith;
+use glam::{vec3, Vec3};
+
+fn icosahedron() -> Vec<[Vec3; 3]> {
+	let t = (1.0 + 5f32.sqrt()) / 2.0;
+
+	let vertices = [
+		vec3(-1.0, t, 0.0),
+		vec3(1.0, t, 0.0),
+		vec3(-1.0, -t, 0.0),
+		vec3(1.0, -t, 0.0),
+		vec3(0.0, -1.0, t),
+		vec3(0.0, 1.0, t),
+		vec3(0.0, -1.0, -t),
+		vec3(0.0, 1.0, -t),
+		vec3(t, 0.0, -1.0),
+		vec3(t, 0.0, 1.0),
+		vec3(-t, 0.0, -1.0),
+		vec3(-t, 0.0, 1.0),
+	]
+	.map(|v| v.normalize());
+
+	let faces: [[usize; 3]; 20] = [
+		[0, 11, 5],
+		[0, 5, 1],
+		[0, 1, 7],
+		[0, 7, 10],
+		[0, 10, 11],
+		[1, 5, 9],
+		[5, 11, 4],
+		[11, 10, 2],
+		[10, 7, 6],
+		[7, 1, 8],
+		[3, 9, 4],
+		[3, 4, 2],
+		[3, 2, 6],
+		[3, 6, 8],
+		[3, 8, 9],
+		[4, 9, 5],
+		[2, 4, 11],
+		[6, 2, 10],
+		[8, 6, 7],
+		[9, 8, 1],
+	];
+
+	faces.iter().map(|f| f.map(|i| vertices[i])).collect()
+}
+
+/// Unit sphere with evenly distributed triangles, created by subdividing an icosahedron.
+/// Each subdivision splits every triangle into four, with the new vertices projected onto the sphere.
+/// Faces are wound counter clockwise, seen from outside.
+pub fn icosphere<V>(subdivisions: u32) -> MeshGeometry<V>
+where
+	V: OverrideAttributesWith + Position3D + Copy + From<Vec3>,
+{
+	let mut triangles = icosahedron();
+
+	for _ in 0..subdivisions {
+		triangles = triangles
+			.into_iter()
+			.flat_map(|[a, b, c]| {
+				let [ab, bc, ca] =
+					[(a, b), (b, c), (c, a)].map(|(p, q)| ((p + q) * 0.5).normalize());
+				[[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+			})
+			.collect();
+	}
+
+	let mut geom = MeshGeometry::new();
+	for [a, b, c] in triangles {
+		geom.add_face3(V::from(a), V::from(b), V::from(c));
+	}
+	geom
+}
+
+#[cfg(test)]
+mod tests;
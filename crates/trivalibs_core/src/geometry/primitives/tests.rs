@@ -0,0 +1,54 @@
+use super::icosphere;
+use crate::{
+	geometry::mesh_geometry_3d::{MeshBufferType, Position3D},
+	rendering::buffered_geometry::NoAttributeOverride,
+};
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct Vert {
+	pos: Vec3,
+}
+impl From<Vec3> for Vert {
+	fn from(pos: Vec3) -> Self {
+		Vert { pos }
+	}
+}
+impl Position3D for Vert {
+	fn position(&self) -> Vec3 {
+		self.pos
+	}
+}
+impl NoAttributeOverride for Vert {}
+
+#[test]
+fn icosphere_subdivisions() {
+	let mut faces = 20;
+	for level in 0..4 {
+		let mut geom = icosphere::<Vert>(level);
+		let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::NoNormals);
+
+		assert_eq!(buffer.index_count, faces * 3);
+		assert_eq!(buffer.vertex_count, faces / 2 + 2);
+		assert!(geom
+			.vertices
+			.iter()
+			.all(|v| (v.data.pos.length() - 1.0).abs() < 1e-5));
+
+		faces *= 4;
+	}
+
+	let geom = icosphere::<Vert>(0);
+	assert_eq!(geom.vertices.len(), 12);
+}
+
+#[test]
+fn icosphere_winding() {
+	let mut geom = icosphere::<Vert>(1);
+	let buffer = geom.to_renderable_buffer_by_type(MeshBufferType::FaceNormals);
+	for [v, normal] in bytemuck::cast_slice::<u8, [Vec3; 2]>(&buffer.vertex_buffer) {
+		assert!(v.dot(*normal) > 0.0);
+	}
+}
@@ -0,0 +1,111 @@
+use glam::Vec2;
+
+fn cross(o: Vec2, a: Vec2, b: Vec2) -> f32 {
+	(a - o).perp_dot(b - o)
+}
+
+/// Signed area of a polygon. Positive for counter clockwise winding.
+pub fn signed_area(polygon: &[Vec2]) -> f32 {
+	let len = polygon.len();
+	(0..len)
+		.map(|i| polygon[i].perp_dot(polygon[(i + 1) % len]))
+		.sum::<f32>()
+		* 0.5
+}
+
+fn in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+	cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+/// Triangulates a simple (non self-intersecting) polygon, which may be concave.
+/// Returns triangles as index triples into the input.
+/// The triangles have the same winding as the polygon, which may be clockwise or counter clockwise.
+pub fn ear_clip(polygon: &[Vec2]) -> Vec<[usize; 3]> {
+	let len = polygon.len();
+	if len < 3 {
+		return vec![];
+	}
+
+	let ccw = signed_area(polygon) >= 0.0;
+
+	// remaining vertices in counter clockwise order
+	let mut indices: Vec<usize> = if ccw {
+		(0..len).collect()
+	} else {
+		(0..len).rev().collect()
+	};
+
+	let mut triangles = Vec::with_capacity(len - 2);
+
+	while indices.len() > 3 {
+		let count = indices.len();
+		let mut clipped = false;
+
+		for i in 0..count {
+			let prev = indices[(i + count - 1) % count];
+			let curr = indices[i];
+			let next = indices[(i + 1) % count];
+
+			let a = polygon[prev];
+			let b = polygon[curr];
+			let c = polygon[next];
+
+			let turn = cross(a, b, c);
+
+			// collinear vertices do not span a triangle and can be dropped
+			if turn.abs() <= f32::EPSILON {
+				indices.remove(i);
+				clipped = true;
+				break;
+			}
+
+			if turn < 0.0 {
+				continue;
+			}
+
+			let contains_other = indices.iter().any(|j| {
+				*j != prev
+					&& *j != curr && *j != next
+					&& polygon[*j] != a
+					&& polygon[*j] != b
+					&& polygon[*j] != c
+					&& in_triangle(polygon[*j], a, b, c)
+			});
+
+			if !contains_other {
+				triangles.push(if ccw {
+					[prev, curr, next]
+				} else {
+					[next, curr, prev]
+				});
+				indices.remove(i);
+				clipped = true;
+				break;
+			}
+		}
+
+		// no ear left, the polygon is not simple
+		if !clipped {
+			break;
+		}
+	}
+
+	if indices.len() == 3
+		&& cross(
+			polygon[indices[0]],
+			polygon[indices[1]],
+			polygon[indices[2]],
+		) > 0.0
+	{
+		triangles.push(if ccw {
+			[indices[0], indices[1], indices[2]]
+		} else {
+			[indices[2], indices[1], indices[0]]
+		});
+	}
+
+	triangles
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use super::{ear_clip, signed_area};
+use glam::{vec2, Vec2};
+
+fn contains(polygon: &[Vec2], p: Vec2) -> bool {
+	let len = polygon.len();
+	let mut inside = false;
+	for i in 0..len {
+		let a = polygon[i];
+		let b = polygon[(i + 1) % len];
+		if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+			inside = !inside;
+		}
+	}
+	inside
+}
+
+fn triangles_area(polygon: &[Vec2], triangles: &[[usize; 3]]) -> f32 {
+	triangles
+		.iter()
+		.map(|t| signed_area(&[polygon[t[0]], polygon[t[1]], polygon[t[2]]]))
+		.sum()
+}
+
+#[test]
+fn convex_quad() {
+	let quad = [
+		vec2(0.0, 0.0),
+		vec2(1.0, 0.0),
+		vec2(1.0, 1.0),
+		vec2(0.0, 1.0),
+	];
+
+	let triangles = ear_clip(&quad);
+	assert_eq!(triangles.len(), 2);
+	assert_eq!(triangles_area(&quad, &triangles), 1.0);
+}
+
+#[test]
+fn concave_l_shape() {
+	let l_shape = [
+		vec2(0.0, 0.0),
+		vec2(2.0, 0.0),
+		vec2(2.0, 1.0),
+		vec2(1.0, 1.0),
+		vec2(1.0, 2.0),
+		vec2(0.0, 2.0),
+	];
+
+	let triangles = ear_clip(&l_shape);
+	assert_eq!(triangles.len(), 4);
+	assert_eq!(triangles_area(&l_shape, &triangles), 3.0);
+
+	for t in &triangles {
+		let centroid = (l_shape[t[0]] + l_shape[t[1]] + l_shape[t[2]]) / 3.0;
+		assert!(contains(&l_shape, centroid));
+	}
+}
+
+#[test]
+fn clockwise_input() {
+	let mut l_shape = vec![
+		vec2(0.0, 0.0),
+		vec2(2.0, 0.0),
+		vec2(2.0, 1.0),
+		vec2(1.0, 1.0),
+		vec2(1.0, 2.0),
+		vec2(0.0, 2.0),
+	];
+	l_shape.reverse();
+
+	let triangles = ear_clip(&l_shape);
+	assert_eq!(triangles.len(), 4);
+	assert_eq!(triangles_area(&l_shape, &triangles), -3.0);
+
+	for t in &triangles {
+		let centroid = (l_shape[t[0]] + l_shape[t[1]] + l_shape[t[2]]) / 3.0;
+		assert!(contains(&l_shape, centroid));
+	}
+}
+
+#[test]
+fn degenerate_input() {
+	assert!(ear_clip(&[vec2(0.0, 0.0), vec2(1.0, 0.0)]).is_empty());
+}
@@ -1,6 +1,9 @@
+use crate::utils::default;
 use bytemuck::Pod;
+use glam::{Vec2, Vec3};
 use serde::Serialize;
 use serde_repr::Serialize_repr;
+use std::{collections::HashMap, ops::Range};
 
 /// Sync with WebGL type values.
 /// For possible values see: https://developer.mozilla.org/en-US/docs/Web/API/WebGLRenderingContext/vertexAttribPointer
@@ -16,6 +19,17 @@ pub enum AttributeType {
 	HalfFloat = 0x140B,
 }
 
+impl AttributeType {
+	/// Returns the byte size of a single component.
+	pub const fn byte_size(&self) -> u32 {
+		match self {
+			Self::Byte | Self::UnsignedByte => 1,
+			Self::Short | Self::UnsignedShort | Self::HalfFloat => 2,
+			Self::Float => 4,
+		}
+	}
+}
+
 /// For numeric values see: https://developer.mozilla.org/en-US/docs/Web/API/WebGL_API/Constants
 #[repr(u32)]
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize_repr)]
@@ -171,6 +185,62 @@ impl VertexFormat {
 	}
 }
 
+/// Packs a float into the bits of a half-precision float, for `Float16x2` and `Float16x4` attributes.
+/// Rounds to the nearest representable value. Values beyond the half-float range become infinity,
+/// values too small for the smallest subnormal become zero.
+pub fn f32_to_f16(value: f32) -> u16 {
+	let bits = value.to_bits();
+	let sign = ((bits >> 16) & 0x8000) as u16;
+	let exp = ((bits >> 23) & 0xff) as i32;
+	let mantissa = bits & 0x7f_ffff;
+
+	if exp == 0xff {
+		// infinity, or a quiet NaN
+		return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+	}
+
+	let exp = exp - 127 + 15;
+	if exp >= 0x1f {
+		return sign | 0x7c00;
+	}
+
+	// Rounds half to even. A carry out of the mantissa correctly increments the exponent.
+	let round = |value: u32, shift: u32| {
+		let truncated = value >> shift;
+		let rest = value & ((1 << shift) - 1);
+		let half = 1 << (shift - 1);
+		truncated + (rest > half || (rest == half && truncated & 1 == 1)) as u32
+	};
+
+	if exp <= 0 {
+		if exp < -10 {
+			return sign;
+		}
+		// subnormal half float, with the implicit leading bit of the f32 mantissa
+		return sign | round(mantissa | 0x80_0000, (14 - exp) as u32) as u16;
+	}
+
+	sign | round(((exp as u32) << 23) | mantissa, 13) as u16
+}
+
+/// Unpacks the bits of a half-precision float, see [`f32_to_f16`].
+pub fn f16_to_f32(half: u16) -> f32 {
+	let sign = ((half & 0x8000) as u32) << 16;
+	let exp = ((half >> 10) & 0x1f) as u32;
+	let mantissa = (half & 0x3ff) as u32;
+
+	let bits = match exp {
+		0 => {
+			// zero or subnormal, mantissa * 2^-24
+			let value = mantissa as f32 / (1 << 24) as f32;
+			return f32::from_bits(value.to_bits() | sign);
+		}
+		0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+		_ => sign | ((exp + 127 - 15) << 23) | (mantissa << 13),
+	};
+	f32::from_bits(bits)
+}
+
 pub struct VertexType {
 	pub name: &'static str,
 	pub format: VertexFormat,
@@ -186,13 +256,56 @@ pub fn vert_type(name: &'static str, format: VertexFormat) -> VertexType {
 	VertexType::new(name, format)
 }
 
-#[derive(Clone, Serialize, Debug)]
+#[derive(Clone, Serialize, Debug, PartialEq)]
 pub struct AttributeLayout {
 	pub name: &'static str,
 	pub size: u32,
 	pub attr_type: AttributeType,
 	pub normalized: bool,
+	/// Byte offset of the attribute in the first vertex.
 	pub offset: u32,
+	/// Bytes between the attribute values of consecutive vertices.
+	pub stride: u32,
+}
+
+impl AttributeLayout {
+	/// Bytes of a single attribute value.
+	pub fn byte_size(&self) -> u32 {
+		self.size * self.attr_type.byte_size()
+	}
+}
+
+/// How the attribute values of a buffered geometry are arranged in its buffer.
+#[derive(Clone, Copy, Serialize, Debug, Default, PartialEq, Eq)]
+pub enum BufferLayout {
+	/// All attributes of a vertex are stored next to each other.
+	#[default]
+	Interleaved,
+	/// The values of each attribute are stored in one contiguous block,
+	/// e.g. for WebGL pipelines with a separate buffer per attribute.
+	Planar,
+}
+
+/// Decodes index bytes, as stored in index buffers, into u32 indices.
+pub(crate) fn decode_indices(bytes: &[u8]) -> Vec<u32> {
+	bytes
+		.chunks_exact(4)
+		.map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+		.collect()
+}
+
+/// The cell and all cells adjacent to it, skipping cells beyond the i64 range.
+fn neighbour_cells(cell: &[i64]) -> Vec<Vec<i64>> {
+	cell.iter().fold(vec![vec![]], |cells, c| {
+		cells
+			.iter()
+			.flat_map(|prefix| {
+				(-1..=1)
+					.filter_map(|d| c.checked_add(d))
+					.map(|c| [prefix.as_slice(), &[c]].concat())
+			})
+			.collect()
+	})
 }
 
 #[derive(Clone, Serialize, Debug)]
@@ -209,6 +322,652 @@ pub struct BufferedGeometry {
 	pub vertex_count: u32,
 	pub rendering_primitive: RenderingPrimitive,
 	pub vertex_layout: Vec<AttributeLayout>,
+
+	/// Min and max corners of the `position` attribute of all vertices in the buffer,
+	/// so that the extents are known without decoding the buffer.
+	/// `None` for geometry without `position` attribute. Missing components are 0.
+	pub bounds_min: Option<[f32; 3]>,
+	pub bounds_max: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeometryError {
+	/// The index buffer byte length is not a multiple of 4.
+	MisalignedIndices { byte_len: usize },
+	/// An index points past the last vertex in the buffer.
+	IndexOutOfRange {
+		position: usize,
+		index: u32,
+		vertex_count: u32,
+	},
+	/// The index count does not fit the vertices per element of the rendering primitive.
+	InvalidIndexCount {
+		index_count: usize,
+		primitive: RenderingPrimitive,
+	},
+	/// The operation does not support the rendering primitive of the geometry.
+	UnsupportedPrimitive { primitive: RenderingPrimitive },
+	/// The geometry at `index` has a different vertex layout or rendering primitive than the first one.
+	LayoutMismatch { index: usize },
+	/// The geometry at `index` has a planar buffer layout, but the operation needs interleaved vertices.
+	PlanarLayout { index: usize },
+	/// The values of an attribute don't fill the same number of vertices as the first attribute.
+	AttributeLengthMismatch {
+		name: &'static str,
+		expected_bytes: usize,
+		actual_bytes: usize,
+	},
+}
+
+impl std::fmt::Display for GeometryError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			GeometryError::MisalignedIndices { byte_len } => write!(
+				f,
+				"index buffer length of {} bytes is not a multiple of 4",
+				byte_len
+			),
+			GeometryError::IndexOutOfRange {
+				position,
+				index,
+				vertex_count,
+			} => write!(
+				f,
+				"index {} at position {} is out of range for {} vertices",
+				index, position, vertex_count
+			),
+			GeometryError::InvalidIndexCount {
+				index_count,
+				primitive,
+			} => write!(
+				f,
+				"index count {} is not a multiple of {} as required by {:?}",
+				index_count,
+				primitive.vertices_per_element(),
+				primitive
+			),
+			GeometryError::UnsupportedPrimitive { primitive } => {
+				write!(f, "rendering primitive {:?} is not supported", primitive)
+			}
+			GeometryError::LayoutMismatch { index } => write!(
+				f,
+				"geometry {} does not match the vertex layout and primitive of the first geometry",
+				index
+			),
+			GeometryError::PlanarLayout { index } => write!(
+				f,
+				"geometry {} has a planar buffer layout, expected interleaved vertices",
+				index
+			),
+			GeometryError::AttributeLengthMismatch {
+				name,
+				expected_bytes,
+				actual_bytes,
+			} => write!(
+				f,
+				"attribute `{}` has {} bytes of values, expected {}",
+				name, actual_bytes, expected_bytes
+			),
+		}
+	}
+}
+
+impl std::error::Error for GeometryError {}
+
+impl RenderingPrimitive {
+	/// Number of vertices per element for primitives drawn as independent elements,
+	/// or 1 for strips, loops, fans and points.
+	pub const fn vertices_per_element(&self) -> usize {
+		match self {
+			Self::Triangles => 3,
+			Self::Lines => 2,
+			_ => 1,
+		}
+	}
+}
+
+impl BufferedGeometry {
+	/// Number of vertices stored in the vertex buffer.
+	pub fn buffer_vertex_count(&self) -> u32 {
+		if self.vertex_size == 0 {
+			return 0;
+		}
+		self.buffer.len() as u32 / self.vertex_size
+	}
+
+	/// Min and max corners of the float `position` attribute of all buffer vertices.
+	/// Returns `None` if there is no such attribute or no vertex.
+	pub fn position_bounds(&self) -> Option<([f32; 3], [f32; 3])> {
+		let attr = self
+			.vertex_layout
+			.iter()
+			.find(|attr| attr.name == "position")?;
+		let component_size = match attr.attr_type {
+			AttributeType::Float => 4,
+			AttributeType::HalfFloat => 2,
+			_ => return None,
+		};
+
+		(0..self.buffer_vertex_count())
+			.fold(None, |bounds: Option<(Vec3, Vec3)>, i| {
+				let start = (attr.offset + i * attr.stride) as usize;
+				let bytes = &self.buffer[start..start + attr.byte_size() as usize];
+				// missing components of 1D and 2D positions are 0
+				let mut pos = Vec3::ZERO;
+				for (c, b) in bytes.chunks_exact(component_size).take(3).enumerate() {
+					pos[c] = match b {
+						[b0, b1] => f16_to_f32(u16::from_ne_bytes([*b0, *b1])),
+						_ => f32::from_ne_bytes([b[0], b[1], b[2], b[3]]),
+					};
+				}
+				Some(bounds.map_or((pos, pos), |(min, max)| (min.min(pos), max.max(pos))))
+			})
+			.map(|(min, max)| (min.to_array(), max.to_array()))
+	}
+
+	/// Sets `bounds_min` and `bounds_max` from [`BufferedGeometry::position_bounds`].
+	pub fn with_bounds(mut self) -> Self {
+		let bounds = self.position_bounds();
+		self.bounds_min = bounds.map(|(min, _)| min);
+		self.bounds_max = bounds.map(|(_, max)| max);
+		self
+	}
+
+	/// Attributes of the vertex at `index` in the vertex buffer.
+	pub fn vertex_attributes(&self, index: usize) -> VertexAttributes<'_> {
+		VertexAttributes {
+			layout: &self.vertex_layout,
+			data: &self.buffer,
+			index,
+		}
+	}
+
+	/// Planar if any attribute is stored in its own block, derived from the attribute strides.
+	pub fn buffer_layout(&self) -> BufferLayout {
+		if self
+			.vertex_layout
+			.iter()
+			.any(|attr| attr.stride != self.vertex_size)
+		{
+			BufferLayout::Planar
+		} else {
+			BufferLayout::Interleaved
+		}
+	}
+
+	/// Copy of the geometry with all attributes interleaved per vertex.
+	pub fn to_interleaved(&self) -> BufferedGeometry {
+		if self.buffer_layout() == BufferLayout::Interleaved {
+			return self.clone();
+		}
+
+		let mut buffer = Vec::with_capacity(self.buffer.len());
+		for i in 0..self.buffer_vertex_count() as usize {
+			for attr in &self.vertex_layout {
+				let start = (attr.offset + i as u32 * attr.stride) as usize;
+				buffer.extend_from_slice(&self.buffer[start..start + attr.byte_size() as usize]);
+			}
+		}
+
+		let mut offset = 0;
+		let vertex_layout = self
+			.vertex_layout
+			.iter()
+			.map(|attr| {
+				let layout = AttributeLayout {
+					offset,
+					stride: self.vertex_size,
+					..attr.clone()
+				};
+				offset += attr.byte_size();
+				layout
+			})
+			.collect();
+
+		BufferedGeometry {
+			buffer,
+			vertex_layout,
+			..self.clone()
+		}
+	}
+
+	/// Decodes the index bytes into u32 indices.
+	pub fn decoded_indices(&self) -> Option<Vec<u32>> {
+		self.indices.as_deref().map(decode_indices)
+	}
+
+	/// Checks that all indices point into the vertex buffer, and that the index count
+	/// matches the rendering primitive. Geometry without indices is always valid.
+	pub fn validate_indices(&self) -> Result<(), GeometryError> {
+		let Some(bytes) = &self.indices else {
+			return Ok(());
+		};
+
+		if bytes.len() % 4 != 0 {
+			return Err(GeometryError::MisalignedIndices {
+				byte_len: bytes.len(),
+			});
+		}
+
+		let indices = self.decoded_indices().unwrap();
+		let vertex_count = self.buffer_vertex_count();
+
+		if let Some((position, index)) = indices
+			.iter()
+			.enumerate()
+			.find(|(_, index)| **index >= vertex_count)
+		{
+			return Err(GeometryError::IndexOutOfRange {
+				position,
+				index: *index,
+				vertex_count,
+			});
+		}
+
+		if !indices
+			.len()
+			.is_multiple_of(self.rendering_primitive.vertices_per_element())
+		{
+			return Err(GeometryError::InvalidIndexCount {
+				index_count: indices.len(),
+				primitive: self.rendering_primitive,
+			});
+		}
+
+		Ok(())
+	}
+
+	/// Merges vertices with equal attributes and returns an indexed geometry.
+	/// Float attributes within `epsilon` of each other are considered equal,
+	/// all other bytes need to match exactly. Each vertex merges into the first kept vertex
+	/// within tolerance, otherwise it is kept itself. An `epsilon` of 0 merges exact copies only.
+	/// Existing indices and the rendering primitive are preserved.
+	/// Planar geometries are interleaved first.
+	///
+	/// # Panics
+	///
+	/// Panics if `epsilon` is negative or NaN.
+	pub fn weld(&self, epsilon: f32) -> BufferedGeometry {
+		assert!(
+			epsilon >= 0.0,
+			"weld epsilon must not be negative, got {}",
+			epsilon
+		);
+		if self.buffer_layout() == BufferLayout::Planar {
+			return self.to_interleaved().weld(epsilon);
+		}
+		let vertex_size = self.vertex_size as usize;
+		let indices = self
+			.decoded_indices()
+			.unwrap_or_else(|| (0..self.buffer_vertex_count()).collect());
+
+		let float_ranges = self
+			.vertex_layout
+			.iter()
+			.filter(|attr| attr.attr_type == AttributeType::Float)
+			.map(|attr| attr.offset as usize..(attr.offset + attr.size * 4) as usize)
+			.collect::<Vec<_>>();
+
+		let mut buffer = Vec::with_capacity(self.buffer.len());
+		let mut remapped = Vec::with_capacity(self.buffer_vertex_count() as usize);
+
+		if epsilon == 0.0 || float_ranges.is_empty() {
+			let mut welded = HashMap::<&[u8], u32>::new();
+			for vertex in self.buffer.chunks_exact(vertex_size) {
+				let next = welded.len() as u32;
+				let index = *welded.entry(vertex).or_insert_with(|| {
+					buffer.extend_from_slice(vertex);
+					next
+				});
+				remapped.push(index);
+			}
+		} else {
+			let read = |vertex: &[u8], i: usize| {
+				f32::from_ne_bytes([vertex[i], vertex[i + 1], vertex[i + 2], vertex[i + 3]])
+			};
+			let float_offsets = float_ranges
+				.iter()
+				.flat_map(|range| range.clone().step_by(4))
+				.collect::<Vec<_>>();
+			let mut is_float = vec![false; vertex_size];
+			for range in &float_ranges {
+				is_float[range.clone()].fill(true);
+			}
+			let matches = |a: &[u8], b: &[u8]| {
+				(0..vertex_size).all(|i| is_float[i] || a[i] == b[i])
+					&& float_offsets
+						.iter()
+						.all(|i| (read(a, *i) - read(b, *i)).abs() <= epsilon)
+			};
+
+			// Kept vertices are sorted into cells of size epsilon by their first float attribute,
+			// e.g. the position. Matches straddling a cell boundary lie in neighbouring cells.
+			let cell = |vertex: &[u8]| {
+				float_ranges[0]
+					.clone()
+					.step_by(4)
+					.map(|i| (read(vertex, i) as f64 / epsilon as f64).floor() as i64)
+					.collect::<Vec<_>>()
+			};
+			let mut cells = HashMap::<Vec<i64>, Vec<u32>>::new();
+
+			for vertex in self.buffer.chunks_exact(vertex_size) {
+				let vertex_cell = cell(vertex);
+				let found = neighbour_cells(&vertex_cell)
+					.iter()
+					.filter_map(|c| cells.get(c))
+					.flatten()
+					.copied()
+					.filter(|i| {
+						let offset = *i as usize * vertex_size;
+						matches(vertex, &buffer[offset..offset + vertex_size])
+					})
+					.min();
+
+				let index = found.unwrap_or_else(|| {
+					let next = (buffer.len() / vertex_size) as u32;
+					buffer.extend_from_slice(vertex);
+					cells.entry(vertex_cell).or_default().push(next);
+					next
+				});
+				remapped.push(index);
+			}
+		}
+
+		let indices = indices
+			.iter()
+			.map(|i| remapped[*i as usize])
+			.collect::<Vec<_>>();
+
+		BufferedGeometry {
+			buffer,
+			vertex_count: indices.len() as u32,
+			indices: Some(bytemuck::cast_slice(&indices).to_vec()),
+			vertex_size: self.vertex_size,
+			rendering_primitive: self.rendering_primitive,
+			vertex_layout: self.vertex_layout.clone(),
+			bounds_min: self.bounds_min,
+			bounds_max: self.bounds_max,
+		}
+	}
+
+	/// Deduplicates vertices with identical bytes in all attributes, and generates the u32 index
+	/// buffer to draw them, e.g. to shrink the buffers of meshes built from unindexed triangles.
+	/// Same as [`BufferedGeometry::weld`] without tolerance.
+	pub fn optimize_indexed(&self) -> BufferedGeometry {
+		self.weld(0.0)
+	}
+
+	/// Concatenates geometries with the same vertex layout into one buffer, e.g. to draw many small
+	/// static meshes from a single form. Indices are offset to the merged buffer.
+	/// If any geometry is indexed, the others get sequential indices, so that all of them can be drawn.
+	/// Returns the merged geometry, and the index range of each source geometry,
+	/// or its vertex range if none of them is indexed.
+	/// Strips, loops, fans and planar geometries can't be concatenated and return an error.
+	pub fn merge(
+		geometries: &[BufferedGeometry],
+	) -> Result<(BufferedGeometry, Vec<Range<u32>>), GeometryError> {
+		let Some(first) = geometries.first() else {
+			return Ok((
+				BufferedGeometry {
+					buffer: vec![],
+					indices: None,
+					vertex_size: 0,
+					vertex_count: 0,
+					rendering_primitive: default(),
+					vertex_layout: vec![],
+					bounds_min: None,
+					bounds_max: None,
+				},
+				vec![],
+			));
+		};
+
+		let primitive = first.rendering_primitive;
+		if !matches!(
+			primitive,
+			RenderingPrimitive::Points | RenderingPrimitive::Lines | RenderingPrimitive::Triangles
+		) {
+			return Err(GeometryError::UnsupportedPrimitive { primitive });
+		}
+
+		for (index, geom) in geometries.iter().enumerate() {
+			if geom.buffer_layout() == BufferLayout::Planar {
+				return Err(GeometryError::PlanarLayout { index });
+			}
+			if geom.vertex_size != first.vertex_size
+				|| geom.vertex_layout != first.vertex_layout
+				|| geom.rendering_primitive != primitive
+			{
+				return Err(GeometryError::LayoutMismatch { index });
+			}
+			geom.validate_indices()?;
+		}
+
+		let indexed = geometries.iter().any(|g| g.indices.is_some());
+		let mut buffer = Vec::with_capacity(geometries.iter().map(|g| g.buffer.len()).sum());
+		let mut indices = Vec::<u32>::new();
+		let mut ranges = Vec::with_capacity(geometries.len());
+
+		for geom in geometries {
+			let offset = buffer.len() as u32 / first.vertex_size;
+			let vertex_count = geom.buffer_vertex_count();
+			buffer.extend_from_slice(&geom.buffer[..(vertex_count * first.vertex_size) as usize]);
+
+			if indexed {
+				let start = indices.len() as u32;
+				let geom_indices = geom
+					.decoded_indices()
+					.unwrap_or_else(|| (0..vertex_count).collect());
+				indices.extend(geom_indices.iter().map(|i| i + offset));
+				ranges.push(start..indices.len() as u32);
+			} else {
+				ranges.push(offset..offset + vertex_count);
+			}
+		}
+
+		let vertex_count = if indexed {
+			indices.len() as u32
+		} else {
+			buffer.len() as u32 / first.vertex_size
+		};
+
+		Ok((
+			BufferedGeometry {
+				buffer,
+				indices: indexed.then(|| bytemuck::cast_slice(&indices).to_vec()),
+				vertex_size: first.vertex_size,
+				vertex_count,
+				rendering_primitive: primitive,
+				vertex_layout: first.vertex_layout.clone(),
+				bounds_min: None,
+				bounds_max: None,
+			}
+			.with_bounds(),
+			ranges,
+		))
+	}
+}
+
+/// Builds a buffered geometry from one array of values per attribute,
+/// e.g. from positions and normals kept in separate typed arrays.
+/// Attributes are interleaved per vertex in the order they are added,
+/// or stored one after another with [`BufferLayout::Planar`].
+pub struct GeometryBuilder {
+	attributes: Vec<(VertexType, Vec<u8>)>,
+	indices: Option<Vec<u32>>,
+	rendering_primitive: RenderingPrimitive,
+	buffer_layout: BufferLayout,
+}
+
+impl GeometryBuilder {
+	pub fn new(rendering_primitive: RenderingPrimitive) -> Self {
+		Self {
+			attributes: vec![],
+			indices: None,
+			rendering_primitive,
+			buffer_layout: BufferLayout::Interleaved,
+		}
+	}
+
+	/// Adds an attribute with one value per vertex. `values` are cast to bytes,
+	/// so their type needs to match the byte size of `format`, e.g. `Vec3` for `Float32x3`.
+	pub fn attribute<T: Pod>(
+		mut self,
+		name: &'static str,
+		format: VertexFormat,
+		values: &[T],
+	) -> Self {
+		self.attributes.push((
+			vert_type(name, format),
+			bytemuck::cast_slice(values).to_vec(),
+		));
+		self
+	}
+
+	pub fn indices(mut self, indices: Vec<u32>) -> Self {
+		self.indices = Some(indices);
+		self
+	}
+
+	pub fn buffer_layout(mut self, buffer_layout: BufferLayout) -> Self {
+		self.buffer_layout = buffer_layout;
+		self
+	}
+
+	/// Arranges the attributes into a buffered geometry.
+	/// Fails if the attributes have values for different numbers of vertices,
+	/// or if the indices don't fit the vertices.
+	pub fn try_to_buffered_geometry(&self) -> Result<BufferedGeometry, GeometryError> {
+		let buffer_vertex_count = self.attributes.first().map_or(0, |(t, values)| {
+			values.len() / t.format.byte_size() as usize
+		});
+
+		for (t, values) in &self.attributes {
+			let expected_bytes = buffer_vertex_count * t.format.byte_size() as usize;
+			if values.len() != expected_bytes {
+				return Err(GeometryError::AttributeLengthMismatch {
+					name: t.name,
+					expected_bytes,
+					actual_bytes: values.len(),
+				});
+			}
+		}
+
+		let layout = create_buffered_geometry_layout(
+			self.attributes
+				.iter()
+				.map(|(t, _)| vert_type(t.name, t.format))
+				.collect(),
+		);
+
+		let mut vertex_layout = layout.vertex_layout;
+		let mut buffer = Vec::with_capacity(buffer_vertex_count * layout.vertex_size as usize);
+		match self.buffer_layout {
+			BufferLayout::Interleaved => {
+				for i in 0..buffer_vertex_count {
+					for (t, values) in &self.attributes {
+						let size = t.format.byte_size() as usize;
+						buffer.extend_from_slice(&values[i * size..(i + 1) * size]);
+					}
+				}
+			}
+			BufferLayout::Planar => {
+				for (attr, (t, values)) in vertex_layout.iter_mut().zip(&self.attributes) {
+					attr.offset = buffer.len() as u32;
+					attr.stride = t.format.byte_size();
+					buffer.extend_from_slice(values);
+				}
+			}
+		}
+
+		let geom = BufferedGeometry {
+			buffer,
+			vertex_count: self
+				.indices
+				.as_ref()
+				.map_or(buffer_vertex_count, |indices| indices.len()) as u32,
+			indices: self
+				.indices
+				.as_ref()
+				.map(|indices| bytemuck::cast_slice(indices).to_vec()),
+			vertex_size: layout.vertex_size,
+			rendering_primitive: self.rendering_primitive,
+			vertex_layout,
+			bounds_min: None,
+			bounds_max: None,
+		}
+		.with_bounds();
+		geom.validate_indices()?;
+
+		Ok(geom)
+	}
+
+	/// Same as [`GeometryBuilder::try_to_buffered_geometry`], but panics on invalid input.
+	pub fn to_buffered_geometry(&self) -> BufferedGeometry {
+		self.try_to_buffered_geometry()
+			.unwrap_or_else(|e| panic!("{}", e))
+	}
+}
+
+/// The attributes of a single vertex in a buffered geometry, looked up by name.
+pub struct VertexAttributes<'a> {
+	layout: &'a [AttributeLayout],
+	data: &'a [u8],
+	index: usize,
+}
+
+impl<'a> VertexAttributes<'a> {
+	/// Layout and bytes of the attribute, or `None` if the vertex has no attribute of that name.
+	pub fn get(&self, name: &str) -> Option<(&'a AttributeLayout, &'a [u8])> {
+		let attr = self.layout.iter().find(|attr| attr.name == name)?;
+		let start = (attr.offset + self.index as u32 * attr.stride) as usize;
+		let end = start + attr.byte_size() as usize;
+		Some((attr, &self.data[start..end]))
+	}
+
+	/// Components of a float or half-float attribute.
+	/// Returns `None` for missing or non-float attributes.
+	pub fn floats(&self, name: &str) -> Option<Vec<f32>> {
+		let (attr, bytes) = self.get(name)?;
+		match attr.attr_type {
+			AttributeType::Float => Some(
+				bytes
+					.chunks_exact(4)
+					.map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+					.collect(),
+			),
+			AttributeType::HalfFloat => Some(
+				bytes
+					.chunks_exact(2)
+					.map(|b| f16_to_f32(u16::from_ne_bytes([b[0], b[1]])))
+					.collect(),
+			),
+			_ => None,
+		}
+	}
+
+	/// A float attribute with at least two components as `Vec2`.
+	pub fn vec2(&self, name: &str) -> Option<Vec2> {
+		match self.floats(name)?.as_slice() {
+			[x, y, ..] => Some(Vec2::new(*x, *y)),
+			_ => None,
+		}
+	}
+
+	/// A float attribute with at least three components as `Vec3`.
+	pub fn vec3(&self, name: &str) -> Option<Vec3> {
+		match self.floats(name)?.as_slice() {
+			[x, y, z, ..] => Some(Vec3::new(*x, *y, *z)),
+			_ => None,
+		}
+	}
+}
+
+/// Reads vertex data back from the attributes of a buffered geometry.
+/// Counterpart to the `vertex_layout` of `BufferedVertexData`.
+pub trait FromAttributes: Sized {
+	fn from_attributes(attributes: &VertexAttributes) -> Self;
 }
 
 pub struct BufferedGeometryLayout {
@@ -228,10 +987,15 @@ pub fn create_buffered_geometry_layout(layout: Vec<VertexType>) -> BufferedGeome
 			attr_type: format.attr_type(),
 			normalized: format.normalized(),
 			offset: vertex_size,
+			stride: 0,
 		});
 		vertex_size += format.byte_size();
 	}
 
+	for attr in &mut vertex_layout {
+		attr.stride = vertex_size;
+	}
+
 	BufferedGeometryLayout {
 		vertex_layout,
 		vertex_size,
@@ -257,3 +1021,6 @@ impl<T: NoAttributeOverride> OverrideAttributesWith for T {
 		self.no_override_with(other)
 	}
 }
+
+#[cfg(test)]
+mod tests;
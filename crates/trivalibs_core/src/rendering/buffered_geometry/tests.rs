@@ -0,0 +1,497 @@
+use super::{
+	create_buffered_geometry_layout, f16_to_f32, f32_to_f16, vert_type, BufferLayout,
+	BufferedGeometry, GeometryBuilder, GeometryError, RenderingPrimitive, VertexFormat,
+};
+use glam::{vec2, vec3, Vec3};
+
+fn geometry(
+	positions: &[Vec3],
+	indices: &[u32],
+	rendering_primitive: RenderingPrimitive,
+) -> BufferedGeometry {
+	let layout =
+		create_buffered_geometry_layout(vec![vert_type("position", VertexFormat::Float32x3)]);
+
+	BufferedGeometry {
+		buffer: bytemuck::cast_slice(positions).to_vec(),
+		indices: Some(bytemuck::cast_slice(indices).to_vec()),
+		vertex_size: layout.vertex_size,
+		vertex_count: indices.len() as u32,
+		rendering_primitive,
+		vertex_layout: layout.vertex_layout,
+		bounds_min: None,
+		bounds_max: None,
+	}
+}
+
+fn quad() -> [Vec3; 4] {
+	[
+		vec3(0.0, 0.0, 0.0),
+		vec3(1.0, 0.0, 0.0),
+		vec3(1.0, 1.0, 0.0),
+		vec3(0.0, 1.0, 0.0),
+	]
+}
+
+#[test]
+fn validate_valid_indices() {
+	let geom = geometry(&quad(), &[0, 1, 2, 0, 2, 3], RenderingPrimitive::Triangles);
+	assert_eq!(geom.buffer_vertex_count(), 4);
+	assert_eq!(geom.validate_indices(), Ok(()));
+
+	let geom = geometry(&quad(), &[0, 1, 2, 3, 0], RenderingPrimitive::TriangleStrip);
+	assert_eq!(geom.validate_indices(), Ok(()));
+
+	let geom = geometry(&quad(), &[0, 1, 1, 2], RenderingPrimitive::Lines);
+	assert_eq!(geom.validate_indices(), Ok(()));
+}
+
+#[test]
+fn validate_index_out_of_range() {
+	let geom = geometry(&quad(), &[0, 1, 2, 0, 2, 4], RenderingPrimitive::Triangles);
+	let err = geom.validate_indices().unwrap_err();
+
+	assert_eq!(
+		err,
+		GeometryError::IndexOutOfRange {
+			position: 5,
+			index: 4,
+			vertex_count: 4
+		}
+	);
+	assert_eq!(
+		err.to_string(),
+		"index 4 at position 5 is out of range for 4 vertices"
+	);
+}
+
+#[test]
+fn validate_index_count() {
+	let geom = geometry(&quad(), &[0, 1, 2, 0, 2], RenderingPrimitive::Triangles);
+	let err = geom.validate_indices().unwrap_err();
+
+	assert_eq!(
+		err,
+		GeometryError::InvalidIndexCount {
+			index_count: 5,
+			primitive: RenderingPrimitive::Triangles
+		}
+	);
+	assert_eq!(
+		err.to_string(),
+		"index count 5 is not a multiple of 3 as required by Triangles"
+	);
+
+	let geom = geometry(&quad(), &[0, 1, 2], RenderingPrimitive::Lines);
+	assert!(matches!(
+		geom.validate_indices(),
+		Err(GeometryError::InvalidIndexCount { .. })
+	));
+}
+
+fn resolved_vertices(geom: &BufferedGeometry) -> Vec<&[u8]> {
+	let size = geom.vertex_size as usize;
+	geom.decoded_indices()
+		.unwrap()
+		.iter()
+		.map(|i| &geom.buffer[*i as usize * size..(*i as usize + 1) * size])
+		.collect()
+}
+
+#[test]
+fn weld() {
+	let [a, b, c, d] = quad();
+	let positions = [a, b, c, a, c, d];
+	let mut geom = geometry(&positions, &[], RenderingPrimitive::Triangles);
+	geom.indices = None;
+	geom.vertex_count = 6;
+
+	let welded = geom.weld(0.0);
+	assert_eq!(welded.buffer_vertex_count(), 4);
+	assert_eq!(welded.decoded_indices(), Some(vec![0, 1, 2, 0, 2, 3]));
+	assert_eq!(welded.vertex_count, 6);
+	assert_eq!(welded.rendering_primitive, RenderingPrimitive::Triangles);
+	assert_eq!(welded.validate_indices(), Ok(()));
+
+	// the drawn vertex sequence is unchanged
+	let sequence = geom
+		.buffer
+		.chunks_exact(geom.vertex_size as usize)
+		.collect::<Vec<_>>();
+	assert_eq!(resolved_vertices(&welded), sequence);
+
+	// existing indices are remapped
+	let positions = [a, b, c, d, a + 0.0001, c];
+	let geom = geometry(
+		&positions,
+		&[0, 1, 2, 4, 5, 3],
+		RenderingPrimitive::Triangles,
+	);
+	assert_eq!(geom.weld(0.0).buffer_vertex_count(), 5);
+
+	let welded = geom.weld(0.001);
+	assert_eq!(welded.buffer_vertex_count(), 4);
+	assert_eq!(welded.decoded_indices(), Some(vec![0, 1, 2, 0, 2, 3]));
+}
+
+#[test]
+fn weld_large_coordinates() {
+	// far beyond the i32 range in units of epsilon
+	let positions = [
+		vec3(3e9, 0.0, 0.0),
+		vec3(4e9, 0.0, 0.0),
+		vec3(-3e9, 0.0, 0.0),
+		vec3(3e9, 0.0, 0.0),
+	];
+	let geom = geometry(&positions, &[0, 1, 2, 3], RenderingPrimitive::Lines);
+
+	let welded = geom.weld(0.01);
+	assert_eq!(welded.buffer_vertex_count(), 3);
+	assert_eq!(welded.decoded_indices(), Some(vec![0, 1, 2, 0]));
+}
+
+#[test]
+fn weld_across_cell_boundaries() {
+	// values within epsilon, on both sides of a multiple of epsilon
+	let positions = [
+		vec3(0.00449, 0.0, 0.0),
+		vec3(0.00451, 0.0, 0.0),
+		vec3(-0.0001, 1.0, 0.0),
+		vec3(0.0001, 1.0, 0.0),
+		vec3(0.02, 0.0, 0.0),
+	];
+	let geom = geometry(&positions, &[0, 1, 2, 3, 4, 0], RenderingPrimitive::Lines);
+
+	let welded = geom.weld(0.001);
+	assert_eq!(welded.buffer_vertex_count(), 3);
+	assert_eq!(welded.decoded_indices(), Some(vec![0, 0, 1, 1, 2, 0]));
+	assert_eq!(
+		welded.vertex_attributes(1).vec3("position"),
+		Some(positions[2])
+	);
+}
+
+#[test]
+#[should_panic]
+fn weld_negative_epsilon() {
+	geometry(&quad(), &[0, 1], RenderingPrimitive::Lines).weld(-1.0);
+}
+
+#[test]
+fn optimize_indexed() {
+	let layout = create_buffered_geometry_layout(vec![
+		vert_type("position", VertexFormat::Float32x3),
+		vert_type("uv", VertexFormat::Float32x2),
+	]);
+	let [a, b, c, d] = quad();
+	// two triangles sharing the edge a-c, uvs follow the positions
+	let vertex = |p: Vec3| [p.x, p.y, p.z, p.x, p.y];
+	let vertices = [a, b, c, a, c, d].map(vertex);
+
+	let geom = BufferedGeometry {
+		buffer: bytemuck::cast_slice(&vertices).to_vec(),
+		indices: None,
+		vertex_size: layout.vertex_size,
+		vertex_count: 6,
+		rendering_primitive: RenderingPrimitive::Triangles,
+		vertex_layout: layout.vertex_layout.clone(),
+		bounds_min: None,
+		bounds_max: None,
+	};
+
+	let optimized = geom.optimize_indexed();
+	assert_eq!(optimized.buffer_vertex_count(), 4);
+	assert_eq!(optimized.buffer.len(), 4 * 20);
+	assert_eq!(optimized.decoded_indices(), Some(vec![0, 1, 2, 0, 2, 3]));
+	assert_eq!(optimized.vertex_count, 6);
+	assert_eq!(optimized.validate_indices(), Ok(()));
+	assert_eq!(optimized.vertex_attributes(3).vec3("position"), Some(d));
+
+	// same position with a different uv is a different vertex
+	let mut vertices = vertices;
+	vertices[3][3] = 0.5;
+	let geom = BufferedGeometry {
+		buffer: bytemuck::cast_slice(&vertices).to_vec(),
+		..geom
+	};
+	let optimized = geom.optimize_indexed();
+	assert_eq!(optimized.buffer_vertex_count(), 5);
+	assert_eq!(optimized.decoded_indices(), Some(vec![0, 1, 2, 3, 2, 4]));
+}
+
+fn cube(offset: Vec3) -> BufferedGeometry {
+	let positions = (0..8)
+		.map(|i| offset + vec3((i & 1) as f32, ((i >> 1) & 1) as f32, (i >> 2) as f32))
+		.collect::<Vec<_>>();
+	#[rustfmt::skip]
+	let indices = [
+		0, 2, 1, 1, 2, 3, 4, 5, 6, 5, 7, 6,
+		0, 1, 4, 1, 5, 4, 2, 6, 3, 3, 6, 7,
+		0, 4, 2, 2, 4, 6, 1, 3, 5, 3, 7, 5,
+	];
+	geometry(&positions, &indices, RenderingPrimitive::Triangles)
+}
+
+fn positions(geom: &BufferedGeometry, range: std::ops::Range<u32>) -> Vec<Vec3> {
+	resolved_vertices(geom)[range.start as usize..range.end as usize]
+		.iter()
+		.map(|v| *bytemuck::from_bytes::<Vec3>(v))
+		.collect()
+}
+
+#[test]
+fn merge() {
+	let cubes = [
+		cube(Vec3::ZERO),
+		cube(vec3(2.0, 0.0, 0.0)),
+		cube(vec3(0.0, 5.0, 0.0)),
+	];
+	let (merged, ranges) = BufferedGeometry::merge(&cubes).unwrap();
+
+	assert_eq!(merged.buffer_vertex_count(), 24);
+	assert_eq!(merged.vertex_count, 108);
+	assert_eq!(merged.validate_indices(), Ok(()));
+	assert_eq!(ranges, vec![0..36, 36..72, 72..108]);
+
+	for (cube, range) in cubes.iter().zip(ranges) {
+		assert_eq!(positions(&merged, range), positions(cube, 0..36));
+	}
+
+	let mut unindexed = cube(Vec3::ONE);
+	unindexed.indices = None;
+	unindexed.vertex_count = 8;
+	let (merged, ranges) = BufferedGeometry::merge(&[cubes[0].clone(), unindexed]).unwrap();
+	assert_eq!(ranges, vec![0..36, 36..44]);
+	assert_eq!(positions(&merged, 36..44)[7], vec3(2.0, 2.0, 2.0));
+
+	let mut lines = cube(Vec3::ZERO);
+	lines.rendering_primitive = RenderingPrimitive::Lines;
+	assert_eq!(
+		BufferedGeometry::merge(&[cubes[0].clone(), lines]).err(),
+		Some(GeometryError::LayoutMismatch { index: 1 })
+	);
+
+	let (empty, ranges) = BufferedGeometry::merge(&[]).unwrap();
+	assert_eq!(empty.vertex_count, 0);
+	assert!(ranges.is_empty());
+}
+
+#[test]
+fn half_floats() {
+	// exactly representable values, including the smallest normal and subnormal
+	let exact = [
+		(0.0, 0x0000),
+		(-0.0, 0x8000),
+		(1.0, 0x3c00),
+		(-2.0, 0xc000),
+		(0.5, 0x3800),
+		(65504.0, 0x7bff),
+		(6.1035156e-5, 0x0400),
+		(5.9604645e-8, 0x0001),
+		(f32::INFINITY, 0x7c00),
+		(f32::NEG_INFINITY, 0xfc00),
+	];
+	for (value, half) in exact {
+		assert_eq!(f32_to_f16(value), half, "{value}");
+		assert_eq!(f16_to_f32(half).to_bits(), value.to_bits(), "{half:x}");
+	}
+
+	assert_eq!(f32_to_f16(0.1), 0x2e66);
+	assert_eq!(f32_to_f16(65520.0), 0x7c00);
+	assert_eq!(f32_to_f16(1e-9), 0x0000);
+	// ties round to even
+	assert_eq!(f32_to_f16(1.0 + 1.0 / 2048.0), 0x3c00);
+	assert_eq!(f32_to_f16(1.0 + 3.0 / 2048.0), 0x3c02);
+	assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+
+	for i in -2000..2000 {
+		let value = i as f32 * 0.0137;
+		let decoded = f16_to_f32(f32_to_f16(value));
+		assert!((decoded - value).abs() <= value.abs() / 2048.0, "{value}");
+	}
+
+	let layout = create_buffered_geometry_layout(vec![vert_type("uv", VertexFormat::Float16x2)]);
+	let uv = [f32_to_f16(0.25), f32_to_f16(-3.5)];
+	let geom = BufferedGeometry {
+		buffer: bytemuck::cast_slice(&uv).to_vec(),
+		indices: None,
+		vertex_size: layout.vertex_size,
+		vertex_count: 1,
+		rendering_primitive: RenderingPrimitive::Points,
+		vertex_layout: layout.vertex_layout,
+		bounds_min: None,
+		bounds_max: None,
+	};
+	assert_eq!(geom.vertex_size, 4);
+	assert_eq!(
+		geom.vertex_attributes(0).vec2("uv"),
+		Some(glam::vec2(0.25, -3.5))
+	);
+}
+
+#[test]
+fn geometry_builder() {
+	let uvs = [
+		vec2(0.0, 0.0),
+		vec2(1.0, 0.0),
+		vec2(1.0, 1.0),
+		vec2(0.0, 1.0),
+	];
+	let geom = GeometryBuilder::new(RenderingPrimitive::Triangles)
+		.attribute("position", VertexFormat::Float32x3, &quad())
+		.attribute("uv", VertexFormat::Float32x2, &uvs)
+		.indices(vec![0, 1, 2, 0, 2, 3])
+		.to_buffered_geometry();
+
+	assert_eq!(geom.vertex_size, 20);
+	assert_eq!(geom.vertex_count, 6);
+	assert_eq!(geom.buffer_vertex_count(), 4);
+	assert_eq!(geom.vertex_layout[1].offset, 12);
+	let vertex = geom.vertex_attributes(2);
+	assert_eq!(vertex.vec3("position"), Some(vec3(1.0, 1.0, 0.0)));
+	assert_eq!(vertex.vec2("uv"), Some(vec2(1.0, 1.0)));
+
+	let unindexed = GeometryBuilder::new(RenderingPrimitive::Points)
+		.attribute("position", VertexFormat::Float32x3, &quad())
+		.to_buffered_geometry();
+	assert_eq!(unindexed.vertex_count, 4);
+	assert_eq!(unindexed.indices, None);
+}
+
+#[test]
+fn geometry_builder_length_mismatch() {
+	let builder = GeometryBuilder::new(RenderingPrimitive::Triangles)
+		.attribute("position", VertexFormat::Float32x3, &quad())
+		.attribute("uv", VertexFormat::Float32x2, &[vec2(0.0, 0.0); 3]);
+
+	assert_eq!(
+		builder.try_to_buffered_geometry().unwrap_err(),
+		GeometryError::AttributeLengthMismatch {
+			name: "uv",
+			expected_bytes: 32,
+			actual_bytes: 24,
+		}
+	);
+
+	let builder = GeometryBuilder::new(RenderingPrimitive::Triangles)
+		.attribute("position", VertexFormat::Float32x3, &quad())
+		.indices(vec![0, 1, 4]);
+	assert!(matches!(
+		builder.try_to_buffered_geometry(),
+		Err(GeometryError::IndexOutOfRange { index: 4, .. })
+	));
+}
+
+#[test]
+#[should_panic(expected = "attribute `uv` has 24 bytes of values, expected 32")]
+fn geometry_builder_panics_on_mismatch() {
+	GeometryBuilder::new(RenderingPrimitive::Triangles)
+		.attribute("position", VertexFormat::Float32x3, &quad())
+		.attribute("uv", VertexFormat::Float32x2, &[vec2(0.0, 0.0); 3])
+		.to_buffered_geometry();
+}
+
+#[test]
+fn planar_buffer_layout() {
+	let builder = |buffer_layout| {
+		GeometryBuilder::new(RenderingPrimitive::Lines)
+			.attribute(
+				"position",
+				VertexFormat::Float32x2,
+				&[vec2(1.0, 2.0), vec2(3.0, 4.0)],
+			)
+			.attribute("weight", VertexFormat::Float32, &[5.0f32, 6.0])
+			.buffer_layout(buffer_layout)
+			.to_buffered_geometry()
+	};
+	let interleaved = builder(BufferLayout::Interleaved);
+	let planar = builder(BufferLayout::Planar);
+
+	let floats = |geom: &BufferedGeometry| bytemuck::cast_slice::<u8, f32>(&geom.buffer).to_vec();
+	assert_eq!(floats(&interleaved), vec![1.0, 2.0, 5.0, 3.0, 4.0, 6.0]);
+	assert_eq!(floats(&planar), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+	assert_eq!(interleaved.buffer_layout(), BufferLayout::Interleaved);
+	assert_eq!(
+		(
+			interleaved.vertex_layout[1].offset,
+			interleaved.vertex_layout[1].stride
+		),
+		(8, 12)
+	);
+	assert_eq!(planar.buffer_layout(), BufferLayout::Planar);
+	assert_eq!(planar.vertex_size, 12);
+	assert_eq!(planar.buffer_vertex_count(), 2);
+	assert_eq!(
+		(
+			planar.vertex_layout[0].offset,
+			planar.vertex_layout[0].stride
+		),
+		(0, 8)
+	);
+	assert_eq!(
+		(
+			planar.vertex_layout[1].offset,
+			planar.vertex_layout[1].stride
+		),
+		(16, 4)
+	);
+
+	let vertex = planar.vertex_attributes(1);
+	assert_eq!(vertex.vec2("position"), Some(vec2(3.0, 4.0)));
+	assert_eq!(vertex.floats("weight"), Some(vec![6.0]));
+
+	let converted = planar.to_interleaved();
+	assert_eq!(converted.buffer, interleaved.buffer);
+	assert_eq!(converted.vertex_layout, interleaved.vertex_layout);
+
+	assert_eq!(
+		BufferedGeometry::merge(&[interleaved, planar]).unwrap_err(),
+		GeometryError::PlanarLayout { index: 1 }
+	);
+}
+
+#[test]
+fn bounds() {
+	let triangle = [
+		vec3(-1.0, 0.5, 2.0),
+		vec3(3.0, -2.0, 0.0),
+		vec3(0.0, 4.0, 1.0),
+	];
+	let geom = GeometryBuilder::new(RenderingPrimitive::Triangles)
+		.attribute("position", VertexFormat::Float32x3, &triangle)
+		.to_buffered_geometry();
+	assert_eq!(geom.bounds_min, Some([-1.0, -2.0, 0.0]));
+	assert_eq!(geom.bounds_max, Some([3.0, 4.0, 2.0]));
+
+	let flat = GeometryBuilder::new(RenderingPrimitive::Points)
+		.attribute(
+			"position",
+			VertexFormat::Float32x2,
+			&[vec2(1.0, 2.0), vec2(-1.0, 5.0)],
+		)
+		.to_buffered_geometry();
+	assert_eq!(flat.bounds_min, Some([-1.0, 2.0, 0.0]));
+	assert_eq!(flat.bounds_max, Some([1.0, 5.0, 0.0]));
+
+	let half = GeometryBuilder::new(RenderingPrimitive::Points)
+		.attribute(
+			"position",
+			VertexFormat::Float16x4,
+			&[[-2.0, 0.5, 1.0, 1.0], [3.0, -0.25, 4.0, 1.0]].map(|v| v.map(f32_to_f16)),
+		)
+		.to_buffered_geometry();
+	assert_eq!(half.bounds_min, Some([-2.0, -0.25, 1.0]));
+	assert_eq!(half.bounds_max, Some([3.0, 0.5, 4.0]));
+
+	let no_position = GeometryBuilder::new(RenderingPrimitive::Points)
+		.attribute("uv", VertexFormat::Float32x2, &[vec2(1.0, 2.0)])
+		.to_buffered_geometry();
+	assert_eq!(no_position.bounds_min, None);
+	assert_eq!(no_position.bounds_max, None);
+
+	let (merged, _) =
+		BufferedGeometry::merge(&[cube(Vec3::ZERO), cube(vec3(4.0, 0.0, -2.0))]).unwrap();
+	assert_eq!(merged.bounds_min, Some([0.0, 0.0, -2.0]));
+	assert_eq!(merged.bounds_max, Some([5.0, 1.0, 1.0]));
+}
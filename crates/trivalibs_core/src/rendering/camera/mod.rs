@@ -1,8 +1,8 @@
 use std::f32::consts::{FRAC_PI_2, TAU};
 
-use crate::utils::default;
+use crate::{geometry::Aabb, utils::default};
 
-use super::transform::Transform;
+use super::{objects::Frustum, transform::Transform};
 use glam::{vec3, Mat4, Quat, Vec2, Vec3, Vec3Swizzles, Vec4};
 use serde::Serialize;
 
@@ -197,6 +197,21 @@ impl PerspectiveCamera {
 		t
 	}
 
+	/// The direction the camera is looking at.
+	pub fn forward(&self) -> Vec3 {
+		self.transform().rotation * Vec3::NEG_Z
+	}
+
+	/// Moves the camera along its current view direction, so that it looks at the center of `bounds`
+	/// and the bounding sphere of `bounds` fits into the view.
+	/// `margin` is the fraction of the screen, that is kept free at each side.
+	pub fn zoom_to_fit(&mut self, bounds: &Aabb, margin: f32) {
+		let tan_half_fov = f32::tan(self.fov * 0.5) * self.aspect_ratio.min(1.0) * (1.0 - margin);
+		let half_fov = tan_half_fov.atan();
+		let distance = bounds.radius() / half_fov.sin();
+		self.translation = bounds.center() - self.forward() * distance;
+	}
+
 	pub fn projection_mat(&self) -> Mat4 {
 		self.proj
 	}
@@ -209,6 +224,11 @@ impl PerspectiveCamera {
 		self.projection_mat() * self.view_mat()
 	}
 
+	/// The planes of the view volume, e.g. to cull objects outside of the view.
+	pub fn frustum(&self) -> Frustum {
+		Frustum::from_view_proj(self.view_proj_mat())
+	}
+
 	pub fn reflected_cam(&self, _plane: Vec4) -> PerspectiveCamera {
 		todo!("reflect translation and rotations around plane")
 	}
@@ -259,3 +279,135 @@ impl PerspectiveCamera {
 
 	// TODO: Implement screen_to_world_ray and ndc_to_world_ray
 }
+
+/// Camera with an orthographic projection, e.g. for 2D overlays and shadow maps.
+/// The view volume is given in view space, the camera looks along its negative Z axis.
+/// Positioned and rotated like [`PerspectiveCamera`].
+#[derive(Debug, Clone, Serialize)]
+pub struct OrthographicCamera {
+	pub left: f32,
+	pub right: f32,
+	pub bottom: f32,
+	pub top: f32,
+	pub near: f32,
+	pub far: f32,
+
+	pub rot_horizontal: f32,
+	pub rot_vertical: f32,
+	pub translation: Vec3,
+}
+
+impl Default for OrthographicCamera {
+	fn default() -> Self {
+		OrthographicCamera {
+			left: -1.0,
+			right: 1.0,
+			bottom: -1.0,
+			top: 1.0,
+			near: 0.1,
+			far: 1000.0,
+			rot_horizontal: 0.0,
+			rot_vertical: 0.0,
+			translation: Vec3::ZERO,
+		}
+	}
+}
+
+impl OrthographicCamera {
+	pub fn transform(&self) -> Transform {
+		Transform::from_translation(self.translation).with_rotation(
+			Quat::from_rotation_y(self.rot_horizontal) * Quat::from_rotation_x(self.rot_vertical),
+		)
+	}
+
+	/// The direction the camera is looking at.
+	pub fn forward(&self) -> Vec3 {
+		self.transform().rotation * Vec3::NEG_Z
+	}
+
+	pub fn projection_mat(&self) -> Mat4 {
+		Mat4::orthographic_rh(
+			self.left,
+			self.right,
+			self.bottom,
+			self.top,
+			self.near,
+			self.far,
+		)
+	}
+
+	pub fn view_mat(&self) -> Mat4 {
+		self.transform().compute_matrix().inverse()
+	}
+
+	pub fn view_proj_mat(&self) -> Mat4 {
+		self.projection_mat() * self.view_mat()
+	}
+
+	/// The planes of the view volume, e.g. to cull objects outside of the view.
+	pub fn frustum(&self) -> Frustum {
+		Frustum::from_view_proj(self.view_proj_mat())
+	}
+}
+
+/// Orbits a camera around a target, e.g. driven by mouse drags and scroll wheel deltas.
+/// The controller is independent of the input events, pass it the deltas from the window or device events
+/// and apply it to the camera afterwards.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrbitController {
+	pub target: Vec3,
+	/// Angle around the Y axis, zero looks along the negative Z axis.
+	pub azimuth: f32,
+	/// Angle above the horizontal plane. Positive values look down onto the target.
+	pub elevation: f32,
+	pub distance: f32,
+	pub min_distance: f32,
+	pub max_distance: f32,
+	/// Rotation in radians per unit of drag delta, e.g. per pixel.
+	pub rotate_speed: f32,
+	/// Relative distance change per unit of zoom delta.
+	pub zoom_speed: f32,
+}
+
+impl Default for OrbitController {
+	fn default() -> Self {
+		OrbitController {
+			target: Vec3::ZERO,
+			azimuth: 0.0,
+			elevation: 0.0,
+			distance: 5.0,
+			min_distance: 0.1,
+			max_distance: 1000.0,
+			rotate_speed: 0.005,
+			zoom_speed: 0.1,
+		}
+	}
+}
+
+impl OrbitController {
+	/// Dragging to the right rotates the camera to the left around the target, dragging down lowers it.
+	/// Positive zoom values move the camera closer to the target.
+	pub fn update(&mut self, delta: Vec2, zoom: f32) {
+		self.azimuth = (self.azimuth - delta.x * self.rotate_speed).rem_euclid(TAU);
+		let max_elevation = FRAC_PI_2 - 0.001;
+		self.elevation =
+			(self.elevation + delta.y * self.rotate_speed).clamp(-max_elevation, max_elevation);
+		self.distance = (self.distance * (-zoom * self.zoom_speed).exp())
+			.clamp(self.min_distance, self.max_distance);
+	}
+
+	/// The camera position on the orbit.
+	pub fn position(&self) -> Vec3 {
+		let (sin_az, cos_az) = self.azimuth.sin_cos();
+		let (sin_el, cos_el) = self.elevation.sin_cos();
+		self.target + vec3(sin_az * cos_el, sin_el, cos_az * cos_el) * self.distance
+	}
+
+	/// Moves the camera onto the orbit, looking at the target.
+	pub fn apply(&self, cam: &mut PerspectiveCamera) {
+		cam.reset_transform(self.position(), self.azimuth, -self.elevation);
+	}
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,158 @@
+use super::{CamProps, OrbitController, OrthographicCamera, PerspectiveCamera};
+use crate::{geometry::Aabb, utils::default};
+use glam::{vec2, vec3, Vec2, Vec3};
+use std::f32::consts::FRAC_PI_2;
+
+fn corners(bounds: &Aabb) -> Vec<Vec3> {
+	(0..8)
+		.map(|i| {
+			vec3(
+				if i & 1 == 0 {
+					bounds.min.x
+				} else {
+					bounds.max.x
+				},
+				if i & 2 == 0 {
+					bounds.min.y
+				} else {
+					bounds.max.y
+				},
+				if i & 4 == 0 {
+					bounds.min.z
+				} else {
+					bounds.max.z
+				},
+			)
+		})
+		.collect()
+}
+
+#[test]
+fn zoom_to_fit() {
+	let cube = Aabb::new(Vec3::splat(-0.5), Vec3::splat(0.5));
+	let margin = 0.1;
+
+	for aspect_ratio in [1.5, 0.5] {
+		let mut cam = PerspectiveCamera::create(CamProps {
+			fov: Some(0.8),
+			aspect_ratio: Some(aspect_ratio),
+			rot_horizontal: Some(0.7),
+			rot_vertical: Some(-0.4),
+			translation: Some(vec3(3.0, 10.0, -4.0)),
+			..default()
+		});
+		let forward = cam.forward();
+
+		cam.zoom_to_fit(&cube, margin);
+
+		assert!(cam.forward().distance(forward) < 1e-6);
+
+		let center = cam.world_to_ndc(cube.center()).unwrap();
+		assert!(center.x.abs() < 1e-5 && center.y.abs() < 1e-5);
+
+		let mut max_extent: f32 = 0.0;
+		for corner in corners(&cube) {
+			let ndc = cam.world_to_ndc(corner).unwrap();
+			assert!(ndc.x.abs() <= 1.0 - margin);
+			assert!(ndc.y.abs() <= 1.0 - margin);
+			assert!(ndc.z > 0.0 && ndc.z < 1.0);
+			max_extent = max_extent.max(ndc.x.abs()).max(ndc.y.abs());
+		}
+		// the cube fills a good part of the view
+		assert!(max_extent > 0.5);
+	}
+}
+
+#[test]
+fn orthographic_clip_space() {
+	let cam = OrthographicCamera {
+		left: -4.0,
+		right: 4.0,
+		bottom: -2.0,
+		top: 2.0,
+		near: 1.0,
+		far: 50.0,
+		rot_horizontal: FRAC_PI_2,
+		translation: vec3(10.0, 0.0, 0.0),
+		..default()
+	};
+	let view_proj = cam.view_proj_mat();
+	let forward = cam.forward();
+	assert!(forward.abs_diff_eq(Vec3::NEG_X, 1e-6));
+
+	let far = view_proj.project_point3(cam.translation + forward * 50.0);
+	assert!(far.abs_diff_eq(vec3(0.0, 0.0, 1.0), 1e-5));
+
+	let near = view_proj.project_point3(cam.translation + forward * 1.0);
+	assert!(near.abs_diff_eq(Vec3::ZERO, 1e-5));
+
+	// The size of the view volume doesn't depend on the distance.
+	let corner = view_proj.project_point3(cam.translation + forward * 25.0 + vec3(0.0, 2.0, -4.0));
+	assert!((corner.x.abs() - 1.0).abs() < 1e-5);
+	assert!((corner.y - 1.0).abs() < 1e-5);
+	assert!((0.0..=1.0).contains(&corner.z));
+}
+
+#[test]
+fn frustum_culling() {
+	let cam = PerspectiveCamera::create(CamProps {
+		fov: Some(0.8),
+		aspect_ratio: Some(1.5),
+		near: Some(0.1),
+		far: Some(100.0),
+		rot_horizontal: Some(0.3),
+		translation: Some(vec3(2.0, 1.0, 5.0)),
+		..default()
+	});
+	let frustum = cam.frustum();
+	let forward = cam.forward();
+
+	let in_front = cam.translation + forward * 10.0;
+	assert!(frustum.intersects_aabb(in_front - 1.0, in_front + 1.0));
+
+	let behind = cam.translation - forward * 10.0;
+	assert!(!frustum.intersects_aabb(behind - 1.0, behind + 1.0));
+
+	let beyond_far = cam.translation + forward * 150.0;
+	assert!(!frustum.intersects_aabb(beyond_far - 1.0, beyond_far + 1.0));
+
+	// Partially inside, around the camera
+	assert!(frustum.intersects_aabb(cam.translation - 1.0, cam.translation + 1.0));
+
+	let ortho = OrthographicCamera {
+		translation: vec3(0.0, 0.0, 10.0),
+		..default()
+	};
+	let frustum = ortho.frustum();
+	assert!(frustum.intersects_aabb(vec3(0.5, 0.5, -0.5), vec3(2.0, 2.0, 0.5)));
+	assert!(!frustum.intersects_aabb(vec3(1.5, 0.0, 0.0), vec3(2.0, 1.0, 1.0)));
+}
+
+#[test]
+fn orbit_controller() {
+	let mut orbit = OrbitController {
+		target: vec3(1.0, 2.0, 3.0),
+		azimuth: 0.5,
+		elevation: 0.3,
+		distance: 4.0,
+		rotate_speed: 0.01,
+		..default()
+	};
+
+	orbit.update(vec2(-20.0, 0.0), 0.0);
+	assert!((orbit.azimuth - 0.7).abs() < 1e-6);
+	assert_eq!((orbit.elevation, orbit.distance), (0.3, 4.0));
+
+	let mut cam = PerspectiveCamera::default();
+	orbit.apply(&mut cam);
+	assert!((cam.translation.distance(orbit.target) - 4.0).abs() < 1e-5);
+	let to_target = (orbit.target - cam.translation).normalize();
+	assert!(cam.forward().abs_diff_eq(to_target, 1e-5));
+	assert!(cam.translation.y > orbit.target.y);
+
+	orbit.update(vec2(0.0, 1000.0), 0.0);
+	assert!(orbit.elevation < FRAC_PI_2);
+
+	orbit.update(Vec2::ZERO, 1.0);
+	assert!(orbit.distance < 4.0);
+}
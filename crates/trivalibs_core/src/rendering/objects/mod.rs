@@ -1,4 +1,4 @@
-use glam::Vec3;
+use glam::{Mat4, Vec3, Vec4};
 
 use super::transform::Transform;
 
@@ -18,6 +18,48 @@ pub struct Plane {
 	pub distance: f32,
 }
 
+impl Plane {
+	/// Plane from the coefficients of `normal.dot(p) + distance = 0`, normalized to a unit normal.
+	pub fn from_coefficients(coefficients: Vec4) -> Self {
+		let length = coefficients.truncate().length();
+		Plane {
+			normal: coefficients.truncate() / length,
+			distance: coefficients.w / length,
+		}
+	}
+
+	/// Distance of the point to the plane, positive on the side the normal points to.
+	pub fn signed_distance(&self, point: Vec3) -> f32 {
+		self.normal.dot(point) + self.distance
+	}
+}
+
+/// The six planes of a camera view volume, with normals pointing inwards.
+pub struct Frustum {
+	/// Left, right, bottom, top, near and far plane.
+	pub planes: [Plane; 6],
+}
+
+impl Frustum {
+	/// Extracts the planes from a view projection matrix with a clip space depth range of 0 to 1.
+	pub fn from_view_proj(view_proj: Mat4) -> Self {
+		let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+		Frustum {
+			planes: [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(Plane::from_coefficients),
+		}
+	}
+
+	/// Whether the axis aligned box is at least partially inside the frustum.
+	/// Boxes close to the frustum corners can be reported as intersecting, although they are outside.
+	pub fn intersects_aabb(&self, min: Vec3, max: Vec3) -> bool {
+		self.planes.iter().all(|plane| {
+			// The corner furthest along the plane normal
+			let corner = Vec3::select(plane.normal.cmpge(Vec3::ZERO), max, min);
+			plane.signed_distance(corner) >= 0.0
+		})
+	}
+}
+
 pub struct Sphere {
 	pub center: Vec3,
 	pub radius: f32,
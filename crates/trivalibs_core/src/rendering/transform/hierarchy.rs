@@ -0,0 +1,114 @@
+use super::Transform;
+use glam::Mat4;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HierarchyError {
+	/// The parents of the contained node form a cycle.
+	Cycle(usize),
+	/// The parent of a node is not part of the hierarchy.
+	MissingParent { node: usize, parent: usize },
+}
+
+impl std::fmt::Display for HierarchyError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			HierarchyError::Cycle(node) => {
+				write!(f, "transform hierarchy has a parent cycle at node {}", node)
+			}
+			HierarchyError::MissingParent { node, parent } => {
+				write!(f, "parent {} of node {} does not exist", parent, node)
+			}
+		}
+	}
+}
+
+impl std::error::Error for HierarchyError {}
+
+/// Local transforms with optional parents, e.g. the parts of a rigged model.
+/// Nodes are referenced by the index returned from [`TransformHierarchy::add`].
+#[derive(Debug, Clone, Default)]
+pub struct TransformHierarchy {
+	pub transforms: Vec<Transform>,
+	parents: Vec<Option<usize>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VisitState {
+	New,
+	Visiting,
+	Done,
+}
+
+impl TransformHierarchy {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a node with a transform relative to its parent, and returns its index.
+	pub fn add(&mut self, transform: Transform, parent: Option<usize>) -> usize {
+		self.transforms.push(transform);
+		self.parents.push(parent);
+		self.transforms.len() - 1
+	}
+
+	pub fn parent(&self, node: usize) -> Option<usize> {
+		self.parents[node]
+	}
+
+	/// Reparents a node. Cycles are only detected when computing the world transforms.
+	pub fn set_parent(&mut self, node: usize, parent: Option<usize>) {
+		self.parents[node] = parent;
+	}
+
+	/// World transform of every node, composed with [`Transform::mul_transform`]
+	/// from the root down, so parents are always resolved before their children.
+	pub fn world_transforms(&self) -> Result<Vec<Transform>, HierarchyError> {
+		let mut states = vec![VisitState::New; self.transforms.len()];
+		let mut world = vec![Transform::IDENTITY; self.transforms.len()];
+
+		for node in 0..self.transforms.len() {
+			self.visit(node, &mut states, &mut world)?;
+		}
+
+		Ok(world)
+	}
+
+	/// World matrices of every node, see [`TransformHierarchy::world_transforms`].
+	pub fn world_matrices(&self) -> Result<Vec<Mat4>, HierarchyError> {
+		Ok(self
+			.world_transforms()?
+			.iter()
+			.map(|t| t.compute_matrix())
+			.collect())
+	}
+
+	fn visit(
+		&self,
+		node: usize,
+		states: &mut [VisitState],
+		world: &mut [Transform],
+	) -> Result<(), HierarchyError> {
+		match states[node] {
+			VisitState::Done => return Ok(()),
+			VisitState::Visiting => return Err(HierarchyError::Cycle(node)),
+			VisitState::New => {}
+		}
+
+		states[node] = VisitState::Visiting;
+
+		world[node] = match self.parents[node] {
+			Some(parent) if parent >= self.transforms.len() => {
+				return Err(HierarchyError::MissingParent { node, parent });
+			}
+			Some(parent) => {
+				self.visit(parent, states, world)?;
+				world[parent].mul_transform(self.transforms[node])
+			}
+			None => self.transforms[node],
+		};
+
+		states[node] = VisitState::Done;
+
+		Ok(())
+	}
+}
@@ -1,4 +1,5 @@
-use glam::{Affine3A, Mat3, Mat4, Quat, Vec3};
+use glam::{Affine3A, DMat4, DQuat, DVec3, Mat3, Mat4, Quat, Vec3, Vec3A};
+use lerp::Lerp;
 use serde::Serialize;
 use std::ops::Mul;
 
@@ -78,7 +79,7 @@ impl Transform {
 	/// points towards the `target` position and [`Transform::up`] points towards `up`.
 	///
 	/// In some cases it's not possible to construct a rotation. Another axis will be picked in those cases:
-	/// * if `target` is the same as the transform translation, `Vec3::Z` is used instead
+	/// * if `target` is the same as the transform translation, `Vec3::NEG_Z` is used as forward direction
 	/// * if `up` is zero, `Vec3::Y` is used instead
 	/// * if the resulting forward direction is parallel with `up`, an orthogonal vector is used as the "right" direction
 	#[inline]
@@ -127,12 +128,25 @@ impl Transform {
 	}
 
 	/// Returns the 3d affine transformation matrix from this transforms translation,
-	/// rotation, and scale.
+	/// rotation, and scale. This is the model matrix of objects, mapping local to world space.
 	#[inline]
 	pub fn compute_matrix(&self) -> Mat4 {
 		Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
 	}
 
+	/// Inverse of [`Transform::compute_matrix`], e.g. the view matrix of a camera transform,
+	/// mapping world to camera space. Composed directly from the inverted scale, rotation and translation,
+	/// which is exact also for non-uniform scales. Falls back to inverting the matrix if a scale component is zero.
+	#[inline]
+	pub fn view_matrix(&self) -> Mat4 {
+		if self.scale.cmpeq(Vec3::ZERO).any() {
+			return self.compute_matrix().inverse();
+		}
+		Mat4::from_scale(self.scale.recip())
+			* Mat4::from_quat(self.rotation.inverse())
+			* Mat4::from_translation(-self.translation)
+	}
+
 	/// Returns the 3d affine transformation matrix from this transforms translation,
 	/// rotation, and scale.
 	#[inline]
@@ -273,7 +287,7 @@ impl Transform {
 	/// and [`Transform::up`] points towards `up`.
 	///
 	/// In some cases it's not possible to construct a rotation. Another axis will be picked in those cases:
-	/// * if `target` is the same as the transtorm translation, `Vec3::Z` is used instead
+	/// * if `target` is the same as the transform translation, `Vec3::NEG_Z` is used as forward direction
 	/// * if `up` is zero, `Vec3::Y` is used instead
 	/// * if the resulting forward direction is parallel with `up`, an orthogonal vector is used as the "right" direction
 	#[inline]
@@ -323,6 +337,90 @@ impl Transform {
 		point += self.translation;
 		point
 	}
+
+	/// Inverse transform, so that `t * t.inverse()` is the identity.
+	/// Exact for uniform scales. Non-uniform scales combined with a rotation would need a shear
+	/// to be inverted in both directions, which a [`Transform`] can't represent.
+	/// Falls back to decomposing the inverted matrix if a scale component is zero.
+	#[must_use]
+	pub fn inverse(&self) -> Self {
+		if self.scale.cmpeq(Vec3::ZERO).any() {
+			return Self::from_matrix(self.compute_matrix().inverse());
+		}
+
+		let rotation = self.rotation.inverse();
+		let scale = self.scale.recip();
+		Transform {
+			translation: scale * (rotation * -self.translation),
+			rotation,
+			scale,
+		}
+	}
+
+	/// Transforms all `points` into `out`, like [`Transform::transform_point`] for each of them.
+	/// Composes the transform into a SIMD affine matrix once, which is faster for large point clouds.
+	///
+	/// Panics if `points` and `out` have different lengths.
+	pub fn transform_points(&self, points: &[Vec3], out: &mut [Vec3]) {
+		assert_eq!(
+			points.len(),
+			out.len(),
+			"transform_points needs an output slice of the same length"
+		);
+		let affine = self.compute_affine();
+		for (point, out) in points.iter().zip(out.iter_mut()) {
+			*out = affine.transform_point3a(Vec3A::from(*point)).into();
+		}
+	}
+
+	/// Interpolates linearly between the translations and scales, and spherically between the rotations.
+	pub fn lerp(&self, other: &Transform, t: f32) -> Self {
+		Transform {
+			translation: self.translation.lerp(other.translation, t),
+			rotation: self.rotation.slerp(other.rotation, t),
+			scale: self.scale.lerp(other.scale, t),
+		}
+	}
+
+	/// Weighted average of transforms, e.g. to blend animation channels.
+	/// The weights are normalized to sum up to 1. Translations and scales are averaged linearly,
+	/// rotations by a normalized weighted sum of the quaternions, aligned to the hemisphere of the first rotation.
+	/// Returns the identity if the weights sum up to zero.
+	///
+	/// Panics if a weight is negative or NaN.
+	pub fn blend(transforms: &[(Transform, f32)]) -> Self {
+		assert!(
+			transforms.iter().all(|(_, w)| *w >= 0.0),
+			"blend weights must be non-negative"
+		);
+		let total: f32 = transforms.iter().map(|(_, w)| w).sum();
+		if total == 0.0 {
+			return Self::IDENTITY;
+		}
+
+		let first = transforms[0].0.rotation;
+		let mut translation = Vec3::ZERO;
+		let mut scale = Vec3::ZERO;
+		let mut rotation = Quat::from_xyzw(0.0, 0.0, 0.0, 0.0);
+
+		for (transform, weight) in transforms {
+			let weight = weight / total;
+			translation += transform.translation * weight;
+			scale += transform.scale * weight;
+			let aligned = if transform.rotation.dot(first) < 0.0 {
+				-transform.rotation
+			} else {
+				transform.rotation
+			};
+			rotation = rotation + aligned * weight;
+		}
+
+		Transform {
+			translation,
+			rotation: rotation.normalize(),
+			scale,
+		}
+	}
 }
 
 impl Default for Transform {
@@ -331,6 +429,12 @@ impl Default for Transform {
 	}
 }
 
+impl Lerp<f32> for Transform {
+	fn lerp(self, other: Self, t: f32) -> Self {
+		Transform::lerp(&self, &other, t)
+	}
+}
+
 impl Mul<Transform> for Transform {
 	type Output = Transform;
 
@@ -346,3 +450,95 @@ impl Mul<Vec3> for Transform {
 		self.transform_point(value)
 	}
 }
+
+/// Double precision variant of [`Transform`] for large world coordinates.
+/// For rendering, convert it with [`TransformF64::relative_to`] into a single precision
+/// [`Transform`] relative to a camera centered origin.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub struct TransformF64 {
+	pub translation: DVec3,
+	pub rotation: DQuat,
+	pub scale: DVec3,
+}
+
+impl TransformF64 {
+	/// An identity [`TransformF64`] with no translation, rotation, and a scale of 1 on all axes.
+	pub const IDENTITY: Self = TransformF64 {
+		translation: DVec3::ZERO,
+		rotation: DQuat::IDENTITY,
+		scale: DVec3::ONE,
+	};
+
+	#[inline]
+	pub const fn from_translation(translation: DVec3) -> Self {
+		TransformF64 {
+			translation,
+			..Self::IDENTITY
+		}
+	}
+
+	#[inline]
+	pub const fn from_rotation(rotation: DQuat) -> Self {
+		TransformF64 {
+			rotation,
+			..Self::IDENTITY
+		}
+	}
+
+	#[inline]
+	pub const fn from_scale(scale: DVec3) -> Self {
+		TransformF64 {
+			scale,
+			..Self::IDENTITY
+		}
+	}
+
+	/// Returns the double precision 3d affine transformation matrix from this transforms
+	/// translation, rotation, and scale.
+	#[inline]
+	pub fn compute_matrix(&self) -> DMat4 {
+		DMat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+	}
+
+	/// Transforms the given `point`, applying scale, rotation and translation.
+	#[inline]
+	pub fn transform_point(&self, mut point: DVec3) -> DVec3 {
+		point = self.scale * point;
+		point = self.rotation * point;
+		point += self.translation;
+		point
+	}
+
+	/// Returns a single precision [`Transform`] with the translation relative to `origin`.
+	/// Subtracting in double precision keeps full accuracy close to the origin, e.g. the camera position.
+	#[inline]
+	pub fn relative_to(&self, origin: DVec3) -> Transform {
+		Transform {
+			translation: (self.translation - origin).as_vec3(),
+			rotation: self.rotation.as_quat(),
+			scale: self.scale.as_vec3(),
+		}
+	}
+}
+
+impl Default for TransformF64 {
+	fn default() -> Self {
+		Self::IDENTITY
+	}
+}
+
+impl From<Transform> for TransformF64 {
+	fn from(t: Transform) -> Self {
+		TransformF64 {
+			translation: t.translation.as_dvec3(),
+			rotation: t.rotation.as_dquat(),
+			scale: t.scale.as_dvec3(),
+		}
+	}
+}
+
+mod hierarchy;
+pub use hierarchy::{HierarchyError, TransformHierarchy};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,219 @@
+use super::{HierarchyError, Transform, TransformF64, TransformHierarchy};
+use glam::{dvec3, vec3, DQuat, Quat, Vec3};
+use std::f32::consts::FRAC_PI_2;
+
+#[test]
+fn f64_precision_far_from_origin() {
+	let origin = dvec3(1e7, 2e7, -1e7);
+	let transform = TransformF64::from_translation(origin + dvec3(0.5, 0.25, 0.0));
+	let point = vec3(0.0003, 0.0, 0.0002);
+
+	let relative = transform.relative_to(origin).transform_point(point);
+	let expected = vec3(0.5003, 0.25, 0.0002);
+	assert!((relative - expected).abs().max_element() < 0.0001);
+
+	let world = transform.transform_point(point.as_dvec3());
+	let expected = origin + expected.as_dvec3();
+	assert!((world - expected).abs().max_element() < 0.0001);
+
+	let transform = Transform::from_translation((origin + dvec3(0.5, 0.25, 0.0)).as_vec3());
+	let world = transform.transform_point(point).as_dvec3();
+	assert!((world - expected).abs().max_element() > 0.001);
+}
+
+#[test]
+fn f64_matrix() {
+	let transform = TransformF64 {
+		translation: dvec3(1e8, 3.0, -2.0),
+		rotation: DQuat::from_rotation_y(0.5),
+		scale: dvec3(2.0, 2.0, 2.0),
+	};
+	let point = dvec3(1.0, 2.0, 3.0);
+
+	let from_mat = transform.compute_matrix().transform_point3(point);
+	let from_transform = transform.transform_point(point);
+	assert!((from_mat - from_transform).abs().max_element() < 1e-6);
+
+	let single = Transform::from_xyz(1.0, 2.0, 3.0);
+	assert_eq!(
+		TransformF64::from(single).relative_to(dvec3(1.0, 2.0, 3.0)),
+		Transform::IDENTITY
+	);
+}
+
+#[test]
+fn blend() {
+	let a = Transform::from_xyz(1.0, 2.0, 3.0)
+		.with_rotation(Quat::from_rotation_y(0.4))
+		.with_scale(vec3(1.0, 2.0, 1.0));
+	let b = Transform::from_xyz(-3.0, 0.0, 1.0)
+		.with_rotation(-Quat::from_rotation_x(1.2))
+		.with_scale(vec3(3.0, 2.0, 0.5));
+
+	let half = Transform::blend(&[(a, 0.5), (b, 0.5)]);
+	assert!(half
+		.translation
+		.abs_diff_eq(a.translation.lerp(b.translation, 0.5), 1e-6));
+	assert!(half.scale.abs_diff_eq(a.scale.lerp(b.scale, 0.5), 1e-6));
+	assert!(half
+		.rotation
+		.abs_diff_eq(a.rotation.slerp(b.rotation, 0.5), 1e-5));
+
+	let unnormalized = Transform::blend(&[(a, 3.0), (b, 3.0)]);
+	assert_eq!(unnormalized, half);
+
+	let weighted = Transform::blend(&[(a, 1.0), (b, 3.0)]);
+	assert!(weighted
+		.translation
+		.abs_diff_eq(a.translation.lerp(b.translation, 0.75), 1e-6));
+
+	assert_eq!(Transform::blend(&[(a, 2.0)]), a);
+	assert_eq!(Transform::blend(&[]), Transform::IDENTITY);
+	assert_eq!(Transform::blend(&[(a, 0.0), (b, 0.0)]), Transform::IDENTITY);
+}
+
+#[test]
+#[should_panic(expected = "blend weights must be non-negative")]
+fn blend_negative_weight() {
+	let a = Transform::from_xyz(1.0, 2.0, 3.0);
+	let b = Transform::from_xyz(-3.0, 0.0, 1.0);
+	Transform::blend(&[(a, 2.0), (b, -1.0)]);
+}
+
+#[test]
+fn lerp() {
+	let a = Transform::from_xyz(1.0, 2.0, 3.0).with_scale(vec3(1.0, 2.0, 4.0));
+	let b = Transform::from_xyz(3.0, -2.0, 5.0)
+		.with_rotation(Quat::from_rotation_y(1.0))
+		.with_scale(vec3(3.0, 2.0, 2.0));
+
+	let half = a.lerp(&b, 0.5);
+	assert_eq!(half.translation, vec3(2.0, 0.0, 4.0));
+	assert_eq!(half.scale, vec3(2.0, 2.0, 3.0));
+	assert!(half.rotation.is_normalized());
+	assert!(half.rotation.abs_diff_eq(Quat::from_rotation_y(0.5), 1e-6));
+
+	assert_eq!(a.lerp(&b, 0.0), a);
+	assert!(a.lerp(&b, 1.0).rotation.abs_diff_eq(b.rotation, 1e-6));
+}
+
+#[test]
+fn inverse() {
+	let t = Transform::from_xyz(1.0, -2.0, 3.0)
+		.with_rotation(Quat::from_euler(glam::EulerRot::YXZ, 0.3, -1.2, 0.7))
+		.with_scale(vec3(2.0, 0.5, 3.0));
+	let point = vec3(0.4, 5.0, -1.5);
+
+	let inverse = t.inverse();
+	assert!((t * inverse * point).abs_diff_eq(point, 1e-5));
+
+	let uniform = t.with_scale(Vec3::splat(2.5));
+	let inverse = uniform.inverse();
+	assert!((uniform * inverse * point).abs_diff_eq(point, 1e-5));
+	assert!((inverse * (uniform * point)).abs_diff_eq(point, 1e-5));
+	assert!(inverse
+		.compute_matrix()
+		.abs_diff_eq(uniform.compute_matrix().inverse(), 1e-5));
+}
+
+#[test]
+fn looking_at_faces_target() {
+	let t = Transform::IDENTITY.looking_at(vec3(0.0, 0.0, -1.0), Vec3::Y);
+	assert!(t.forward().abs_diff_eq(vec3(0.0, 0.0, -1.0), 1e-6));
+	assert!(t.up().abs_diff_eq(Vec3::Y, 1e-6));
+
+	let t = Transform::from_xyz(1.0, 2.0, 3.0).looking_at(vec3(4.0, -2.0, 3.0), Vec3::Y);
+	assert!(t.forward().abs_diff_eq(vec3(0.6, -0.8, 0.0), 1e-6));
+	assert!(t.right().cross(t.up()).abs_diff_eq(t.back(), 1e-6));
+	assert!(t
+		.transform_point(vec3(0.0, 0.0, -5.0))
+		.abs_diff_eq(vec3(4.0, -2.0, 3.0), 1e-5));
+}
+
+#[test]
+fn hierarchy_world_transforms() {
+	let mut hierarchy = TransformHierarchy::new();
+	let root = hierarchy.add(
+		Transform::from_xyz(10.0, 0.0, 0.0).with_rotation(Quat::from_rotation_y(FRAC_PI_2)),
+		None,
+	);
+	let arm = hierarchy.add(
+		Transform::from_xyz(0.0, 0.0, -2.0).with_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+		Some(root),
+	);
+	let hand = hierarchy.add(Transform::from_xyz(1.0, 0.0, 0.0), Some(arm));
+
+	let world = hierarchy.world_transforms().unwrap();
+	assert_eq!(world[root], hierarchy.transforms[root]);
+	// arm: (0, 0, -2) rotated around y by 90° is (-2, 0, 0)
+	assert!(world[arm]
+		.translation
+		.abs_diff_eq(vec3(8.0, 0.0, 0.0), 1e-5));
+	// hand: (1, 0, 0) rotated around z is (0, 1, 0), around y stays (0, 1, 0)
+	assert!(world[hand]
+		.translation
+		.abs_diff_eq(vec3(8.0, 1.0, 0.0), 1e-5));
+
+	let matrices = hierarchy.world_matrices().unwrap();
+	assert!(matrices[hand]
+		.transform_point3(Vec3::ZERO)
+		.abs_diff_eq(vec3(8.0, 1.0, 0.0), 1e-5));
+
+	hierarchy.set_parent(root, Some(hand));
+	assert!(matches!(
+		hierarchy.world_transforms(),
+		Err(HierarchyError::Cycle(_))
+	));
+
+	hierarchy.set_parent(root, Some(7));
+	assert_eq!(
+		hierarchy.world_transforms(),
+		Err(HierarchyError::MissingParent {
+			node: root,
+			parent: 7
+		})
+	);
+}
+
+#[test]
+fn view_matrix() {
+	let camera = Transform::from_xyz(0.0, 0.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y);
+	let view = camera.view_matrix();
+	assert!(view
+		.transform_point3(Vec3::ZERO)
+		.abs_diff_eq(vec3(0.0, 0.0, -5.0), 1e-6));
+	assert!(view
+		.transform_point3(vec3(1.0, 0.0, 0.0))
+		.abs_diff_eq(vec3(1.0, 0.0, -5.0), 1e-6));
+
+	let t = Transform::from_xyz(1.0, -2.0, 3.0)
+		.with_rotation(Quat::from_rotation_x(0.7))
+		.with_scale(vec3(2.0, 0.5, 3.0));
+	let model_view = t.view_matrix() * t.compute_matrix();
+	assert!(model_view.abs_diff_eq(glam::Mat4::IDENTITY, 1e-5));
+}
+
+#[test]
+fn transform_points() {
+	let t = Transform::from_xyz(1.0, -2.0, 3.0)
+		.with_rotation(Quat::from_rotation_y(0.8) * Quat::from_rotation_x(-0.3))
+		.with_scale(vec3(2.0, 0.5, 1.5));
+	let points = (0..100)
+		.map(|i| {
+			let i = i as f32;
+			vec3(i.sin() * 10.0, i * 0.5 - 20.0, (i * 0.3).cos() * 5.0)
+		})
+		.collect::<Vec<_>>();
+
+	let mut out = vec![Vec3::ZERO; points.len()];
+	t.transform_points(&points, &mut out);
+	for (point, out) in points.iter().zip(out) {
+		assert!(out.abs_diff_eq(t * *point, 1e-4));
+	}
+}
+
+#[test]
+#[should_panic]
+fn transform_points_length_mismatch() {
+	Transform::IDENTITY.transform_points(&[Vec3::ZERO; 3], &mut [Vec3::ZERO; 2]);
+}
@@ -1,4 +1,5 @@
 use glam::{vec2, vec3, Vec2, Vec3};
+use std::f32::consts::{PI, TAU};
 
 pub fn fit1101(x: f32) -> f32 {
 	x * 0.5 + 0.5
@@ -42,3 +43,74 @@ impl Fit for Vec3 {
 		vec3(self.x.fit1101(), self.y.fit1101(), self.z.fit1101())
 	}
 }
+
+pub fn deg_to_rad(deg: f32) -> f32 {
+	deg * (PI / 180.0)
+}
+
+pub fn rad_to_deg(rad: f32) -> f32 {
+	rad * (180.0 / PI)
+}
+
+/// Wraps an angle in radians into `[-PI, PI)`, also for large multiples of `TAU`.
+pub fn wrap_angle(angle: f32) -> f32 {
+	let wrapped = (angle + PI).rem_euclid(TAU) - PI;
+	// keeps the upper bound exclusive, should the remainder round up to TAU
+	if wrapped >= PI {
+		-PI
+	} else {
+		wrapped
+	}
+}
+
+/// Unsigned angle in radians between two vectors, in `[0, PI]`.
+/// Returns 0 if one of the vectors is zero.
+pub fn angle_between(a: Vec2, b: Vec2) -> f32 {
+	signed_angle_between(a, b).abs()
+}
+
+/// Angle in radians to rotate `a` onto the direction of `b`, in `[-PI, PI]`.
+/// Positive angles rotate counterclockwise, from the x axis towards the y axis.
+pub fn signed_angle_between(a: Vec2, b: Vec2) -> f32 {
+	a.perp_dot(b).atan2(a.dot(b))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn angle_conversions() {
+		assert!((deg_to_rad(180.0) - PI).abs() < 1e-6);
+		assert!((rad_to_deg(PI / 2.0) - 90.0).abs() < 1e-5);
+		assert!((rad_to_deg(deg_to_rad(37.0)) - 37.0).abs() < 1e-5);
+	}
+
+	#[test]
+	fn wrap_angles() {
+		assert!((wrap_angle(3.0 * PI).abs() - PI).abs() < 1e-5);
+		assert!((wrap_angle(-3.0 * PI).abs() - PI).abs() < 1e-5);
+		assert!((wrap_angle(0.5) - 0.5).abs() < 1e-6);
+		assert!((wrap_angle(0.5 - TAU) - 0.5).abs() < 1e-5);
+		assert!((wrap_angle(0.5 + 1000.0 * TAU) - 0.5).abs() < 1e-2);
+		for i in -20..20 {
+			let wrapped = wrap_angle(i as f32 * 1.7);
+			assert!((-PI..PI).contains(&wrapped));
+		}
+		assert_eq!(wrap_angle(PI), -PI);
+		assert_eq!(wrap_angle(-PI), -PI);
+		assert_eq!(wrap_angle(3.0 * PI), -PI);
+	}
+
+	#[test]
+	fn angles_between_vectors() {
+		assert!((angle_between(Vec2::X, Vec2::Y) - PI / 2.0).abs() < 1e-6);
+		assert!((angle_between(Vec2::Y, Vec2::X) - PI / 2.0).abs() < 1e-6);
+		assert!((angle_between(Vec2::X, vec2(-2.0, 0.0)) - PI).abs() < 1e-6);
+		assert_eq!(angle_between(Vec2::ZERO, Vec2::X), 0.0);
+
+		assert!((signed_angle_between(Vec2::X, Vec2::Y) - PI / 2.0).abs() < 1e-6);
+		assert!((signed_angle_between(Vec2::Y, Vec2::X) + PI / 2.0).abs() < 1e-6);
+		assert!((signed_angle_between(vec2(1.0, 1.0), vec2(1.0, -1.0)) + PI / 2.0).abs() < 1e-6);
+	}
+}
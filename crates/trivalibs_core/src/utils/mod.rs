@@ -1,7 +1,10 @@
 pub mod app_state;
 pub mod math;
 pub mod rand_utils;
+pub mod smoothing;
 
+/// Shorthand for [`Default::default`], mostly used to fill up props structs,
+/// e.g. `LayerProps { multisampled: true, ..default() }`.
 pub fn default<T: Default>() -> T {
 	std::default::Default::default()
 }
@@ -1,12 +1,81 @@
-use glam::{vec3, vec4, Vec3, Vec4};
-use rand::random;
+use glam::{vec2, vec3, vec4, Vec2, Vec3, Vec4};
+use rand::{random, rngs::StdRng, seq::SliceRandom, thread_rng, Rng, RngCore, SeedableRng};
+
+fn range_with<R: Rng + ?Sized>(rng: &mut R, min: f32, max: f32) -> f32 {
+	min + (max - min) * rng.gen::<f32>()
+}
+
+fn usize_with<R: Rng + ?Sized>(rng: &mut R, max: usize) -> usize {
+	(rng.gen::<f32>() * max as f32).floor() as usize
+}
+
+fn sign_with<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+	if rng.gen::<f32>() < 0.5 {
+		1.0
+	} else {
+		-1.0
+	}
+}
+
+fn vec3_with<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+	vec3(rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>())
+}
+
+fn vec3_range_with<R: Rng + ?Sized>(rng: &mut R, min: f32, max: f32) -> Vec3 {
+	vec3(
+		range_with(rng, min, max),
+		range_with(rng, min, max),
+		range_with(rng, min, max),
+	)
+}
+
+fn in_unit_sphere_with<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+	loop {
+		let p = vec3_range_with(rng, -1.0, 1.0);
+
+		let ls = p.length_squared();
+		if ls < 1.0 && ls > 0.0000001 {
+			return p;
+		}
+	}
+}
+
+fn vec4_with<R: Rng + ?Sized>(rng: &mut R) -> Vec4 {
+	vec4(
+		rng.gen::<f32>(),
+		rng.gen::<f32>(),
+		rng.gen::<f32>(),
+		rng.gen::<f32>(),
+	)
+}
+
+fn vec4_range_with<R: Rng + ?Sized>(rng: &mut R, min: f32, max: f32) -> Vec4 {
+	vec4(
+		range_with(rng, min, max),
+		range_with(rng, min, max),
+		range_with(rng, min, max),
+		range_with(rng, min, max),
+	)
+}
+
+fn normal_with<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+	(rng.gen::<f32>() + rng.gen::<f32>() + rng.gen::<f32>()) / 1.5 - 1.0
+}
+
+fn normal_01_with<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+	(rng.gen::<f32>() + rng.gen::<f32>() + rng.gen::<f32>()) / 3.
+}
+
+fn pick_with<'a, T, R: Rng + ?Sized>(rng: &mut R, items: &'a [T]) -> &'a T {
+	&items[(rng.gen::<f64>() * items.len() as f64).floor() as usize]
+}
 
 pub fn rand_range(min: f32, max: f32) -> f32 {
-	min + (max - min) * random::<f32>()
+	range_with(&mut thread_rng(), min, max)
 }
 
 pub fn rand_usize(max: usize) -> usize {
-	(random::<f32>() * max as f32).floor() as usize
+	usize_with(&mut thread_rng(), max)
 }
 
 pub fn rand_f32() -> f32 {
@@ -22,23 +91,15 @@ pub fn rand_bool() -> bool {
 }
 
 pub fn rand_sign() -> f32 {
-	if rand_f32() < 0.5 {
-		1.0
-	} else {
-		-1.0
-	}
+	sign_with(&mut thread_rng())
 }
 
 pub fn rand_vec3() -> Vec3 {
-	vec3(random::<f32>(), random::<f32>(), random::<f32>())
+	vec3_with(&mut thread_rng())
 }
 
 pub fn rand_vec3_range(min: f32, max: f32) -> Vec3 {
-	vec3(
-		rand_range(min, max),
-		rand_range(min, max),
-		rand_range(min, max),
-	)
+	vec3_range_with(&mut thread_rng(), min, max)
 }
 
 pub fn rand_vec3_unit() -> Vec3 {
@@ -46,46 +107,25 @@ pub fn rand_vec3_unit() -> Vec3 {
 }
 
 pub fn rand_in_unit_sphere() -> Vec3 {
-	loop {
-		let p = vec3(
-			rand_range(-1.0, 1.0),
-			rand_range(-1.0, 1.0),
-			rand_range(-1.0, 1.0),
-		);
-
-		let ls = p.length_squared();
-		if ls < 1.0 && ls > 0.0000001 {
-			return p;
-		}
-	}
+	in_unit_sphere_with(&mut thread_rng())
 }
 
 pub fn rand_vec4() -> Vec4 {
-	vec4(
-		random::<f32>(),
-		random::<f32>(),
-		random::<f32>(),
-		random::<f32>(),
-	)
+	vec4_with(&mut thread_rng())
 }
 
 pub fn rand_vec4_range(min: f32, max: f32) -> Vec4 {
-	vec4(
-		rand_range(min, max),
-		rand_range(min, max),
-		rand_range(min, max),
-		rand_range(min, max),
-	)
+	vec4_range_with(&mut thread_rng(), min, max)
 }
 
 /// Returns a random number in the range [-1, 1] with normal distribution.
 pub fn rand_normal() -> f32 {
-	(random::<f32>() + random::<f32>() + random::<f32>()) / 1.5 - 1.0
+	normal_with(&mut thread_rng())
 }
 
 /// Returns a random number in the range [0, 1] with normal distribution arround 0.5.
 pub fn rand_normal_01() -> f32 {
-	(random::<f32>() + random::<f32>() + random::<f32>()) / 3.
+	normal_01_with(&mut thread_rng())
 }
 
 pub trait Pick<T> {
@@ -94,12 +134,670 @@ pub trait Pick<T> {
 
 impl<T> Pick<T> for &[T] {
 	fn pick(&self) -> &T {
-		&self[(random::<f64>() * self.len() as f64).floor() as usize]
+		pick_with(&mut thread_rng(), self)
 	}
 }
 
 impl<T> Pick<T> for Vec<T> {
 	fn pick(&self) -> &T {
-		&self[(random::<f64>() * self.len() as f64).floor() as usize]
+		pick_with(&mut thread_rng(), self)
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WeightError {
+	/// The weight at the contained index is negative, NaN or infinite.
+	InvalidWeight(usize),
+	/// There are no items, or all weights are zero.
+	NoPositiveWeight,
+}
+
+impl std::fmt::Display for WeightError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			WeightError::InvalidWeight(index) => {
+				write!(f, "weight at index {} is negative or not finite", index)
+			}
+			WeightError::NoPositiveWeight => write!(f, "no item has a positive weight"),
+		}
+	}
+}
+
+impl std::error::Error for WeightError {}
+
+/// Sum of all weights, if they are valid and at least one of them is positive.
+fn weight_total(weights: impl Iterator<Item = f32>) -> Result<f32, WeightError> {
+	let mut total = 0.0;
+	for (i, w) in weights.enumerate() {
+		if w < 0.0 || !w.is_finite() {
+			return Err(WeightError::InvalidWeight(i));
+		}
+		total += w;
+	}
+	if total > 0.0 {
+		Ok(total)
+	} else {
+		Err(WeightError::NoPositiveWeight)
+	}
+}
+
+/// Picks an item with a probability proportional to its weight.
+/// Items with zero weight are never picked.
+/// Use an [`AliasTable`] for many draws from the same weights.
+pub fn weighted_choice<'a, T, R: Rng + ?Sized>(
+	items: &'a [(T, f32)],
+	rng: &mut R,
+) -> Result<&'a T, WeightError> {
+	let total = weight_total(items.iter().map(|(_, w)| *w))?;
+
+	let mut threshold = rng.gen::<f32>() * total;
+	let mut last = None;
+	for (item, weight) in items.iter().filter(|(_, w)| *w > 0.0) {
+		if threshold < *weight {
+			return Ok(item);
+		}
+		threshold -= weight;
+		last = Some(item);
+	}
+	// Rounding errors can leave a tiny rest of the threshold.
+	Ok(last.unwrap())
+}
+
+/// Draws indices from a fixed weighted distribution in constant time, using Vose's alias method.
+/// Building the table takes linear time in the number of weights.
+#[derive(Debug, Clone)]
+pub struct AliasTable {
+	probability: Vec<f32>,
+	alias: Vec<usize>,
+}
+
+impl AliasTable {
+	pub fn new(weights: &[f32]) -> Result<Self, WeightError> {
+		let total = weight_total(weights.iter().copied())?;
+		let n = weights.len();
+
+		// weights scaled to an average of 1
+		let mut scaled = weights
+			.iter()
+			.map(|w| w * n as f32 / total)
+			.collect::<Vec<_>>();
+		let (mut small, mut large): (Vec<_>, Vec<_>) = (0..n).partition(|i| scaled[*i] < 1.0);
+
+		let mut probability = vec![1.0; n];
+		let mut alias = (0..n).collect::<Vec<_>>();
+
+		while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+			small.pop();
+			probability[s] = scaled[s];
+			alias[s] = l;
+
+			scaled[l] -= 1.0 - scaled[s];
+			if scaled[l] < 1.0 {
+				large.pop();
+				small.push(l);
+			}
+		}
+		// Leftovers of either list are full columns, up to rounding errors.
+		// Only zero weights must never be drawn, so they point to a positive weight instead.
+		if let Some(positive) = weights.iter().position(|w| *w > 0.0) {
+			for s in small.into_iter().filter(|s| weights[*s] == 0.0) {
+				probability[s] = 0.0;
+				alias[s] = positive;
+			}
+		}
+
+		Ok(Self { probability, alias })
+	}
+
+	/// Index of a weight, with a probability proportional to the weight.
+	pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+		let i = rng.gen_range(0..self.probability.len());
+		if rng.gen::<f32>() < self.probability[i] {
+			i
+		} else {
+			self.alias[i]
+		}
+	}
+}
+
+/// Picks `n` distinct items in random order, or all items if there are less than `n`.
+pub fn sample_without_replacement<'a, T, R: Rng + ?Sized>(
+	items: &'a [T],
+	n: usize,
+	rng: &mut R,
+) -> Vec<&'a T> {
+	items.choose_multiple(rng, n).collect()
+}
+
+/// Shuffles the items in place. Pass a seeded rng, e.g. `StdRng::seed_from_u64`, for reproducible orders.
+pub fn shuffle<T, R: Rng + ?Sized>(items: &mut [T], rng: &mut R) {
+	items.shuffle(rng);
+}
+
+/// Uniformly distributed point inside the unit disk.
+pub fn random_in_unit_disk<R: Rng + ?Sized>(rng: &mut R) -> Vec2 {
+	let r = rng.gen::<f32>().sqrt();
+	let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+	vec2(angle.cos(), angle.sin()) * r
+}
+
+/// Uniformly distributed point on the surface of the unit sphere.
+/// Uses Archimedes' projection, so points are area-uniform and not clustered at the poles.
+pub fn random_on_unit_sphere<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+	let z = rng.gen_range(-1.0..=1.0_f32);
+	let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+	let r = (1.0 - z * z).max(0.0).sqrt();
+	vec3(angle.cos() * r, angle.sin() * r, z)
+}
+
+/// Uniformly distributed point inside the unit sphere.
+pub fn random_in_unit_sphere<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+	in_unit_sphere_with(rng)
+}
+
+/// Uniformly distributed point inside the triangle `a`, `b`, `c`.
+pub fn random_in_triangle<R: Rng + ?Sized>(a: Vec3, b: Vec3, c: Vec3, rng: &mut R) -> Vec3 {
+	let mut u = rng.gen::<f32>();
+	let mut v = rng.gen::<f32>();
+	// fold points of the other half of the parallelogram back into the triangle
+	if u + v > 1.0 {
+		u = 1.0 - u;
+		v = 1.0 - v;
+	}
+	a + (b - a) * u + (c - a) * v
+}
+
+/// Deterministic random generator for reproducible procedural generation.
+/// Generators with the same seed produce the same sequence, with the same version of `rand`.
+/// Offers the helpers of this module as methods, and can be passed to functions taking an [`Rng`].
+#[derive(Debug, Clone)]
+pub struct SeededRng(StdRng);
+
+impl SeededRng {
+	pub fn new(seed: u64) -> Self {
+		Self(StdRng::seed_from_u64(seed))
+	}
+
+	pub fn range(&mut self, min: f32, max: f32) -> f32 {
+		range_with(self, min, max)
+	}
+
+	pub fn usize(&mut self, max: usize) -> usize {
+		usize_with(self, max)
+	}
+
+	pub fn f32(&mut self) -> f32 {
+		self.gen()
+	}
+
+	pub fn f64(&mut self) -> f64 {
+		self.gen()
+	}
+
+	pub fn bool(&mut self) -> bool {
+		self.gen()
+	}
+
+	pub fn sign(&mut self) -> f32 {
+		sign_with(self)
+	}
+
+	pub fn vec3(&mut self) -> Vec3 {
+		vec3_with(self)
+	}
+
+	pub fn vec3_range(&mut self, min: f32, max: f32) -> Vec3 {
+		vec3_range_with(self, min, max)
+	}
+
+	pub fn vec3_unit(&mut self) -> Vec3 {
+		self.in_unit_sphere().normalize()
+	}
+
+	pub fn in_unit_sphere(&mut self) -> Vec3 {
+		in_unit_sphere_with(self)
+	}
+
+	pub fn vec4(&mut self) -> Vec4 {
+		vec4_with(self)
+	}
+
+	pub fn vec4_range(&mut self, min: f32, max: f32) -> Vec4 {
+		vec4_range_with(self, min, max)
+	}
+
+	/// See [`rand_normal`].
+	pub fn normal(&mut self) -> f32 {
+		normal_with(self)
+	}
+
+	/// See [`rand_normal_01`].
+	pub fn normal_01(&mut self) -> f32 {
+		normal_01_with(self)
+	}
+
+	pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+		pick_with(self, items)
+	}
+
+	/// See [`weighted_choice`].
+	pub fn weighted_choice<'a, T>(&mut self, items: &'a [(T, f32)]) -> Result<&'a T, WeightError> {
+		weighted_choice(items, self)
+	}
+
+	pub fn sample_without_replacement<'a, T>(&mut self, items: &'a [T], n: usize) -> Vec<&'a T> {
+		sample_without_replacement(items, n, self)
+	}
+
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		shuffle(items, self)
+	}
+}
+
+impl RngCore for SeededRng {
+	fn next_u32(&mut self) -> u32 {
+		self.0.next_u32()
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.0.next_u64()
+	}
+
+	fn fill_bytes(&mut self, dest: &mut [u8]) {
+		self.0.fill_bytes(dest)
+	}
+
+	fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+		self.0.try_fill_bytes(dest)
+	}
+}
+
+/// Coherent gradient and value noise, seeded from a [`SeededRng`].
+/// All noise functions return values in `[-1, 1]` and repeat every 256 units.
+#[derive(Debug, Clone)]
+pub struct Noise {
+	perm: Vec<u8>,
+	values: Vec<f32>,
+}
+
+fn fade(t: f32) -> f32 {
+	t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+	a + (b - a) * t
+}
+
+/// Integer lattice coordinate of a floored `x`, wrapped to the 256 entries of the permutation table,
+/// so that the neighbouring coordinate can't overflow for large inputs.
+fn lattice(x0: f32) -> i32 {
+	x0.rem_euclid(256.0) as i32
+}
+
+impl Noise {
+	pub fn new(rng: &mut SeededRng) -> Self {
+		let mut perm = (0..=255u8).collect::<Vec<_>>();
+		rng.shuffle(&mut perm);
+		perm.extend_from_within(..);
+		let values = (0..256).map(|_| rng.range(-1.0, 1.0)).collect();
+		Self { perm, values }
+	}
+
+	pub fn from_seed(seed: u64) -> Self {
+		Self::new(&mut SeededRng::new(seed))
+	}
+
+	fn hash(&self, x: i32, y: i32) -> usize {
+		self.perm[self.perm[(x & 255) as usize] as usize + (y & 255) as usize] as usize
+	}
+
+	fn hash_3d(&self, x: i32, y: i32, z: i32) -> usize {
+		self.perm[self.hash(x, y) + (z & 255) as usize] as usize
+	}
+
+	fn grad_2d(hash: usize, x: f32, y: f32) -> f32 {
+		// eight unit directions, axis aligned and diagonal
+		const D: f32 = std::f32::consts::FRAC_1_SQRT_2;
+		let (gx, gy) = match hash & 7 {
+			0 => (1.0, 0.0),
+			1 => (-1.0, 0.0),
+			2 => (0.0, 1.0),
+			3 => (0.0, -1.0),
+			4 => (D, D),
+			5 => (-D, D),
+			6 => (D, -D),
+			_ => (-D, -D),
+		};
+		gx * x + gy * y
+	}
+
+	fn grad_3d(hash: usize, x: f32, y: f32, z: f32) -> f32 {
+		// the twelve cube edge directions of improved Perlin noise
+		match hash % 12 {
+			0 => x + y,
+			1 => -x + y,
+			2 => x - y,
+			3 => -x - y,
+			4 => x + z,
+			5 => -x + z,
+			6 => x - z,
+			7 => -x - z,
+			8 => y + z,
+			9 => -y + z,
+			10 => y - z,
+			_ => -y - z,
+		}
+	}
+
+	pub fn perlin_2d(&self, x: f32, y: f32) -> f32 {
+		let (x0, y0) = (x.floor(), y.floor());
+		let (xi, yi) = (lattice(x0), lattice(y0));
+		let (x, y) = (x - x0, y - y0);
+		let (u, v) = (fade(x), fade(y));
+
+		let n00 = Self::grad_2d(self.hash(xi, yi), x, y);
+		let n10 = Self::grad_2d(self.hash(xi + 1, yi), x - 1.0, y);
+		let n01 = Self::grad_2d(self.hash(xi, yi + 1), x, y - 1.0);
+		let n11 = Self::grad_2d(self.hash(xi + 1, yi + 1), x - 1.0, y - 1.0);
+
+		// unit gradients reach at most sqrt(1/2)
+		let n = lerp(lerp(n00, n10, u), lerp(n01, n11, u), v);
+		(n * std::f32::consts::SQRT_2).clamp(-1.0, 1.0)
+	}
+
+	pub fn perlin_3d(&self, x: f32, y: f32, z: f32) -> f32 {
+		let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+		let (xi, yi, zi) = (lattice(x0), lattice(y0), lattice(z0));
+		let (x, y, z) = (x - x0, y - y0, z - z0);
+		let (u, v, w) = (fade(x), fade(y), fade(z));
+
+		let corner = |dx: i32, dy: i32, dz: i32| {
+			let hash = self.hash_3d(xi + dx, yi + dy, zi + dz);
+			Self::grad_3d(hash, x - dx as f32, y - dy as f32, z - dz as f32)
+		};
+
+		let n = lerp(
+			lerp(
+				lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+				lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+				v,
+			),
+			lerp(
+				lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+				lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+				v,
+			),
+			w,
+		);
+		n.clamp(-1.0, 1.0)
+	}
+
+	/// Smoothly interpolated random values at the integer lattice points.
+	pub fn value_noise_2d(&self, x: f32, y: f32) -> f32 {
+		let (x0, y0) = (x.floor(), y.floor());
+		let (xi, yi) = (lattice(x0), lattice(y0));
+		let (u, v) = (fade(x - x0), fade(y - y0));
+		let value = |dx: i32, dy: i32| self.values[self.hash(xi + dx, yi + dy)];
+
+		lerp(
+			lerp(value(0, 0), value(1, 0), u),
+			lerp(value(0, 1), value(1, 1), u),
+			v,
+		)
+	}
+}
+
+/// Fractal sum of `octaves` noise samples. `noise` is called with the frequency of each octave,
+/// starting at 1 and multiplied by `lacunarity`, and weighted with amplitudes starting at 1
+/// and multiplied by `gain`. The sum is normalized, so it stays within the range of `noise`.
+///
+/// `fbm(5, 2.0, 0.5, |f| noise.perlin_2d(x * f, y * f))`
+pub fn fbm(octaves: u32, lacunarity: f32, gain: f32, noise: impl Fn(f32) -> f32) -> f32 {
+	let mut sum = 0.0;
+	let mut total = 0.0;
+	let mut frequency = 1.0;
+	let mut amplitude = 1.0;
+	for _ in 0..octaves {
+		sum += noise(frequency) * amplitude;
+		total += amplitude;
+		frequency *= lacunarity;
+		amplitude *= gain;
+	}
+	if total > 0.0 {
+		sum / total
+	} else {
+		0.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashSet;
+
+	#[test]
+	fn weighted_choice_frequencies() {
+		let mut rng = StdRng::seed_from_u64(7);
+		let items = [('a', 1.0), ('b', 0.0), ('c', 3.0), ('d', 0.0), ('e', 6.0)];
+		let expected = [0.1, 0.0, 0.3, 0.0, 0.6];
+		let draws = 100_000;
+
+		let mut counts = [0; 5];
+		for _ in 0..draws {
+			let item = weighted_choice(&items, &mut rng).unwrap();
+			counts[items.iter().position(|(i, _)| i == item).unwrap()] += 1;
+		}
+
+		let table = AliasTable::new(&items.map(|(_, w)| w)).unwrap();
+		let mut table_counts = [0; 5];
+		for _ in 0..draws {
+			table_counts[table.sample(&mut rng)] += 1;
+		}
+
+		for counts in [counts, table_counts] {
+			assert_eq!(counts[1], 0);
+			assert_eq!(counts[3], 0);
+			for (count, expected) in counts.iter().zip(expected) {
+				let frequency = *count as f32 / draws as f32;
+				assert!(
+					(frequency - expected).abs() < 0.01,
+					"{frequency} != {expected}"
+				);
+			}
+		}
+
+		assert_eq!(
+			weighted_choice(&[(1, 0.0), (2, 0.0)], &mut rng),
+			Err(WeightError::NoPositiveWeight)
+		);
+		assert_eq!(
+			weighted_choice::<u8, _>(&[], &mut rng),
+			Err(WeightError::NoPositiveWeight)
+		);
+		assert_eq!(
+			weighted_choice(&[(1, 1.0), (2, -1.0)], &mut rng),
+			Err(WeightError::InvalidWeight(1))
+		);
+		assert_eq!(
+			weighted_choice(&[(1, f32::NAN)], &mut rng),
+			Err(WeightError::InvalidWeight(0))
+		);
+		assert_eq!(
+			AliasTable::new(&[0.0, -2.0]).unwrap_err(),
+			WeightError::InvalidWeight(1)
+		);
+		assert_eq!(
+			AliasTable::new(&[]).unwrap_err(),
+			WeightError::NoPositiveWeight
+		);
+
+		let single = AliasTable::new(&[0.0, 2.0, 0.0]).unwrap();
+		assert!((0..100).all(|_| single.sample(&mut rng) == 1));
+	}
+
+	#[test]
+	fn sample_without_repeats() {
+		let mut rng = StdRng::seed_from_u64(3);
+		let items: Vec<u32> = (0..20).collect();
+
+		for n in [0, 1, 10, 20, 30] {
+			let sample = sample_without_replacement(&items, n, &mut rng);
+			assert_eq!(sample.len(), n.min(items.len()));
+			assert_eq!(sample.iter().collect::<HashSet<_>>().len(), sample.len());
+		}
+	}
+
+	#[test]
+	fn seeded_shuffle() {
+		let shuffled = |seed| {
+			let mut items: Vec<u32> = (0..10).collect();
+			shuffle(&mut items, &mut StdRng::seed_from_u64(seed));
+			items
+		};
+
+		assert_eq!(shuffled(1), shuffled(1));
+		assert_ne!(shuffled(1), shuffled(2));
+
+		let mut sorted = shuffled(1);
+		sorted.sort();
+		assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn seeded_rng_determinism() {
+		let sequence = |seed| {
+			let mut rng = SeededRng::new(seed);
+			let items = [1, 2, 3, 4, 5];
+			let mut shuffled = items;
+			rng.shuffle(&mut shuffled);
+			(
+				rng.f32(),
+				rng.range(-5.0, 5.0),
+				rng.usize(100),
+				rng.vec3_unit(),
+				rng.vec4_range(0.0, 2.0),
+				rng.normal(),
+				*rng.pick(&items),
+				rng.weighted_choice(&[(1, 1.0), (2, 2.0)]).copied(),
+				shuffled,
+				rng.gen::<u64>(),
+			)
+		};
+
+		assert_eq!(sequence(42), sequence(42));
+		assert_ne!(sequence(42), sequence(43));
+
+		let mut a = SeededRng::new(9);
+		let mut b = a.clone();
+		for _ in 0..100 {
+			assert_eq!(a.in_unit_sphere(), b.in_unit_sphere());
+		}
+		let range = SeededRng::new(1).range(2.0, 3.0);
+		assert!((2.0..3.0).contains(&range));
+	}
+
+	#[test]
+	fn noise_range_and_determinism() {
+		let a = Noise::from_seed(5);
+		let b = Noise::new(&mut SeededRng::new(5));
+		let c = Noise::from_seed(6);
+
+		let mut min = f32::MAX;
+		let mut max = f32::MIN;
+		let mut differs = false;
+		for i in 0..2000 {
+			let x = i as f32 * 0.173 - 40.0;
+			let y = i as f32 * 0.071 + (i as f32).sin() * 3.0;
+			let z = i as f32 * 0.037;
+
+			let values = [
+				a.perlin_2d(x, y),
+				a.perlin_3d(x, y, z),
+				a.value_noise_2d(x, y),
+				fbm(5, 2.0, 0.5, |f| a.perlin_2d(x * f, y * f)),
+			];
+			for v in values {
+				assert!((-1.0..=1.0).contains(&v));
+				min = min.min(v);
+				max = max.max(v);
+			}
+
+			assert_eq!(values[0], b.perlin_2d(x, y));
+			assert_eq!(values[1], b.perlin_3d(x, y, z));
+			assert_eq!(values[2], b.value_noise_2d(x, y));
+			differs |= values[0] != c.perlin_2d(x, y);
+		}
+
+		assert!(differs);
+		assert!(min < -0.5 && max > 0.5);
+
+		// gradient noise is zero at lattice points, and continuous between them
+		assert_eq!(a.perlin_2d(3.0, -7.0), 0.0);
+		assert!((a.perlin_2d(3.5, 1.25) - a.perlin_2d(3.5001, 1.25)).abs() < 1e-3);
+		assert!((a.value_noise_2d(3.5, 1.25) - a.value_noise_2d(3.5001, 1.25)).abs() < 1e-3);
+		assert_eq!(fbm(0, 2.0, 0.5, |_| 1.0), 0.0);
+
+		// the lattice repeats every 256 units, also far beyond the i32 range
+		assert_eq!(a.perlin_2d(3.5, 1.25), a.perlin_2d(-252.5, 257.25));
+		assert_eq!(
+			a.perlin_3d(3.5, 1.25, 0.5),
+			a.perlin_3d(259.5, -254.75, 0.5)
+		);
+		assert_eq!(a.value_noise_2d(3.5, 1.25), a.value_noise_2d(3.5, -510.75));
+		for x in [i32::MAX as f32, i32::MIN as f32 - 512.0, f32::MAX] {
+			assert!((-1.0..=1.0).contains(&a.perlin_2d(x, x)));
+			assert!((-1.0..=1.0).contains(&a.perlin_3d(x, x, x)));
+			assert!((-1.0..=1.0).contains(&a.value_noise_2d(x, x)));
+		}
+	}
+
+	#[test]
+	fn point_distributions() {
+		let mut rng = SeededRng::new(11);
+		let n = 20000;
+		let mean = |points: &[Vec3]| points.iter().sum::<Vec3>() / points.len() as f32;
+
+		let disk = (0..n)
+			.map(|_| random_in_unit_disk(&mut rng).extend(0.0))
+			.collect::<Vec<_>>();
+		assert!(disk.iter().all(|p| p.length() <= 1.0));
+		assert!(mean(&disk).length() < 0.02);
+		// half of the area lies within radius sqrt(1/2)
+		let inner = disk.iter().filter(|p| p.length() < 0.5_f32.sqrt()).count();
+		assert!((inner as f32 / n as f32 - 0.5).abs() < 0.02);
+
+		let surface = (0..n)
+			.map(|_| random_on_unit_sphere(&mut rng))
+			.collect::<Vec<_>>();
+		assert!(surface.iter().all(|p| (p.length() - 1.0).abs() < 1e-5));
+		assert!(mean(&surface).length() < 0.02);
+		// area-uniform points have a mean distance of 1/2 to the equator plane of every axis
+		for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+			let height = surface.iter().map(|p| p.dot(axis).abs()).sum::<f32>() / n as f32;
+			assert!((height - 0.5).abs() < 0.02);
+		}
+
+		let ball = (0..n)
+			.map(|_| random_in_unit_sphere(&mut rng))
+			.collect::<Vec<_>>();
+		assert!(ball.iter().all(|p| p.length() < 1.0));
+		assert!(mean(&ball).length() < 0.02);
+
+		let (a, b, c) = (
+			vec3(0.0, 0.0, 0.0),
+			vec3(4.0, 0.0, 0.0),
+			vec3(0.0, 2.0, 1.0),
+		);
+		let triangle = (0..n)
+			.map(|_| random_in_triangle(a, b, c, &mut rng))
+			.collect::<Vec<_>>();
+		let normal = (b - a).cross(c - a);
+		for p in &triangle {
+			assert!(normal.dot(*p - a).abs() < 1e-4);
+			for (v0, v1) in [(a, b), (b, c), (c, a)] {
+				assert!((v1 - v0).cross(*p - v0).dot(normal) >= -1e-4);
+			}
+		}
+		assert!(mean(&triangle).abs_diff_eq((a + b + c) / 3.0, 0.03));
 	}
 }
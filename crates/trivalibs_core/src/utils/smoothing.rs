@@ -0,0 +1,125 @@
+use crate::geometry::interpolation::Spring;
+use std::ops::{Add, Mul, Sub};
+
+/// Values that can be smoothed over time, like `f32`, `Vec2` or `Vec3`.
+pub trait Smoothable:
+	Copy + Default + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self>
+{
+}
+
+impl<T> Smoothable for T where
+	T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>
+{
+}
+
+/// Moves `current` exponentially towards `target`. `lambda` is the approach rate per second,
+/// the result is independent of the frame rate, e.g. two steps of `dt / 2` equal one step of `dt`.
+pub fn damp<T: Smoothable>(current: T, target: T, lambda: f32, dt: f32) -> T {
+	current + (target - current) * (1.0 - f32::exp(-lambda * dt))
+}
+
+/// Value that follows a target with [`damp`].
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothedValue<T: Smoothable> {
+	pub value: T,
+	pub lambda: f32,
+}
+
+impl<T: Smoothable> SmoothedValue<T> {
+	pub fn new(value: T, lambda: f32) -> Self {
+		Self { value, lambda }
+	}
+
+	pub fn update(&mut self, target: T, dt: f32) -> T {
+		self.value = damp(self.value, target, self.lambda, dt);
+		self.value
+	}
+}
+
+/// Critically damped spring, that follows a target without overshooting it
+/// when starting at rest. Unlike [`SmoothedValue`] it keeps its velocity,
+/// so it eases in and out when the target moves.
+#[derive(Debug, Clone, Copy)]
+pub struct SpringValue<T: Smoothable> {
+	pub value: T,
+	pub velocity: T,
+	/// Angular frequency of the spring. Higher values follow the target faster.
+	pub omega: f32,
+}
+
+impl<T: Smoothable> SpringValue<T> {
+	pub fn new(value: T, omega: f32) -> Self {
+		Self {
+			value,
+			velocity: T::default(),
+			omega,
+		}
+	}
+
+	/// Advances a [`Spring`] with stiffness `omega²` and critical damping.
+	pub fn update(&mut self, target: T, dt: f32) -> T {
+		let stiffness = self.omega * self.omega;
+		let damping = Spring::<T>::critical_damping(stiffness);
+		let mut spring = Spring {
+			position: self.value,
+			velocity: self.velocity,
+		};
+		spring.update(target, dt, stiffness, damping);
+
+		self.value = spring.position;
+		self.velocity = spring.velocity;
+		self.value
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::{vec3, Vec3};
+
+	#[test]
+	fn damp_converges() {
+		let mut value = 0.0;
+		let mut prev_dist = 10.0;
+		for _ in 0..100 {
+			value = damp(value, 10.0, 5.0, 1.0 / 60.0);
+			let dist = 10.0 - value;
+			assert!(dist > 0.0 && dist < prev_dist);
+			prev_dist = dist;
+		}
+		assert!(prev_dist < 0.02);
+	}
+
+	#[test]
+	fn damp_frame_rate_independent() {
+		let target = vec3(1.0, -2.0, 3.0);
+		let full = damp(Vec3::ZERO, target, 3.0, 0.1);
+		let half = damp(damp(Vec3::ZERO, target, 3.0, 0.05), target, 3.0, 0.05);
+		assert!(full.distance(half) < 1e-5);
+
+		let mut smoothed = SmoothedValue::new(Vec3::ZERO, 3.0);
+		smoothed.update(target, 0.05);
+		assert!(smoothed.update(target, 0.05).distance(full) < 1e-5);
+	}
+
+	#[test]
+	fn spring_follows_without_overshoot() {
+		let mut spring = SpringValue::new(0.0, 10.0);
+		let mut prev = 0.0;
+		for _ in 0..120 {
+			let value = spring.update(1.0, 1.0 / 60.0);
+			assert!(value >= prev && value <= 1.0);
+			prev = value;
+		}
+		assert!((1.0 - prev).abs() < 1e-3);
+		assert!(spring.velocity.abs() < 1e-2);
+
+		// frame rate independent as well
+		let mut a = SpringValue::new(0.0, 4.0);
+		let mut b = a;
+		a.update(1.0, 0.1);
+		b.update(1.0, 0.05);
+		b.update(1.0, 0.05);
+		assert!((a.value - b.value).abs() < 1e-5);
+	}
+}
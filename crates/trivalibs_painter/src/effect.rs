@@ -1,4 +1,9 @@
-use super::{shade::Shade, uniform::Uniform, Painter};
+use super::{
+	shade::Shade,
+	stencil::Stencil,
+	uniform::{Uniform, UniformKind},
+	Painter,
+};
 use std::collections::BTreeMap;
 
 pub(crate) struct EffectStorage {
@@ -6,11 +11,39 @@ pub(crate) struct EffectStorage {
 	pub shade: Shade,
 	pub pipeline_key: Vec<u8>,
 	pub blend_state: wgpu::BlendState,
+	pub output_format: Option<wgpu::TextureFormat>,
+	pub pass: Option<EffectPass>,
+	pub stencil: Option<Stencil>,
 }
 
+/// Own render target of an effect, at a fraction of the layer resolution.
+/// Chains of passes build e.g. bloom: a bright pass, downsample passes at decreasing scales,
+/// and a final pass at scale 1 that combines them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectPass {
+	/// Size of the target relative to the layer size.
+	pub scale: f32,
+	pub format: wgpu::TextureFormat,
+}
+
+#[derive(Debug, PartialEq)]
 pub struct EffectProps {
 	pub uniforms: BTreeMap<u32, Uniform>,
 	pub blend_state: wgpu::BlendState,
+	/// Format of the texture this effect renders into.
+	/// If it differs from the format of the previous target in the layer,
+	/// the layer allocates an additional target with this format. Defaults to the layer format.
+	pub output_format: Option<wgpu::TextureFormat>,
+	/// Renders into a new target of the layer, instead of sharing the target of the previous effect.
+	/// Following effects without pass render into the same target. Takes precedence over `output_format`.
+	/// Use [`crate::layer::Layer::get_effect_uniform`] to sample the target in later effects.
+	pub pass: Option<EffectPass>,
+	/// Restricts the effect to the pixels passing the stencil test, e.g. [`Stencil::equal`]
+	/// for the mask written by sketches of the layer. Effects with stencil test keep the pixels
+	/// outside of the mask, and may sample their own target, as they render into a separate
+	/// texture that is blended into the target through the mask.
+	/// They must render into a full size target, and are not supported on multisampled layers.
+	pub stencil: Option<Stencil>,
 }
 
 impl Default for EffectProps {
@@ -18,35 +51,84 @@ impl Default for EffectProps {
 		EffectProps {
 			uniforms: BTreeMap::new(),
 			blend_state: wgpu::BlendState::REPLACE,
+			output_format: None,
+			pass: None,
+			stencil: None,
 		}
 	}
 }
 
-#[derive(Clone, Copy, Debug)]
+impl EffectProps {
+	pub fn builder() -> EffectPropsBuilder {
+		EffectPropsBuilder(EffectProps::default())
+	}
+}
+
+/// Fluent alternative to spreading [`EffectProps::default`] into a struct literal.
+pub struct EffectPropsBuilder(EffectProps);
+
+impl EffectPropsBuilder {
+	pub fn uniforms(mut self, uniforms: BTreeMap<u32, Uniform>) -> Self {
+		self.0.uniforms = uniforms;
+		self
+	}
+
+	pub fn blend_state(mut self, blend_state: wgpu::BlendState) -> Self {
+		self.0.blend_state = blend_state;
+		self
+	}
+
+	pub fn output_format(mut self, format: wgpu::TextureFormat) -> Self {
+		self.0.output_format = Some(format);
+		self
+	}
+
+	pub fn pass(mut self, scale: f32, format: wgpu::TextureFormat) -> Self {
+		self.0.pass = Some(EffectPass { scale, format });
+		self
+	}
+
+	pub fn stencil(mut self, stencil: Stencil) -> Self {
+		self.0.stencil = Some(stencil);
+		self
+	}
+
+	pub fn build(self) -> EffectProps {
+		self.0
+	}
+}
+
+/// Resolved state of an effect, see [`Painter::effect_debug`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectDebug {
+	pub shade: Shade,
+	/// Bind group index and kind of each effect uniform.
+	pub uniform_bindings: Vec<(u32, UniformKind)>,
+	pub output_format: Option<wgpu::TextureFormat>,
+	pub pass: Option<EffectPass>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Effect(pub(crate) usize);
 
+/// Pipeline key of a blend state.
+pub(crate) fn blend_key(blend: &wgpu::BlendState) -> [u8; 6] {
+	[
+		blend.alpha.dst_factor as u8,
+		blend.alpha.src_factor as u8,
+		blend.alpha.operation as u8,
+		blend.color.dst_factor as u8,
+		blend.color.src_factor as u8,
+		blend.color.operation as u8,
+	]
+}
+
 impl Effect {
 	pub fn new(painter: &mut Painter, shade: Shade, props: &EffectProps) -> Self {
 		let pipeline_key = vec![
 			(shade.0 as u16).to_le_bytes().to_vec(),
-			(props.blend_state.alpha.dst_factor as u8)
-				.to_le_bytes()
-				.to_vec(),
-			(props.blend_state.alpha.src_factor as u8)
-				.to_le_bytes()
-				.to_vec(),
-			(props.blend_state.alpha.operation as u8)
-				.to_le_bytes()
-				.to_vec(),
-			(props.blend_state.color.dst_factor as u8)
-				.to_le_bytes()
-				.to_vec(),
-			(props.blend_state.color.src_factor as u8)
-				.to_le_bytes()
-				.to_vec(),
-			(props.blend_state.color.operation as u8)
-				.to_le_bytes()
-				.to_vec(),
+			blend_key(&props.blend_state).to_vec(),
+			Stencil::pipeline_key(props.stencil).to_vec(),
 		]
 		.into_iter()
 		.flatten()
@@ -57,6 +139,9 @@ impl Effect {
 			shade,
 			pipeline_key,
 			blend_state: props.blend_state,
+			output_format: props.output_format,
+			pass: props.pass,
+			stencil: props.stencil,
 		};
 
 		painter.effects.push(effect);
@@ -1,4 +1,4 @@
-use super::{painter::get_padded_size, Painter};
+use super::{frame_capture::CaptureEvent, painter::get_padded_size, Painter};
 use trivalibs_core::rendering::RenderableBuffer;
 
 pub struct FormProps {
@@ -15,6 +15,10 @@ impl Default for FormProps {
 	}
 }
 
+/// Range of indices, or vertices for forms without index buffer, to draw a part of a form,
+/// see [`crate::sketch::SketchProps::draw_range`].
+pub type SectionRange = std::ops::Range<u32>;
+
 pub(crate) struct FormStorage {
 	pub vertex_buffer: wgpu::Buffer,
 	pub index_buffer: Option<wgpu::Buffer>,
@@ -31,7 +35,7 @@ where
 	pub index_buffer: Option<&'a [u32]>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Form(pub(crate) usize);
 
 impl Form {
@@ -39,6 +43,12 @@ impl Form {
 	where
 		T: bytemuck::Pod + bytemuck::Zeroable,
 	{
+		painter.capture_event(|| CaptureEvent::BufferUpdate {
+			form: self.0,
+			vertex_bytes: std::mem::size_of_val(data.vertex_buffer),
+			index_bytes: data.index_buffer.map_or(0, std::mem::size_of_val),
+		});
+
 		let f = &mut painter.forms[self.0];
 
 		f.vertex_count = data.vertex_buffer.len() as u32;
@@ -72,6 +82,12 @@ impl Form {
 	}
 
 	pub fn update_buffer(&self, painter: &mut Painter, buffers: RenderableBuffer) {
+		painter.capture_event(|| CaptureEvent::BufferUpdate {
+			form: self.0,
+			vertex_bytes: buffers.vertex_buffer.len(),
+			index_bytes: buffers.index_buffer.as_ref().map_or(0, |i| i.len()),
+		});
+
 		let f = &mut painter.forms[self.0];
 
 		f.vertex_count = buffers.vertex_count;
@@ -0,0 +1,177 @@
+use serde::{Serialize, Serializer};
+use std::path::PathBuf;
+
+/// Painter command recorded during a captured frame.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum CaptureEvent {
+	BufferUpdate {
+		form: usize,
+		vertex_bytes: usize,
+		index_bytes: usize,
+	},
+	UniformUpdate {
+		uniform: usize,
+		bytes: usize,
+	},
+	Draw {
+		sketch: usize,
+		layer: Option<usize>,
+		#[serde(serialize_with = "hex")]
+		pipeline: Vec<u8>,
+		elements: u32,
+		instances: usize,
+		/// Draws of the depth prepass only write depth, and are followed by a color draw.
+		depth_prepass: bool,
+	},
+	Effect {
+		effect: usize,
+		layer: usize,
+		#[serde(serialize_with = "hex")]
+		pipeline: Vec<u8>,
+	},
+	Layer {
+		layer: usize,
+		sketches: usize,
+		effects: usize,
+		draw_calls: usize,
+	},
+	Present {
+		layer: Option<usize>,
+	},
+}
+
+fn hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+	serializer.serialize_str(&hex)
+}
+
+/// Log of the painter commands of a single frame, written as JSON when the frame is presented.
+#[derive(Serialize)]
+pub(crate) struct FrameCapture {
+	#[serde(skip)]
+	pub path: PathBuf,
+	pub events: Vec<CaptureEvent>,
+}
+
+impl FrameCapture {
+	pub fn new(path: PathBuf) -> Self {
+		FrameCapture {
+			path,
+			events: vec![],
+		}
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("frame capture serializes to JSON")
+	}
+
+	/// Writes the JSON to the capture path. Browsers have no file system,
+	/// so on wasm the JSON is logged instead.
+	pub fn write(&self) -> std::io::Result<()> {
+		#[cfg(target_arch = "wasm32")]
+		{
+			log::info!("Frame capture {:?}:\n{}", self.path, self.to_json());
+			Ok(())
+		}
+		#[cfg(not(target_arch = "wasm32"))]
+		std::fs::write(&self.path, self.to_json())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		gpu_tests::{effect_layer, headless_painter, triangle_sketch, FILL_RED},
+		layer::LayerProps,
+		sketch::SketchProps,
+	};
+	use serde_json::{json, Value};
+	use trivalibs_core::utils::default;
+
+	#[test]
+	fn event_json() {
+		let mut capture = FrameCapture::new(PathBuf::new());
+		capture.events.push(CaptureEvent::Draw {
+			sketch: 0,
+			layer: Some(0),
+			pipeline: vec![0, 1, 255],
+			elements: 6,
+			instances: 1,
+			depth_prepass: false,
+		});
+		capture.events.push(CaptureEvent::Present { layer: None });
+
+		let json: Value = serde_json::from_str(&capture.to_json()).unwrap();
+		assert_eq!(
+			json,
+			json!({
+				"events": [
+					{
+						"type": "draw",
+						"sketch": 0,
+						"layer": 0,
+						"pipeline": "0001ff",
+						"elements": 6,
+						"instances": 1,
+						"depth_prepass": false,
+					},
+					{ "type": "present", "layer": null },
+				]
+			})
+		);
+	}
+
+	#[test]
+	fn capture_painted_frame() {
+		let Some(mut p) = headless_painter(4, 4) else {
+			return;
+		};
+		let sketch = triangle_sketch(
+			&mut p,
+			&SketchProps {
+				depth_test: true,
+				..default()
+			},
+		);
+		let layer = p.layer_create(&LayerProps {
+			sketches: vec![sketch],
+			depth_prepass: true,
+			..default()
+		});
+		let effect_layer = effect_layer(&mut p, FILL_RED, default());
+
+		let path = std::env::temp_dir().join(format!("frame_capture_{}.json", std::process::id()));
+		p.capture_next_frame(&path);
+		p.compose(&[layer, effect_layer]).unwrap();
+		// presenting needs a window surface, finish the frame like `show` does
+		p.finish_capture(Some(effect_layer.0));
+
+		let json = std::fs::read_to_string(&path).unwrap();
+		std::fs::remove_file(&path).unwrap();
+		let json: Value = serde_json::from_str(&json).unwrap();
+		let events = json["events"].as_array().unwrap();
+
+		let types = events
+			.iter()
+			.map(|e| e["type"].as_str().unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			types,
+			["draw", "draw", "layer", "effect", "layer", "present"]
+		);
+
+		assert_eq!(events[0]["depth_prepass"], true);
+		assert_eq!(events[1]["depth_prepass"], false);
+		assert_eq!(events[1]["sketch"], sketch.0);
+		assert_eq!(events[1]["elements"], 3);
+		assert_eq!(events[2]["layer"], layer.0);
+		assert_eq!(events[3]["layer"], effect_layer.0);
+		assert_eq!(events[5]["layer"], effect_layer.0);
+
+		// the capture only covers a single frame
+		p.paint(&layer).unwrap();
+		assert!(!path.exists());
+	}
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+/// Caps the frame rate of the render loop, independent of the surface present mode.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FramePacer {
+	frame_time: Option<Duration>,
+}
+
+impl FramePacer {
+	pub fn new(target_fps: Option<f32>) -> Self {
+		FramePacer {
+			frame_time: target_fps
+				.filter(|fps| *fps > 0.0 && fps.is_finite())
+				.map(|fps| Duration::from_secs_f32(1.0 / fps)),
+		}
+	}
+
+	/// Time left to wait before the next frame, `elapsed` after the previous frame started.
+	pub fn remaining(&self, elapsed: Duration) -> Option<Duration> {
+		self.frame_time
+			.and_then(|t| t.checked_sub(elapsed))
+			.filter(|d| !d.is_zero())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn frames_per_simulated_second(pacer: FramePacer, render_time: Duration) -> u32 {
+		let mut time = Duration::ZERO;
+		let mut frames = 0;
+		while time < Duration::from_secs(1) {
+			time += render_time;
+			if let Some(wait) = pacer.remaining(render_time) {
+				time += wait;
+			}
+			frames += 1;
+		}
+		frames
+	}
+
+	#[test]
+	fn target_fps() {
+		let render_time = Duration::from_millis(2);
+
+		let uncapped = frames_per_simulated_second(FramePacer::new(None), render_time);
+		assert_eq!(uncapped, 500);
+
+		let capped = frames_per_simulated_second(FramePacer::new(Some(30.0)), render_time);
+		assert!((29..=31).contains(&capped));
+
+		// frames that take longer than the target are not delayed any further
+		let slow = FramePacer::new(Some(30.0));
+		assert_eq!(slow.remaining(Duration::from_millis(50)), None);
+
+		assert!(FramePacer::new(Some(0.0)).remaining(render_time).is_none());
+	}
+}
@@ -0,0 +1,860 @@
+//! Rendering tests on a headless painter. They are skipped on machines without GPU adapter.
+
+use crate::{
+	effect::{Effect, EffectProps},
+	form::FormData,
+	layer::{Layer, LayerProps},
+	painter::{show_view_format, ShowOpts, UniformType},
+	scene::{cube_buffer, Scene},
+	shade::{Shade, ShadeEffectProps, ShadeProps},
+	sketch::{Sketch, SketchProps},
+	stencil::Stencil,
+	texture::{SamplerProps, Texture, TextureCubeProps, TextureDepthProps, TextureError},
+	uniform::UniformKind,
+	Painter,
+};
+use trivalibs_core::{
+	glam::{vec3, vec4},
+	rendering::{
+		buffered_geometry::{GeometryBuilder, GeometryError, RenderingPrimitive, VertexFormat},
+		camera::{CamProps, PerspectiveCamera},
+		transform::Transform,
+	},
+	utils::default,
+};
+
+pub(crate) fn headless_painter(width: u32, height: u32) -> Option<Painter> {
+	let painter = pollster::block_on(Painter::headless(width, height));
+	if painter.is_none() {
+		eprintln!("no GPU adapter available, skipping rendering test");
+	}
+	painter
+}
+
+pub(crate) const FILL_RED: &str = "
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+/// Effect running the WGSL fragment shader `wgsl`.
+pub(crate) fn wgsl_effect(
+	p: &mut Painter,
+	wgsl: &str,
+	uniform_types: &[&wgpu::BindGroupLayout],
+	props: &EffectProps,
+) -> Effect {
+	let shade = p.shade_create_effect(ShadeEffectProps { uniform_types });
+	shade.set_wgsl(p, wgsl);
+	p.effect_create(shade, props)
+}
+
+pub(crate) const SOLID_TRIANGLE: &str = "
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+	return vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+	return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+}
+";
+
+/// Sketch of a single triangle covering the whole target, painted green.
+pub(crate) fn triangle_sketch(p: &mut Painter, props: &SketchProps) -> Sketch {
+	wgsl_triangle_sketch(p, SOLID_TRIANGLE, 0.5, props)
+}
+
+/// Sketch of a single triangle covering the whole target at depth `z`,
+/// shaded by a WGSL module with `vs_main` taking the position at location 0.
+pub(crate) fn wgsl_triangle_sketch(
+	p: &mut Painter,
+	wgsl: &str,
+	z: f32,
+	props: &SketchProps,
+) -> Sketch {
+	let positions = [vec3(-1.0, -1.0, z), vec3(3.0, -1.0, z), vec3(-1.0, 3.0, z)];
+	let form = p.form_create(
+		&FormData {
+			vertex_buffer: &positions,
+			index_buffer: None,
+		},
+		default(),
+	);
+	let shade = position_shade(p, wgsl);
+	p.sketch_create(form, shade, props)
+}
+
+/// Sketch of a full height quad from `x0` to `x1` in clip space, shaded by `wgsl` like [`wgsl_triangle_sketch`].
+pub(crate) fn quad_sketch(
+	p: &mut Painter,
+	wgsl: &str,
+	x0: f32,
+	x1: f32,
+	props: &SketchProps,
+) -> Sketch {
+	let positions = [
+		vec3(x0, -1.0, 0.5),
+		vec3(x1, -1.0, 0.5),
+		vec3(x1, 1.0, 0.5),
+		vec3(x0, 1.0, 0.5),
+	];
+	let form = p.form_create(
+		&FormData {
+			vertex_buffer: &positions,
+			index_buffer: Some(&[0, 1, 2, 0, 2, 3]),
+		},
+		default(),
+	);
+	let shade = position_shade(p, wgsl);
+	p.sketch_create(form, shade, props)
+}
+
+/// Shade without uniforms for `Float32x3` positions, running the WGSL module `wgsl`.
+pub(crate) fn position_shade(p: &mut Painter, wgsl: &str) -> Shade {
+	let shade = p.shade_create(ShadeProps::<_, wgpu::BindGroupLayout> {
+		vertex_format: wgpu::VertexFormat::Float32x3,
+		uniform_types: &[],
+	});
+	shade.set_wgsl(p, wgsl);
+	shade
+}
+
+/// Layer with a single effect, running the WGSL fragment shader `wgsl`.
+pub(crate) fn effect_layer(p: &mut Painter, wgsl: &str, props: LayerProps) -> Layer {
+	let effect = wgsl_effect(p, wgsl, &[], &default());
+	p.layer_create(&LayerProps {
+		effects: vec![effect],
+		..props
+	})
+}
+
+#[test]
+fn read_layer_pixels_as_rgba() {
+	let Some(mut p) = headless_painter(4, 2) else {
+		return;
+	};
+	let layer = effect_layer(&mut p, FILL_RED, default());
+	p.paint(&layer).unwrap();
+
+	let pixels = p.read_layer_pixels(&layer);
+	assert_eq!(pixels.len(), 4 * 2 * 4);
+	for pixel in pixels.chunks(4) {
+		assert_eq!(pixel, [255, 0, 0, 255]);
+	}
+}
+
+#[test]
+fn read_float_layer_pixels() {
+	let Some(mut p) = headless_painter(4, 2) else {
+		return;
+	};
+	let layer = effect_layer(
+		&mut p,
+		FILL_RED,
+		LayerProps {
+			format: Some(wgpu::TextureFormat::Rgba16Float),
+			..default()
+		},
+	);
+	p.paint(&layer).unwrap();
+
+	// pixels keep the layout of the layer format, four f16 values per pixel
+	let pixels = p.read_layer_pixels(&layer);
+	assert_eq!(pixels.len(), 4 * 2 * 8);
+	let one = 0x3c00u16.to_le_bytes();
+	for pixel in pixels.chunks(8) {
+		assert_eq!(pixel, [one[0], one[1], 0, 0, 0, 0, one[0], one[1]]);
+	}
+}
+
+#[test]
+fn wgsl_effect_gets_texture_coord() {
+	let Some(mut p) = headless_painter(2, 1) else {
+		return;
+	};
+	let wgsl = "
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	return vec4<f32>(coord.x, 0.0, 0.0, 1.0);
+}
+";
+	let layer = effect_layer(
+		&mut p,
+		wgsl,
+		LayerProps {
+			format: Some(wgpu::TextureFormat::Rgba8Unorm),
+			..default()
+		},
+	);
+	p.paint(&layer).unwrap();
+
+	// the coord of the pixel centers
+	let pixels = p.read_layer_pixels(&layer);
+	assert!(pixels[0].abs_diff(64) <= 1);
+	assert!(pixels[4].abs_diff(191) <= 1);
+}
+
+#[test]
+fn tonemap_hdr_layer_to_srgb() {
+	let Some(mut p) = headless_painter(2, 2) else {
+		return;
+	};
+	let hdr = "
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	return vec4<f32>(3.0, 1.0, 0.25, 1.0);
+}
+";
+	let tonemap = "
+@group(0) @binding(0) var hdr_tex: texture_2d<f32>;
+@group(0) @binding(1) var hdr_sampler: sampler;
+
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	let hdr = textureSample(hdr_tex, hdr_sampler, coord).rgb;
+	return vec4<f32>(hdr / (hdr + 1.0), 1.0);
+}
+";
+	let hdr = wgsl_effect(&mut p, hdr, &[], &default());
+	let tex_type = p.uniform_type_tex_2d_frag();
+	let tonemap = wgsl_effect(
+		&mut p,
+		tonemap,
+		&[&tex_type],
+		&EffectProps {
+			output_format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+			..default()
+		},
+	);
+	let layer = p.layer_create(&LayerProps {
+		effects: vec![hdr, tonemap],
+		format: Some(wgpu::TextureFormat::Rgba16Float),
+		..default()
+	});
+	let hdr_uniform = layer.get_sketch_uniform(&mut p);
+	p.effect_set_uniform(&tonemap, 0, hdr_uniform.uniform);
+	p.paint(&layer).unwrap();
+
+	let srgb_byte = |linear: f32| {
+		let encoded = if linear <= 0.0031308 {
+			linear * 12.92
+		} else {
+			1.055 * linear.powf(1.0 / 2.4) - 0.055
+		};
+		(encoded * 255.0).round() as u8
+	};
+	let expected = [srgb_byte(0.75), srgb_byte(0.5), srgb_byte(0.2), 255];
+
+	let pixels = p.read_layer_pixels(&layer);
+	assert_eq!(pixels.len(), 2 * 2 * 4);
+	for pixel in pixels.chunks(4) {
+		for (byte, expected) in pixel.iter().zip(expected) {
+			assert!(byte.abs_diff(expected) <= 1);
+		}
+	}
+}
+
+/// Stored byte of `stored` in a layer of `layer_format`, shown like [`Painter::show_with`] into
+/// a target of `target_format`, that stands in for the surface.
+fn shown_byte(
+	p: &mut Painter,
+	stored: u8,
+	layer_format: wgpu::TextureFormat,
+	target_format: wgpu::TextureFormat,
+	opts: ShowOpts,
+) -> u8 {
+	// writes the stored byte, whether the layer encodes to sRGB on write or not
+	let value = stored as f32 / 255.0;
+	let value = if layer_format.is_srgb() {
+		((value + 0.055) / 1.055).powf(2.4)
+	} else {
+		value
+	};
+	let fill = format!(
+		"
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {{
+	return vec4<f32>(vec3<f32>({:.6}), 1.0);
+}}
+",
+		value
+	);
+	let layer = effect_layer(
+		p,
+		&fill,
+		LayerProps {
+			format: Some(layer_format),
+			..default()
+		},
+	);
+	p.paint(&layer).unwrap();
+	assert_eq!(p.read_layer_pixels(&layer)[0], stored);
+
+	let view_format = show_view_format(layer_format, target_format.is_srgb(), opts);
+	let output = layer.get_output_view_uniform(p, view_format);
+	let copy = "
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	return textureSample(tex, tex_sampler, coord);
+}
+";
+	let tex_type = p.uniform_type_tex_2d_frag();
+	let effect = wgsl_effect(p, copy, &[&tex_type], &default());
+	p.effect_set_uniform(&effect, 0, output.uniform);
+	let target = p.layer_create(&LayerProps {
+		effects: vec![effect],
+		format: Some(target_format),
+		..default()
+	});
+	p.paint(&target).unwrap();
+	p.read_layer_pixels(&target)[0]
+}
+
+#[test]
+fn show_mid_gray() {
+	use wgpu::TextureFormat::{Rgba8Unorm, Rgba8UnormSrgb};
+
+	let Some(mut p) = headless_painter(2, 2) else {
+		return;
+	};
+	let linear = ShowOpts::default();
+	let encoded = ShowOpts {
+		assume_linear_input: false,
+	};
+	let assert_byte = |byte: u8, expected: u8| {
+		assert!(
+			byte.abs_diff(expected) <= 1,
+			"{} instead of {}",
+			byte,
+			expected
+		);
+	};
+
+	// linear input is sampled with the view of the layer format
+	// sRGB surfaces encode linear mid-gray
+	let byte = shown_byte(&mut p, 128, Rgba8Unorm, Rgba8UnormSrgb, linear);
+	assert_byte(byte, 188);
+	// unorm surfaces receive the sampled values, decoded from sRGB layers
+	let byte = shown_byte(&mut p, 128, Rgba8UnormSrgb, Rgba8Unorm, linear);
+	assert_byte(byte, 55);
+	let byte = shown_byte(&mut p, 128, Rgba8Unorm, Rgba8Unorm, linear);
+	assert_byte(byte, 128);
+
+	// encoded input is written back unchanged through a reinterpreted view,
+	// devices without view formats fall back to the view of the layer format
+	let reinterpreted = p.supports_view_formats();
+	let byte = shown_byte(&mut p, 128, Rgba8Unorm, Rgba8UnormSrgb, encoded);
+	assert_byte(byte, if reinterpreted { 128 } else { 188 });
+	let byte = shown_byte(&mut p, 128, Rgba8UnormSrgb, Rgba8Unorm, encoded);
+	assert_byte(byte, if reinterpreted { 128 } else { 55 });
+}
+
+#[test]
+fn depth_prepass_respects_discard() {
+	let Some(mut p) = headless_painter(4, 2) else {
+		return;
+	};
+	let discard_left = "
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+	return vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+	if pos.x < 2.0 {
+		discard;
+	}
+	return vec4<f32>(0.0, 0.0, 1.0, 1.0);
+}
+";
+	let depth_test = SketchProps {
+		depth_test: true,
+		..default()
+	};
+	let front = wgsl_triangle_sketch(&mut p, discard_left, 0.2, &depth_test);
+	let back = wgsl_triangle_sketch(&mut p, SOLID_TRIANGLE, 0.8, &depth_test);
+	let layer = p.layer_create(&LayerProps {
+		sketches: vec![front, back],
+		depth_prepass: true,
+		..default()
+	});
+	p.paint(&layer).unwrap();
+
+	// the back sketch shows where the front sketch discards its fragments
+	let pixels = p.read_layer_pixels(&layer);
+	for (i, pixel) in pixels.chunks(4).enumerate() {
+		if i % 4 < 2 {
+			assert_eq!(pixel, [0, 255, 0, 255]);
+		} else {
+			assert_eq!(pixel, [0, 0, 255, 255]);
+		}
+	}
+}
+
+#[test]
+fn depth_prepass_skips_occluded_fragments() {
+	let Some(mut p) = headless_painter(2, 2) else {
+		return;
+	};
+	let color_triangle =
+		|color: &str| SOLID_TRIANGLE.replace("vec4<f32>(0.0, 1.0, 0.0, 1.0)", color);
+	// additive blending shows every shaded fragment in the result
+	let additive = SketchProps {
+		depth_test: true,
+		blend_state: wgpu::BlendState {
+			color: wgpu::BlendComponent {
+				src_factor: wgpu::BlendFactor::One,
+				dst_factor: wgpu::BlendFactor::One,
+				operation: wgpu::BlendOperation::Add,
+			},
+			alpha: wgpu::BlendComponent::REPLACE,
+		},
+		..default()
+	};
+	let back_wgsl = color_triangle("vec4<f32>(0.25, 0.0, 0.0, 1.0)");
+	let front_wgsl = color_triangle("vec4<f32>(0.0, 0.5, 0.0, 1.0)");
+
+	for (depth_prepass, expected) in [(false, [64, 128, 0, 255]), (true, [0, 128, 0, 255])] {
+		let back = wgsl_triangle_sketch(&mut p, &back_wgsl, 0.8, &additive);
+		let front = wgsl_triangle_sketch(&mut p, &front_wgsl, 0.2, &additive);
+		let layer = p.layer_create(&LayerProps {
+			sketches: vec![back, front],
+			depth_prepass,
+			format: Some(wgpu::TextureFormat::Rgba8Unorm),
+			..default()
+		});
+		p.paint(&layer).unwrap();
+
+		for pixel in p.read_layer_pixels(&layer).chunks(4) {
+			assert_eq!(pixel, expected, "depth prepass: {}", depth_prepass);
+		}
+	}
+}
+
+#[test]
+fn bloom_effect_passes() {
+	let Some(mut p) = headless_painter(8, 8) else {
+		return;
+	};
+	let scene = "
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	return vec4<f32>(0.5, 0.0, 0.0, 1.0);
+}
+";
+	let bright = "
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	let color = textureSample(tex, tex_sampler, coord).rgb;
+	return vec4<f32>(max(color - 0.25, vec3<f32>(0.0)), 1.0);
+}
+";
+	let downsample = "
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	return textureSample(tex, tex_sampler, coord);
+}
+";
+	let combine = "
+@group(0) @binding(0) var scene_tex: texture_2d<f32>;
+@group(0) @binding(1) var scene_sampler: sampler;
+@group(1) @binding(0) var bloom_tex: texture_2d<f32>;
+@group(1) @binding(1) var bloom_sampler: sampler;
+
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	let scene = textureSample(scene_tex, scene_sampler, coord).rgb;
+	let bloom = textureSample(bloom_tex, bloom_sampler, coord).rgb;
+	return vec4<f32>(scene + bloom, 1.0);
+}
+";
+	let tex_type = p.uniform_type_tex_2d_frag();
+	let hdr = wgpu::TextureFormat::Rgba16Float;
+	let scene = wgsl_effect(&mut p, scene, &[], &default());
+	let bright = wgsl_effect(
+		&mut p,
+		bright,
+		&[&tex_type],
+		&EffectProps::builder().pass(0.5, hdr).build(),
+	);
+	let downsample = wgsl_effect(
+		&mut p,
+		downsample,
+		&[&tex_type],
+		&EffectProps::builder().pass(0.25, hdr).build(),
+	);
+	let combine = wgsl_effect(
+		&mut p,
+		combine,
+		&[&tex_type, &tex_type],
+		&EffectProps::builder()
+			.pass(1.0, wgpu::TextureFormat::Rgba8Unorm)
+			.build(),
+	);
+	let layer = p.layer_create(&LayerProps {
+		effects: vec![scene, bright, downsample, combine],
+		format: Some(hdr),
+		..default()
+	});
+
+	let scene_uniform = layer.get_sketch_uniform(&mut p);
+	let bright_uniform = layer.get_effect_uniform(&mut p, 1);
+	let downsample_uniform = layer.get_effect_uniform(&mut p, 2);
+	p.effect_set_uniform(&bright, 0, scene_uniform.uniform);
+	p.effect_set_uniform(&downsample, 0, bright_uniform.uniform);
+	p.effect_set_uniform(&combine, 0, scene_uniform.uniform);
+	p.effect_set_uniform(&combine, 1, downsample_uniform.uniform);
+	p.paint(&layer).unwrap();
+
+	let pixels = p.read_layer_pixels(&layer);
+	assert_eq!(pixels.len(), 8 * 8 * 4);
+	for pixel in pixels.chunks(4) {
+		assert!(pixel[0].abs_diff(191) <= 1);
+		assert_eq!(&pixel[1..], [0, 0, 255]);
+	}
+}
+
+#[test]
+fn effect_uniform_keeps_kind() {
+	let Some(mut p) = headless_painter(2, 2) else {
+		return;
+	};
+	let tex_type = p.uniform_type_tex_2d_frag();
+	let effect = wgsl_effect(&mut p, FILL_RED, &[&tex_type], &default());
+	let layer = p.layer_create(&LayerProps {
+		effects: vec![effect],
+		..default()
+	});
+	let texture = layer.get_sketch_uniform(&mut p);
+	p.effect_set_uniform(&effect, 0, texture.uniform);
+
+	let buffer = p.uniform_type_buffered_frag().create_buff(&mut p, 1.0f32);
+	let replaced = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+		p.effect_set_uniform(&effect, 0, buffer.uniform)
+	}));
+	assert!(replaced.is_err());
+
+	// cubemaps have a different layout than 2D textures
+	let face = [255u8; 4];
+	let cube = p.texture_cube_create(&TextureCubeProps {
+		size: 1,
+		format: wgpu::TextureFormat::Rgba8Unorm,
+		faces: [&face; 6],
+	});
+	let sampler = p.sampler_create(&default());
+	let cube = p
+		.uniform_type_tex_cube_frag()
+		.create_tex_cube(&mut p, cube, &sampler);
+	assert_eq!(p.bindings[cube.uniform.0].kind, UniformKind::TextureCube);
+	let replaced = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+		p.effect_set_uniform(&effect, 0, cube.uniform)
+	}));
+	assert!(replaced.is_err());
+}
+
+#[test]
+fn cube_with_uncopyable_format() {
+	let Some(mut p) = headless_painter(2, 2) else {
+		return;
+	};
+	let format = wgpu::TextureFormat::Depth24PlusStencil8;
+	let cube = Texture::try_create_cube(
+		&mut p,
+		&TextureCubeProps {
+			size: 1,
+			format,
+			faces: [&[0u8; 4]; 6],
+		},
+	);
+	assert!(matches!(cube, Err(TextureError::UncopyableFormat(f)) if f == format));
+}
+
+#[test]
+fn compare_sampled_depth_texture() {
+	let Some(mut p) = headless_painter(2, 2) else {
+		return;
+	};
+	// depth textures are cleared to 0 on creation
+	let depth = Texture::create_depth(&mut p, &TextureDepthProps::new(2, 2));
+	let sampler_props = SamplerProps {
+		compare: Some(wgpu::CompareFunction::Greater),
+		..default()
+	};
+	let sampler = p.sampler_create(&sampler_props);
+	let depth_type = p.uniform_type_tex_2d_with(wgpu::ShaderStages::FRAGMENT, &sampler_props);
+	let depth = depth_type.create_tex2d(&mut p, depth, &sampler);
+
+	let shadow = "
+@group(0) @binding(0) var depth: texture_depth_2d;
+@group(0) @binding(1) var depth_sampler: sampler_comparison;
+
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	let lit = textureSampleCompare(depth, depth_sampler, coord, 0.5);
+	return vec4<f32>(lit, 0.0, 0.0, 1.0);
+}
+";
+	let effect = wgsl_effect(&mut p, shadow, &[&depth_type], &default());
+	p.effect_set_uniform(&effect, 0, depth.uniform);
+	let layer = p.layer_create(&LayerProps {
+		effects: vec![effect],
+		format: Some(wgpu::TextureFormat::Rgba8Unorm),
+		..default()
+	});
+	p.paint(&layer).unwrap();
+
+	for pixel in p.read_layer_pixels(&layer).chunks(4) {
+		assert_eq!(pixel, [255, 0, 0, 255]);
+	}
+}
+
+#[test]
+fn scene_entries_at_transformed_positions() {
+	let Some(mut p) = headless_painter(32, 16) else {
+		return;
+	};
+	let camera = PerspectiveCamera::create(CamProps {
+		fov: Some(0.8),
+		translation: Some(vec3(0.0, 0.0, 10.0)),
+		..default()
+	});
+	let mut scene = Scene::new(&mut p, camera);
+	let cube = p.form_from_buffer(cube_buffer(2.0), default());
+	let red = vec4(1.0, 0.0, 0.0, 1.0);
+	let blue = vec4(0.0, 0.0, 1.0, 1.0);
+	scene.add(&mut p, cube, Transform::from_xyz(-3.0, 0.0, 0.0), red);
+	scene.add(&mut p, cube, Transform::from_xyz(3.0, 0.0, 0.0), blue);
+	let layer = p.layer_create(&LayerProps {
+		format: Some(wgpu::TextureFormat::Rgba8Unorm),
+		..scene.layer_props()
+	});
+	scene.render(&mut p, &layer).unwrap();
+
+	let pixels = p.read_layer_pixels(&layer);
+	let pixel = |x: usize, y: usize| &pixels[(y * 32 + x) * 4..][..4];
+	let (mut left, mut right) = (0, 0);
+	for y in 0..16 {
+		for x in 0..32 {
+			let [r, g, b, a] = pixel(x, y).try_into().unwrap();
+			assert_eq!((g, a), (0, 255));
+			if x < 16 {
+				assert_eq!(b, 0, "blue cube in the left half at {x}, {y}");
+				left += (r > 0) as u32;
+			} else {
+				assert_eq!(r, 0, "red cube in the right half at {x}, {y}");
+				right += (b > 0) as u32;
+			}
+		}
+	}
+	assert!(left > 0 && right > 0);
+
+	// the cubes are centered at the height of the camera, with the black clear color between them
+	assert!(pixel(10, 8)[0] > 0);
+	assert!(pixel(21, 8)[2] > 0);
+	for y in 0..16 {
+		assert_eq!(pixel(15, y), [0, 0, 0, 255]);
+		assert_eq!(pixel(16, y), [0, 0, 0, 255]);
+	}
+}
+
+#[test]
+fn merged_form_sections() {
+	let Some(mut p) = headless_painter(4, 2) else {
+		return;
+	};
+	let quad = |x0: f32, x1: f32| {
+		GeometryBuilder::new(RenderingPrimitive::Triangles)
+			.attribute(
+				"position",
+				VertexFormat::Float32x3,
+				&[
+					vec3(x0, -1.0, 0.5),
+					vec3(x1, -1.0, 0.5),
+					vec3(x1, 1.0, 0.5),
+					vec3(x0, 1.0, 0.5),
+				],
+			)
+			.indices(vec![0, 1, 2, 0, 2, 3])
+			.to_buffered_geometry()
+	};
+	let (form, ranges) = p
+		.merge_forms(&[quad(-1.0, 0.0), quad(0.0, 1.0)], default())
+		.unwrap();
+	assert_eq!(ranges, vec![0..6, 6..12]);
+
+	let red = position_shade(
+		&mut p,
+		&SOLID_TRIANGLE.replace("0.0, 1.0, 0.0", "1.0, 0.0, 0.0"),
+	);
+	let green = position_shade(&mut p, SOLID_TRIANGLE);
+	let left = p.sketch_create(
+		form,
+		red,
+		&SketchProps {
+			draw_range: Some(ranges[0].clone()),
+			..default()
+		},
+	);
+	let right = p.sketch_create(
+		form,
+		green,
+		&SketchProps {
+			draw_range: Some(ranges[1].clone()),
+			..default()
+		},
+	);
+	let layer = p.layer_create(&LayerProps {
+		sketches: vec![left, right],
+		..default()
+	});
+	p.paint(&layer).unwrap();
+
+	let pixels = p.read_layer_pixels(&layer);
+	for (i, pixel) in pixels.chunks(4).enumerate() {
+		if i % 4 < 2 {
+			assert_eq!(pixel, [255, 0, 0, 255]);
+		} else {
+			assert_eq!(pixel, [0, 255, 0, 255]);
+		}
+	}
+
+	let points = GeometryBuilder::new(RenderingPrimitive::Points)
+		.attribute("position", VertexFormat::Float32x3, &[vec3(0.0, 0.0, 0.5)])
+		.to_buffered_geometry();
+	assert_eq!(
+		p.merge_forms(&[quad(-1.0, 0.0), points], default()).err(),
+		Some(GeometryError::LayoutMismatch { index: 1 })
+	);
+}
+
+#[test]
+fn stencil_effect_samples_its_target() {
+	let Some(mut p) = headless_painter(4, 2) else {
+		return;
+	};
+	let red = SOLID_TRIANGLE.replace("0.0, 1.0, 0.0", "1.0, 0.0, 0.0");
+	let masked = quad_sketch(
+		&mut p,
+		SOLID_TRIANGLE,
+		-1.0,
+		0.0,
+		&SketchProps {
+			stencil: Some(Stencil::write(1)),
+			..default()
+		},
+	);
+	let unmasked = quad_sketch(&mut p, &red, 0.0, 1.0, &default());
+
+	// darkens the green of the sketch inside the mask
+	let darken = "
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+
+@fragment
+fn main(@location(0) coord: vec2<f32>) -> @location(0) vec4<f32> {
+	let color = textureSample(tex, tex_sampler, coord);
+	return vec4<f32>(color.r, color.g * 0.5, 1.0, 1.0);
+}
+";
+	let tex_type = p.uniform_type_tex_2d_frag();
+	let effect = wgsl_effect(
+		&mut p,
+		darken,
+		&[&tex_type],
+		&EffectProps {
+			stencil: Some(Stencil::equal(1)),
+			..default()
+		},
+	);
+	let layer = p.layer_create(&LayerProps {
+		sketches: vec![masked, unmasked],
+		effects: vec![effect],
+		format: Some(wgpu::TextureFormat::Rgba8Unorm),
+		depth_format: Some(wgpu::TextureFormat::Depth24PlusStencil8),
+		clear_color: Some(wgpu::Color::BLUE),
+		..default()
+	});
+	let target = layer.get_sketch_uniform(&mut p);
+	p.effect_set_uniform(&effect, 0, target.uniform);
+	p.paint(&layer).unwrap();
+
+	let pixels = p.read_layer_pixels(&layer);
+	for (i, pixel) in pixels.chunks(4).enumerate() {
+		if i % 4 < 2 {
+			assert_eq!(pixel[0], 0);
+			assert!(pixel[1].abs_diff(128) <= 1);
+			assert_eq!(&pixel[2..], [255, 255]);
+		} else {
+			// kept outside of the mask, instead of cleared by the effect
+			assert_eq!(pixel, [255, 0, 0, 255]);
+		}
+	}
+}
+
+#[test]
+fn draw_rejects_stencil_sketch() {
+	let Some(mut p) = headless_painter(2, 2) else {
+		return;
+	};
+	let sketch = triangle_sketch(
+		&mut p,
+		&SketchProps {
+			stencil: Some(Stencil::write(1)),
+			..default()
+		},
+	);
+	let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| p.draw(&sketch)))
+		.expect_err("drawing a stencil sketch to the window");
+	let message = panic
+		.downcast_ref::<&str>()
+		.copied()
+		.or_else(|| panic.downcast_ref::<String>().map(|m| m.as_str()))
+		.unwrap_or_default();
+	assert!(message.contains("stencil"));
+}
+
+/// Renders to a window owned by the test, like a host application embedding the painter.
+/// Skipped where no display is available.
+#[cfg(target_os = "linux")]
+#[test]
+fn attach_to_host_window() {
+	use winit::{event_loop::EventLoop, platform::x11::EventLoopBuilderExtX11};
+
+	let mut builder = EventLoop::builder();
+	builder.with_any_thread(true);
+	let Ok(event_loop) = builder.build() else {
+		eprintln!("no display available, skipping window test");
+		return;
+	};
+	#[allow(deprecated)]
+	let window = event_loop
+		.create_window(winit::window::Window::default_attributes().with_visible(false))
+		.unwrap();
+
+	// Safety: the painter is dropped at the end of the block, before the window.
+	{
+		let mut p = unsafe { Painter::attach(&window) };
+		let layer = effect_layer(&mut p, FILL_RED, default());
+		p.request_next_frame();
+		p.paint(&layer).unwrap();
+		p.show(&layer).unwrap();
+
+		p.resize(winit::dpi::PhysicalSize::new(8, 6));
+		assert_eq!(layer.current_size(&p), (8, 6));
+		p.paint(&layer).unwrap();
+		p.show(&layer).unwrap();
+	}
+}
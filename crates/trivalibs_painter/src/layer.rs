@@ -1,15 +1,18 @@
 use super::{
-	effect::Effect,
-	painter::UniformType,
+	effect::{Effect, EffectPass},
+	painter::{clamp_range, UniformType},
 	sketch::Sketch,
-	texture::{Texture, Texture2DProps, TextureDepthProps, UniformTex2D},
+	texture::{
+		SamplerProps, Texture, Texture2DProps, TextureDepthProps, UniformTex2D,
+		DEFAULT_DEPTH_FORMAT,
+	},
 	uniform::Uniform,
 	Painter,
 };
 use std::collections::BTreeMap;
 use trivalibs_core::utils::default;
 
-fn map_format_to_u8(format: wgpu::TextureFormat) -> u8 {
+pub(crate) fn map_format_to_u8(format: wgpu::TextureFormat) -> u8 {
 	match format {
 		wgpu::TextureFormat::R8Unorm => 0,
 		wgpu::TextureFormat::R8Snorm => 1,
@@ -92,13 +95,139 @@ fn map_format_to_u8(format: wgpu::TextureFormat) -> u8 {
 	}
 }
 
+/// Assigns a target to each effect, given the effects output formats and passes.
+/// Returns the format and scale of all layer targets, starting with the full size layer format,
+/// and the target index of each effect.
+/// Effects with a pass always get a new target.
+/// Otherwise effects without output format render into the current target,
+/// and effects with a different format switch to a target of that format and the current scale.
+fn effect_targets(
+	layer_format: wgpu::TextureFormat,
+	effects: &[(Option<wgpu::TextureFormat>, Option<EffectPass>)],
+) -> (Vec<EffectPass>, Vec<usize>) {
+	let mut passes = vec![EffectPass {
+		scale: 1.0,
+		format: layer_format,
+	}];
+	let mut targets = Vec::with_capacity(effects.len());
+	let mut current = 0;
+
+	for (format, pass) in effects {
+		if let Some(pass) = pass {
+			passes.push(*pass);
+			current = passes.len() - 1;
+		} else if let Some(format) = format {
+			let pass = EffectPass {
+				format: *format,
+				scale: passes[current].scale,
+			};
+			if pass != passes[current] {
+				current = passes.iter().position(|p| *p == pass).unwrap_or_else(|| {
+					passes.push(pass);
+					passes.len() - 1
+				});
+			}
+		}
+		targets.push(current);
+	}
+
+	(passes, targets)
+}
+
+/// Size of a layer target with the given scale, at least one pixel.
+fn scaled_size(width: u32, height: u32, scale: f32) -> (u32, u32) {
+	let scaled = |size: u32| ((size as f32 * scale).round() as u32).max(1);
+	(scaled(width), scaled(height))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LayerError {
+	/// The requested depth format has no depth aspect.
+	NonDepthFormat(wgpu::TextureFormat),
+	/// Sketches or effects of the layer use a stencil test, but the depth format has no stencil aspect.
+	NonStencilFormat(wgpu::TextureFormat),
+	/// An effect with stencil test renders into a scaled target, or the layer is multisampled,
+	/// so the target does not match the depth stencil texture of the layer.
+	StencilEffectTarget,
+}
+
+impl std::fmt::Display for LayerError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			LayerError::NonDepthFormat(format) => {
+				write!(f, "{:?} is not a depth texture format", format)
+			}
+			LayerError::NonStencilFormat(format) => {
+				write!(
+					f,
+					"stencil tests need a depth format with stencil, got {:?}",
+					format
+				)
+			}
+			LayerError::StencilEffectTarget => {
+				write!(
+					f,
+					"effects with stencil test need a full size target and a layer without multisampling"
+				)
+			}
+		}
+	}
+}
+
+impl std::error::Error for LayerError {}
+
+/// The requested depth format of a layer, or the default depth format.
+fn depth_format(format: Option<wgpu::TextureFormat>) -> Result<wgpu::TextureFormat, LayerError> {
+	match format {
+		None => Ok(DEFAULT_DEPTH_FORMAT),
+		Some(format) if format.has_depth_aspect() => Ok(format),
+		Some(format) => Err(LayerError::NonDepthFormat(format)),
+	}
+}
+
+/// Checks that the depth texture of a layer supports the stencil tests of its sketches and effects.
+/// `stencil_effect_scales` are the target scales of all effects with a stencil test.
+fn validate_stencil(
+	depth_format: wgpu::TextureFormat,
+	multisampled: bool,
+	sketch_stencil: bool,
+	stencil_effect_scales: &[f32],
+) -> Result<(), LayerError> {
+	if !sketch_stencil && stencil_effect_scales.is_empty() {
+		return Ok(());
+	}
+	if !depth_format.has_stencil_aspect() {
+		return Err(LayerError::NonStencilFormat(depth_format));
+	}
+	if !stencil_effect_scales.is_empty()
+		&& (multisampled || stencil_effect_scales.iter().any(|s| *s != 1.0))
+	{
+		return Err(LayerError::StencilEffectTarget);
+	}
+	Ok(())
+}
+
 pub(crate) struct LayerStorage {
 	pub target_textures: Vec<Texture>,
-	pub target_uniforms: Vec<UniformTex2D>,
+	/// Size of each target texture relative to the layer size.
+	pub target_scales: Vec<f32>,
+	/// Cached uniforms by target index.
+	pub target_uniforms: BTreeMap<usize, UniformTex2D>,
+	/// Cached uniforms of the output target, reinterpreted as its sRGB or linear counterpart format.
+	pub output_view_uniforms: BTreeMap<u8, UniformTex2D>,
 	pub sketches: Vec<Sketch>,
 	pub depth_texture: Option<Texture>,
+	/// Multisampled render target of multisampled layers, that is resolved into the first target texture.
+	pub multisampled_texture: Option<Texture>,
 	// pub depth_uniform: Option<UniformTex2D>,
 	pub effects: Vec<Effect>,
+	/// Index into target_textures for each effect.
+	pub effect_targets: Vec<usize>,
+	/// Index into target_textures of the final layer output.
+	pub output_target: usize,
+	/// Index into target_textures of the target that stencil effects render into,
+	/// by the index of the target they are composited into.
+	pub stencil_targets: BTreeMap<usize, usize>,
 	pub width: u32,
 	pub height: u32,
 	pub use_window_size: bool,
@@ -107,8 +236,12 @@ pub(crate) struct LayerStorage {
 	pub pipeline_key: Vec<u8>,
 	pub format: wgpu::TextureFormat,
 	pub multisampled: bool,
+	pub depth_prepass: bool,
+	pub depth_format: wgpu::TextureFormat,
+	pub sampler: SamplerProps,
 }
 
+#[derive(Debug, PartialEq)]
 pub struct LayerProps {
 	pub sketches: Vec<Sketch>,
 	pub effects: Vec<Effect>,
@@ -119,6 +252,18 @@ pub struct LayerProps {
 	pub binding_visibility: wgpu::ShaderStages,
 	pub uniforms: BTreeMap<u32, Uniform>,
 	pub multisampled: bool,
+	/// Renders the depth of all depth tested sketches first, and shades only their visible fragments
+	/// afterwards. Reduces overdraw for expensive fragment shaders.
+	/// The prepass skips the fragment stage, except for shades that contain `discard`,
+	/// whose fragment shader runs in both passes, so that discarded fragments don't write depth.
+	pub depth_prepass: bool,
+	/// Format of the depth texture, e.g. `Depth32Float` for precise depth sampling,
+	/// or a format with stencil for sketches and effects with stencil test, e.g. `Depth24PlusStencil8`.
+	/// Defaults to `Depth24Plus`. Must be a depth format.
+	pub depth_format: Option<wgpu::TextureFormat>,
+	/// Sampler used when other shaders read the layer targets, e.g. [`SamplerProps::nearest`]
+	/// for pixelated upscaling of a small layer.
+	pub sampler: SamplerProps,
 }
 
 impl Default for LayerProps {
@@ -133,15 +278,121 @@ impl Default for LayerProps {
 			binding_visibility: wgpu::ShaderStages::FRAGMENT,
 			clear_color: None,
 			multisampled: false,
+			depth_prepass: false,
+			depth_format: None,
+			sampler: default(),
 		}
 	}
 }
 
-#[derive(Clone, Copy)]
+impl LayerProps {
+	pub fn builder() -> LayerPropsBuilder {
+		LayerPropsBuilder(default())
+	}
+}
+
+/// Fluent alternative to spreading [`LayerProps::default`] into a struct literal.
+pub struct LayerPropsBuilder(LayerProps);
+
+impl LayerPropsBuilder {
+	pub fn sketches(mut self, sketches: Vec<Sketch>) -> Self {
+		self.0.sketches = sketches;
+		self
+	}
+
+	pub fn effects(mut self, effects: Vec<Effect>) -> Self {
+		self.0.effects = effects;
+		self
+	}
+
+	pub fn width(mut self, width: u32) -> Self {
+		self.0.width = width;
+		self
+	}
+
+	pub fn height(mut self, height: u32) -> Self {
+		self.0.height = height;
+		self
+	}
+
+	pub fn format(mut self, format: wgpu::TextureFormat) -> Self {
+		self.0.format = Some(format);
+		self
+	}
+
+	pub fn clear_color(mut self, color: wgpu::Color) -> Self {
+		self.0.clear_color = Some(color);
+		self
+	}
+
+	pub fn binding_visibility(mut self, visibility: wgpu::ShaderStages) -> Self {
+		self.0.binding_visibility = visibility;
+		self
+	}
+
+	pub fn uniforms(mut self, uniforms: BTreeMap<u32, Uniform>) -> Self {
+		self.0.uniforms = uniforms;
+		self
+	}
+
+	pub fn multisampled(mut self, multisampled: bool) -> Self {
+		self.0.multisampled = multisampled;
+		self
+	}
+
+	pub fn depth_prepass(mut self, depth_prepass: bool) -> Self {
+		self.0.depth_prepass = depth_prepass;
+		self
+	}
+
+	pub fn depth_format(mut self, format: wgpu::TextureFormat) -> Self {
+		self.0.depth_format = Some(format);
+		self
+	}
+
+	pub fn sampler(mut self, sampler: SamplerProps) -> Self {
+		self.0.sampler = sampler;
+		self
+	}
+
+	pub fn build(self) -> LayerProps {
+		self.0
+	}
+}
+
+/// Geometry drawn by the sketches of a layer, see [`Layer::geometry_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LayerGeometryStats {
+	/// Vertices of the forms of all sketches, counted once per sketch.
+	/// Only the draw range is counted for forms without indices.
+	pub total_vertices: u32,
+	/// Indices in the draw ranges of all sketches, counted once per sketch.
+	pub total_indices: u32,
+	/// One call per sketch instance, and one per effect.
+	pub draw_calls: usize,
+}
+
+impl LayerGeometryStats {
+	fn add_sketch(&mut self, vertices: u32, indices: u32, instances: usize) {
+		self.total_vertices += vertices;
+		self.total_indices += indices;
+		self.draw_calls += instances.max(1);
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Layer(pub(crate) usize);
 
 impl Layer {
+	/// Panics if the props are invalid, see [`Layer::try_new`].
 	pub fn new(painter: &mut Painter, props: &LayerProps) -> Self {
+		Self::try_new(painter, props).unwrap_or_else(|e| panic!("{}", e))
+	}
+
+	/// Creates a layer, or returns an error if `depth_format` is not a depth format,
+	/// or does not support the stencil tests of the sketches and effects.
+	pub fn try_new(painter: &mut Painter, props: &LayerProps) -> Result<Self, LayerError> {
+		let depth_format = depth_format(props.depth_format)?;
 		let use_window_size = props.width == 0 || props.height == 0;
 		let width = if use_window_size {
 			painter.config.width
@@ -154,23 +405,68 @@ impl Layer {
 			props.height
 		};
 
-		let mut target_texture = Vec::with_capacity(1);
-
 		let format = props.format.unwrap_or(painter.config.format);
 
-		target_texture.push(Texture::create_2d(
-			painter,
-			&Texture2DProps {
-				width,
-				height,
-				format,
-				usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-					| wgpu::TextureUsages::TEXTURE_BINDING,
-			},
-		));
-		let len = target_texture.len();
+		let effect_outputs = props
+			.effects
+			.iter()
+			.map(|e| {
+				let e = &painter.effects[e.0];
+				(e.output_format, e.pass)
+			})
+			.collect::<Vec<_>>();
+		let (mut target_passes, effect_targets) = effect_targets(format, &effect_outputs);
+
+		let sketch_stencil = props
+			.sketches
+			.iter()
+			.any(|s| painter.sketches[s.0].stencil.is_some());
+		let stencil_effect_scales = props
+			.effects
+			.iter()
+			.zip(&effect_targets)
+			.filter(|(e, _)| painter.effects[e.0].stencil.is_some())
+			.map(|(_, target)| target_passes[*target].scale)
+			.collect::<Vec<_>>();
+		validate_stencil(
+			depth_format,
+			props.multisampled,
+			sketch_stencil,
+			&stencil_effect_scales,
+		)?;
+
+		// Effects with stencil test render into an additional target of the same format,
+		// that is composited into their target through the mask.
+		let mut stencil_targets = BTreeMap::new();
+		for (effect, target) in props.effects.iter().zip(&effect_targets) {
+			if painter.effects[effect.0].stencil.is_some() && !stencil_targets.contains_key(target)
+			{
+				target_passes.push(target_passes[*target]);
+				stencil_targets.insert(*target, target_passes.len() - 1);
+			}
+		}
 
-		let mut use_depth: bool = false;
+		let target_texture = target_passes
+			.iter()
+			.map(|pass| {
+				let (width, height) = scaled_size(width, height, pass.scale);
+				Texture::create_2d(
+					painter,
+					&Texture2DProps {
+						width,
+						height,
+						format: pass.format,
+						usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+							| wgpu::TextureUsages::TEXTURE_BINDING
+							| wgpu::TextureUsages::COPY_SRC,
+						mip_levels: 1,
+					},
+				)
+			})
+			.collect::<Vec<_>>();
+		let output_target = effect_targets.last().copied().unwrap_or(0);
+
+		let mut use_depth: bool = sketch_stencil || !stencil_effect_scales.is_empty();
 		for s in &props.sketches {
 			let sketch = &painter.sketches[s.0];
 			if sketch.depth_test {
@@ -179,46 +475,178 @@ impl Layer {
 			}
 		}
 
-		let depth_texture =
-			use_depth.then(|| Texture::create_depth(painter, &TextureDepthProps { width, height }));
+		let depth_texture = use_depth.then(|| {
+			Texture::create_depth(
+				painter,
+				&TextureDepthProps {
+					multisampled: props.multisampled,
+					format: depth_format,
+					..TextureDepthProps::new(width, height)
+				},
+			)
+		});
+
+		let multisampled_texture = props.multisampled.then(|| {
+			Texture::create_multisampled(
+				painter,
+				&Texture2DProps {
+					width,
+					height,
+					format,
+					usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+					mip_levels: 1,
+				},
+			)
+		});
 
-		let pipeline_key = vec![map_format_to_u8(format), props.multisampled as u8];
+		let pipeline_key = vec![
+			map_format_to_u8(format),
+			props.multisampled as u8,
+			map_format_to_u8(depth_format),
+			use_depth as u8,
+		];
 
 		let storage = LayerStorage {
 			width,
 			height,
 			target_textures: target_texture,
-			target_uniforms: Vec::with_capacity(len),
+			target_scales: target_passes.iter().map(|p| p.scale).collect(),
+			target_uniforms: BTreeMap::new(),
+			output_view_uniforms: BTreeMap::new(),
 			sketches: props.sketches.clone(),
 			effects: props.effects.clone(),
+			effect_targets,
+			output_target,
+			stencil_targets,
 			depth_texture,
+			multisampled_texture,
 			use_window_size,
 			clear_color: props.clear_color,
 			binding_visibility: props.binding_visibility,
 			format,
 			pipeline_key,
 			multisampled: props.multisampled,
+			depth_prepass: props.depth_prepass,
+			depth_format,
+			sampler: props.sampler,
 		};
 
 		painter.layers.push(storage);
-		Layer(painter.layers.len() - 1)
+		Ok(Layer(painter.layers.len() - 1))
 	}
 
+	/// Uniform of the layer output.
+	/// If effects changed the output format, this is the target of the last effect.
 	pub fn get_uniform(&self, painter: &mut Painter) -> UniformTex2D {
-		if let Some(uniform) = painter.layers[self.0].target_uniforms.get(0) {
+		let output_target = painter.layers[self.0].output_target;
+		self.get_target_uniform(painter, output_target)
+	}
+
+	/// Uniform of the full size target the sketches render into, in the layer format.
+	/// Effects that neither have a pass nor change the format render into it as well.
+	/// Use it as input of effects that change the output format, e.g. to tonemap an HDR layer.
+	/// Like [`Layer::get_uniform`], it needs to be fetched again after resizing the layer.
+	pub fn get_sketch_uniform(&self, painter: &mut Painter) -> UniformTex2D {
+		self.get_target_uniform(painter, 0)
+	}
+
+	/// Uniform of the target, that the effect at `effect_index` in the layer renders into.
+	/// Bind it to later effects with [`Painter::effect_set_uniform`], e.g. to chain effect passes.
+	/// Like [`Layer::get_uniform`], it needs to be fetched again after resizing the layer.
+	pub fn get_effect_uniform(&self, painter: &mut Painter, effect_index: usize) -> UniformTex2D {
+		let target = painter.layers[self.0].effect_targets[effect_index];
+		self.get_target_uniform(painter, target)
+	}
+
+	/// Uniform of the layer output, sampled as `format`,
+	/// which is the output format or its sRGB or linear counterpart.
+	/// Falls back to the output format, if the device can't reinterpret it.
+	pub(crate) fn get_output_view_uniform(
+		&self,
+		painter: &mut Painter,
+		format: wgpu::TextureFormat,
+	) -> UniformTex2D {
+		let l = &painter.layers[self.0];
+		let texture = l.target_textures[l.output_target];
+		if painter.textures[texture.0].texture.format() == format {
+			return self.get_uniform(painter);
+		}
+		if let Some(uniform) = l.output_view_uniforms.get(&map_format_to_u8(format)) {
 			return *uniform;
 		}
-		let visibility = painter.layers[self.0].binding_visibility;
-		let uniform = UniformTex2D::get_layout(painter, visibility).create_tex2d(
-			painter,
-			painter.layers[self.0].target_textures[0],
-			&painter.sampler_create(&default()),
-		);
 
-		painter.layers[self.0].target_uniforms.push(uniform);
+		let layout = UniformTex2D::get_layout_with(painter, l.binding_visibility, &l.sampler);
+		let sampler = painter.sampler_create(&l.sampler);
+		let uniform = match texture.reinterpreted_view(painter, format, &layout, &sampler) {
+			Some(uniform) => uniform,
+			None => {
+				log::warn!(
+					"Can't sample the layer output as {:?}, showing it without sRGB conversion",
+					format
+				);
+				self.get_uniform(painter)
+			}
+		};
+
+		painter.layers[self.0]
+			.output_view_uniforms
+			.insert(map_format_to_u8(format), uniform);
+		uniform
+	}
+
+	pub(crate) fn get_target_uniform(&self, painter: &mut Painter, target: usize) -> UniformTex2D {
+		if let Some(uniform) = painter.layers[self.0].target_uniforms.get(&target) {
+			return *uniform;
+		}
+		let l = &painter.layers[self.0];
+		let uniform = UniformTex2D::get_layout_with(painter, l.binding_visibility, &l.sampler)
+			.create_tex2d(
+				painter,
+				painter.layers[self.0].target_textures[target],
+				&painter.sampler_create(&painter.layers[self.0].sampler),
+			);
+
+		painter.layers[self.0]
+			.target_uniforms
+			.insert(target, uniform);
 		uniform
 	}
 
+	/// Sums the vertex and index counts drawn by the sketches, and the draw calls of a paint.
+	/// Forms used by several sketches are counted for each of them.
+	/// Indexed forms count all their vertices, and only the indices in the draw range.
+	pub fn geometry_stats(&self, painter: &Painter) -> LayerGeometryStats {
+		let l = &painter.layers[self.0];
+		let mut stats = LayerGeometryStats {
+			draw_calls: l.effects.len(),
+			..default()
+		};
+		for sketch in &l.sketches {
+			let sketch = &painter.sketches[sketch.0];
+			let form = &painter.forms[sketch.form.0];
+			let (vertices, indices) = if form.index_buffer.is_some() {
+				let indices = clamp_range(&sketch.draw_range, form.index_count);
+				(form.vertex_count, indices.len() as u32)
+			} else {
+				let vertices = clamp_range(&sketch.draw_range, form.vertex_count);
+				(vertices.len() as u32, 0)
+			};
+			stats.add_sketch(vertices, indices, sketch.instances.len());
+		}
+		stats
+	}
+
+	/// Whether the layer was created or resized with a zero size, and therefore follows the window size.
+	pub fn tracks_window_size(&self, painter: &Painter) -> bool {
+		painter.layers[self.0].use_window_size
+	}
+
+	/// The current width and height of the layer textures.
+	pub fn current_size(&self, painter: &Painter) -> (u32, u32) {
+		let storage = &painter.layers[self.0];
+		(storage.width, storage.height)
+	}
+
 	pub fn resize(&mut self, painter: &mut Painter, width: u32, height: u32) {
 		let use_window_size = width == 0 || height == 0;
 		let width = if use_window_size {
@@ -233,20 +661,26 @@ impl Layer {
 		};
 
 		let storage = &mut painter.layers[self.0];
+		storage.use_window_size = use_window_size;
 		if storage.width == width && storage.height == height {
 			return;
 		}
 
 		storage.width = width;
 		storage.height = height;
-		storage.use_window_size = use_window_size;
 		storage.target_uniforms.clear();
+		storage.output_view_uniforms.clear();
 
 		let targets = storage.target_textures.clone();
+		let scales = storage.target_scales.clone();
 		let depth_texture = storage.depth_texture.clone();
+		let multisampled_texture = storage.multisampled_texture;
+		let multisampled = storage.multisampled;
+		let depth_format = storage.depth_format;
 
-		for texture in targets.iter() {
+		for (texture, scale) in targets.iter().zip(scales) {
 			let format = painter.textures[texture.0].texture.format();
+			let (width, height) = scaled_size(width, height, scale);
 			texture.replace_2d(
 				painter,
 				&Texture2DProps {
@@ -254,13 +688,300 @@ impl Layer {
 					height,
 					format,
 					usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-						| wgpu::TextureUsages::TEXTURE_BINDING,
+						| wgpu::TextureUsages::TEXTURE_BINDING
+						| wgpu::TextureUsages::COPY_SRC,
+					mip_levels: 1,
+				},
+			);
+		}
+
+		if let Some(texture) = multisampled_texture {
+			let format = painter.textures[texture.0].texture.format();
+			texture.replace_2d(
+				painter,
+				&Texture2DProps {
+					width,
+					height,
+					format,
+					usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+					mip_levels: 1,
 				},
 			);
 		}
 
 		if let Some(depth_texture) = depth_texture {
-			depth_texture.replace_depth(painter, &TextureDepthProps { width, height });
+			depth_texture.replace_depth(
+				painter,
+				&TextureDepthProps {
+					multisampled,
+					format: depth_format,
+					..TextureDepthProps::new(width, height)
+				},
+			);
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		effect::Effect,
+		sketch::{Sketch, SketchProps},
+	};
+
+	#[test]
+	fn builder_equals_struct_literal() {
+		let from_builder = LayerProps::builder()
+			.sketches(vec![Sketch(0), Sketch(1)])
+			.effects(vec![Effect(2)])
+			.clear_color(wgpu::Color::WHITE)
+			.multisampled(true)
+			.depth_prepass(true)
+			.depth_format(wgpu::TextureFormat::Depth32Float)
+			.build();
+
+		let from_literal = LayerProps {
+			sketches: vec![Sketch(0), Sketch(1)],
+			effects: vec![Effect(2)],
+			clear_color: Some(wgpu::Color::WHITE),
+			multisampled: true,
+			depth_prepass: true,
+			depth_format: Some(wgpu::TextureFormat::Depth32Float),
+			..default()
+		};
+
+		assert_eq!(from_builder, from_literal);
+		assert_eq!(LayerProps::builder().build(), LayerProps::default());
+	}
+
+	#[test]
+	fn effect_output_formats() {
+		use wgpu::TextureFormat::{Rgba16Float, Rgba8UnormSrgb};
+
+		let formats_of =
+			|passes: Vec<EffectPass>| passes.iter().map(|p| p.format).collect::<Vec<_>>();
+		let outputs = |formats: &[Option<wgpu::TextureFormat>]| {
+			formats.iter().map(|f| (*f, None)).collect::<Vec<_>>()
+		};
+
+		let (passes, targets) = effect_targets(Rgba16Float, &[]);
+		assert_eq!(formats_of(passes), vec![Rgba16Float]);
+		assert!(targets.is_empty());
+
+		let (passes, targets) = effect_targets(
+			Rgba16Float,
+			&outputs(&[None, Some(Rgba16Float), Some(Rgba8UnormSrgb), None]),
+		);
+		assert_eq!(formats_of(passes), vec![Rgba16Float, Rgba8UnormSrgb]);
+		assert_eq!(targets, vec![0, 0, 1, 1]);
+
+		let (passes, targets) = effect_targets(
+			Rgba16Float,
+			&outputs(&[
+				Some(Rgba8UnormSrgb),
+				Some(Rgba16Float),
+				Some(Rgba8UnormSrgb),
+			]),
+		);
+		assert_eq!(formats_of(passes), vec![Rgba16Float, Rgba8UnormSrgb]);
+		assert_eq!(targets, vec![1, 0, 1]);
+	}
+
+	#[test]
+	fn depth_formats() {
+		use wgpu::TextureFormat::{Depth24PlusStencil8, Depth32Float, Rgba8Unorm, Stencil8};
+
+		assert_eq!(depth_format(None), Ok(DEFAULT_DEPTH_FORMAT));
+		assert_eq!(depth_format(Some(Depth32Float)), Ok(Depth32Float));
+		assert_eq!(
+			depth_format(Some(Depth24PlusStencil8)),
+			Ok(Depth24PlusStencil8)
+		);
+		assert_eq!(
+			depth_format(Some(Rgba8Unorm)),
+			Err(LayerError::NonDepthFormat(Rgba8Unorm))
+		);
+		assert_eq!(
+			depth_format(Some(Stencil8)),
+			Err(LayerError::NonDepthFormat(Stencil8))
+		);
+	}
+
+	#[test]
+	fn stencil_validation() {
+		use wgpu::TextureFormat::{Depth24Plus, Depth24PlusStencil8};
+
+		assert_eq!(validate_stencil(Depth24Plus, true, false, &[]), Ok(()));
+		assert_eq!(
+			validate_stencil(Depth24PlusStencil8, false, true, &[1.0, 1.0]),
+			Ok(())
+		);
+		assert_eq!(
+			validate_stencil(Depth24Plus, false, true, &[]),
+			Err(LayerError::NonStencilFormat(Depth24Plus))
+		);
+		assert_eq!(
+			validate_stencil(Depth24Plus, false, false, &[1.0]),
+			Err(LayerError::NonStencilFormat(Depth24Plus))
+		);
+		assert_eq!(
+			validate_stencil(Depth24PlusStencil8, false, true, &[1.0, 0.5]),
+			Err(LayerError::StencilEffectTarget)
+		);
+		assert_eq!(
+			validate_stencil(Depth24PlusStencil8, true, true, &[1.0]),
+			Err(LayerError::StencilEffectTarget)
+		);
+		// multisampled sketches can still write and test the stencil
+		assert_eq!(
+			validate_stencil(Depth24PlusStencil8, true, true, &[]),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn effect_pass_targets() {
+		use wgpu::TextureFormat::{Rgba16Float, Rgba8UnormSrgb};
+
+		let pass = |scale| EffectPass {
+			scale,
+			format: Rgba16Float,
+		};
+		// bright pass, two downsample levels, and a full size combine pass
+		let (passes, targets) = effect_targets(
+			Rgba8UnormSrgb,
+			&[
+				(None, Some(pass(0.5))),
+				(None, Some(pass(0.25))),
+				(None, None),
+				(None, Some(pass(1.0))),
+			],
+		);
+		assert_eq!(
+			passes,
+			vec![
+				EffectPass {
+					scale: 1.0,
+					format: Rgba8UnormSrgb
+				},
+				pass(0.5),
+				pass(0.25),
+				pass(1.0)
+			]
+		);
+		assert_eq!(targets, vec![1, 2, 2, 3]);
+
+		let sizes = passes
+			.iter()
+			.map(|p| scaled_size(800, 600, p.scale))
+			.collect::<Vec<_>>();
+		assert_eq!(sizes, vec![(800, 600), (400, 300), (200, 150), (800, 600)]);
+		assert_eq!(scaled_size(3, 1, 0.25), (1, 1));
+
+		// output formats without pass stay at the scale of the current target
+		let (passes, targets) = effect_targets(
+			Rgba8UnormSrgb,
+			&[(None, Some(pass(0.5))), (Some(Rgba8UnormSrgb), None)],
+		);
+		assert_eq!(
+			passes[2],
+			EffectPass {
+				scale: 0.5,
+				format: Rgba8UnormSrgb
+			}
+		);
+		assert_eq!(targets, vec![1, 2]);
+	}
+
+	#[test]
+	fn geometry_stats() {
+		let mut stats = LayerGeometryStats::default();
+		// triangle without indices, and an indexed quad
+		stats.add_sketch(3, 0, 0);
+		stats.add_sketch(4, 6, 0);
+		assert_eq!(
+			stats,
+			LayerGeometryStats {
+				total_vertices: 7,
+				total_indices: 6,
+				draw_calls: 2,
+			}
+		);
+
+		stats.add_sketch(4, 6, 3);
+		assert_eq!(stats.total_vertices, 11);
+		assert_eq!(stats.draw_calls, 5);
+	}
+
+	#[test]
+	fn geometry_stats_of_draw_ranges() {
+		use crate::gpu_tests::{quad_sketch, triangle_sketch, SOLID_TRIANGLE};
+
+		let Some(mut p) = crate::gpu_tests::headless_painter(2, 2) else {
+			return;
+		};
+		// first triangle of an indexed quad, and a range exceeding the triangle
+		let quad = quad_sketch(
+			&mut p,
+			SOLID_TRIANGLE,
+			-1.0,
+			1.0,
+			&SketchProps {
+				draw_range: Some(0..3),
+				..default()
+			},
+		);
+		let triangle = triangle_sketch(
+			&mut p,
+			&SketchProps {
+				draw_range: Some(1..9),
+				..default()
+			},
+		);
+		let full_quad = quad_sketch(&mut p, SOLID_TRIANGLE, -1.0, 1.0, &default());
+		let layer = p.layer_create(&LayerProps {
+			sketches: vec![quad, triangle, full_quad],
+			..default()
+		});
+		assert_eq!(
+			layer.geometry_stats(&p),
+			LayerGeometryStats {
+				total_vertices: 4 + 2 + 4,
+				total_indices: 3 + 6,
+				draw_calls: 3,
+			}
+		);
+	}
+
+	#[test]
+	fn window_size_tracking() {
+		let Some(mut p) = crate::gpu_tests::headless_painter(4, 2) else {
+			return;
+		};
+		let mut layer = p.layer_create(&default());
+		assert!(layer.tracks_window_size(&p));
+		assert_eq!(layer.current_size(&p), (4, 2));
+
+		p.resize(winit::dpi::PhysicalSize::new(8, 6));
+		assert_eq!(layer.current_size(&p), (8, 6));
+
+		layer.resize(&mut p, 3, 3);
+		assert!(!layer.tracks_window_size(&p));
+		p.resize(winit::dpi::PhysicalSize::new(10, 10));
+		assert_eq!(layer.current_size(&p), (3, 3));
+
+		// a zero size follows the window again
+		layer.resize(&mut p, 0, 0);
+		assert!(layer.tracks_window_size(&p));
+		assert_eq!(layer.current_size(&p), (10, 10));
+
+		let fixed = p.layer_create(&LayerProps {
+			width: 5,
+			height: 7,
+			..default()
+		});
+		assert!(!fixed.tracks_window_size(&p));
+		assert_eq!(fixed.current_size(&p), (5, 7));
+	}
+}
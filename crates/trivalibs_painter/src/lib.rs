@@ -1,15 +1,16 @@
+use frame_pacer::FramePacer;
 use notify::Watcher;
 use std::{sync::Arc, time::Instant};
 use wgpu::SurfaceError;
 use winit::{
 	application::ApplicationHandler,
-	dpi::PhysicalSize,
-	event::{DeviceEvent, DeviceId, ElementState, KeyEvent, WindowEvent},
-	event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy},
+	event::{DeviceEvent, DeviceId, ElementState, KeyEvent, StartCause, WindowEvent},
+	event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy},
 	keyboard::{KeyCode, PhysicalKey},
 	window::{Window, WindowId},
 };
 
+pub use image;
 pub use wgpu;
 pub use winit;
 
@@ -17,22 +18,35 @@ pub mod painter;
 pub use painter::Painter;
 pub mod effect;
 pub mod form;
+mod frame_capture;
+mod frame_pacer;
+#[cfg(test)]
+mod gpu_tests;
 pub mod layer;
+pub mod render_graph;
+pub mod scene;
 pub mod shade;
 pub mod shaders;
 pub mod sketch;
+pub mod stencil;
 pub mod texture;
 pub mod uniform;
 
 pub trait CanvasApp<RenderState, UserEvent> {
 	fn init(&self, painter: &mut Painter) -> RenderState;
-	fn resize(&mut self, painter: &mut Painter, render_state: &mut RenderState);
 	fn update(&mut self, painter: &mut Painter, render_state: &mut RenderState, tpf: f32);
 	fn render(&self, painter: &mut Painter, render_state: &RenderState)
 		-> Result<(), SurfaceError>;
-	fn window_event(&mut self, event: WindowEvent, painter: &Painter);
-	fn device_event(&mut self, event: DeviceEvent, painter: &Painter);
-	fn user_event(&mut self, event: UserEvent, painter: &Painter);
+
+	/// Called after the window was resized. Layers with window size are resized already.
+	fn resize(&mut self, _painter: &mut Painter, _render_state: &mut RenderState) {}
+	fn window_event(&mut self, _event: WindowEvent, _painter: &Painter) {}
+	fn device_event(&mut self, _event: DeviceEvent, _painter: &Painter) {}
+	fn user_event(&mut self, _event: UserEvent, _painter: &Painter) {}
+
+	/// Called once when the event loop terminates, e.g. after the window was closed,
+	/// while the painter and its GPU resources are still valid. Use it to flush state to disk.
+	fn on_exit(&mut self, _painter: &mut Painter) {}
 }
 
 enum WindowState {
@@ -59,6 +73,8 @@ where
 	is_running: bool,
 	is_resizing: bool,
 	now: Instant,
+	frame_pacer: FramePacer,
+	frame_deferred: bool,
 }
 
 pub struct CanvasHandle<UserEvent>
@@ -147,6 +163,13 @@ where
 		let _ = event_loop.run_app(&mut app);
 	}
 
+	/// Limits the frame rate to `target_fps`. Frames requested before the frame time passed
+	/// are deferred, without blocking the event loop. `None` renders as fast as frames are requested.
+	pub fn with_target_fps(mut self, target_fps: Option<f32>) -> Self {
+		self.app.frame_pacer = FramePacer::new(target_fps);
+		self
+	}
+
 	pub fn get_handle(&self) -> CanvasHandle<UserEvent> {
 		CanvasHandle {
 			event_loop_proxy: self.app.event_loop_proxy.clone(),
@@ -184,6 +207,8 @@ pub fn create_canvas_app<
 		is_running: true,
 		is_resizing: false,
 		now: Instant::now(),
+		frame_pacer: FramePacer::default(),
+		frame_deferred: false,
 	};
 
 	return CanvasAppStarter {
@@ -302,6 +327,15 @@ where
 					}
 
 					WindowEvent::RedrawRequested => {
+						if self.is_running && !self.is_resizing {
+							if let Some(wait) = self.frame_pacer.remaining(self.now.elapsed()) {
+								// Redraw once the frame time passed, see `new_events`.
+								event_loop.set_control_flow(ControlFlow::wait_duration(wait));
+								self.frame_deferred = true;
+								return;
+							}
+						}
+
 						if self.is_running || self.is_resizing {
 							let elapsed = self.now.elapsed().as_secs_f32();
 							self.now = Instant::now();
@@ -316,10 +350,7 @@ where
 								Ok(_) => {}
 								// Reconfigure the surface if it's lost or outdated
 								Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-									painter.resize(PhysicalSize {
-										width: painter.config.width,
-										height: painter.config.height,
-									});
+									painter.reconfigure_surface();
 									self.app.resize(painter, render_state);
 								}
 								// The system is out of memory, we should probably quit
@@ -365,6 +396,18 @@ where
 		}
 	}
 
+	fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+		if let StartCause::ResumeTimeReached { .. } = cause {
+			if self.frame_deferred {
+				self.frame_deferred = false;
+				event_loop.set_control_flow(ControlFlow::Wait);
+				if let WindowState::Initialized(painter) = &self.state {
+					painter.request_next_frame();
+				}
+			}
+		}
+	}
+
 	fn device_event(
 		&mut self,
 		_event_loop: &ActiveEventLoop,
@@ -375,4 +418,13 @@ where
 			self.app.device_event(event, painter);
 		}
 	}
+
+	fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+		// Taking the painter out of the state guarantees a single call,
+		// and drops the device only after the app cleaned up.
+		let state = std::mem::replace(&mut self.state, WindowState::Uninitialized);
+		if let WindowState::Initialized(mut painter) = state {
+			self.app.on_exit(&mut painter);
+		}
+	}
 }
@@ -1,17 +1,29 @@
 use super::{
-	effect::{Effect, EffectProps, EffectStorage},
-	form::{Form, FormData, FormProps, FormStorage},
-	layer::{Layer, LayerProps, LayerStorage},
+	effect::{blend_key, Effect, EffectDebug, EffectProps, EffectStorage},
+	form::{Form, FormData, FormProps, FormStorage, SectionRange},
+	frame_capture::{CaptureEvent, FrameCapture},
+	layer::{map_format_to_u8, Layer, LayerProps, LayerStorage},
+	render_graph::RenderGraph,
 	shade::{AttribsFormat, Shade, ShadeEffectProps, ShadeProps, ShadeStorage},
-	shaders::FULL_SCREEN_QUAD,
-	sketch::{Sketch, SketchProps, SketchStorage},
-	texture::{SamplerProps, Texture, Texture2DProps, TextureStorage, UniformTex2D},
-	uniform::{get_uniform_layout_buffered, Mat3U, UniformBuffer, Vec3U},
+	shaders::{COPY_PIXELS, FULL_SCREEN_QUAD},
+	sketch::{Sketch, SketchDebug, SketchProps, SketchStorage},
+	stencil::Stencil,
+	texture::{
+		sample_count, ImageTextureProps, SamplerProps, Texture, Texture2DProps, TextureCubeProps,
+		TextureStorage, UniformTex2D, UniformTexCube, DEFAULT_DEPTH_FORMAT,
+	},
+	uniform::{
+		get_uniform_layout_buffered, uniform_bindings, Mat3U, Uniform, UniformBuffer,
+		UniformStorage, Vec3U,
+	},
 };
-use std::{collections::BTreeMap, sync::Arc};
+use std::{cell::RefCell, collections::BTreeMap, path::PathBuf, sync::Arc};
 use trivalibs_core::{
 	glam::{Mat3, Vec3},
-	rendering::RenderableBuffer,
+	rendering::{
+		buffered_geometry::{BufferedGeometry, GeometryError},
+		RenderableBuffer,
+	},
 	utils::default,
 };
 use wgpu::util::make_spirv;
@@ -19,6 +31,9 @@ use winit::window::Window;
 
 pub(crate) const FULL_SCREEN_TEXTURE_PIPELINE: &'static [u8] = &[0xff, 0xff];
 
+/// Pipeline key prefix of the pipelines compositing stencil effects through their mask.
+const STENCIL_COMPOSITE_PIPELINE: u8 = 0xfd;
+
 pub trait UniformType {
 	fn create_buff<T: bytemuck::Pod>(&self, painter: &mut Painter, data: T) -> UniformBuffer<T>;
 	fn create_mat3(&self, painter: &mut Painter, mat: Mat3) -> UniformBuffer<Mat3U>;
@@ -29,6 +44,12 @@ pub trait UniformType {
 		texture: Texture,
 		sampler: &wgpu::Sampler,
 	) -> UniformTex2D;
+	fn create_tex_cube(
+		&self,
+		painter: &mut Painter,
+		texture: Texture,
+		sampler: &wgpu::Sampler,
+	) -> UniformTexCube;
 	fn layout(&self) -> &wgpu::BindGroupLayout;
 }
 
@@ -54,47 +75,85 @@ impl UniformType for wgpu::BindGroupLayout {
 		UniformTex2D::new(painter, self, texture, sampler)
 	}
 
+	fn create_tex_cube(
+		&self,
+		painter: &mut Painter,
+		texture: Texture,
+		sampler: &wgpu::Sampler,
+	) -> UniformTexCube {
+		UniformTexCube::new(painter, self, texture, sampler)
+	}
+
 	fn layout(&self) -> &wgpu::BindGroupLayout {
 		self
 	}
 }
 
 pub struct Painter {
-	pub surface: wgpu::Surface<'static>,
+	surface: Option<wgpu::Surface<'static>>,
 	pub config: wgpu::SurfaceConfiguration,
 	pub adapter: wgpu::Adapter,
 	pub device: wgpu::Device,
 	pub queue: wgpu::Queue,
-	window: Arc<Window>,
+	window: Option<Arc<Window>>,
 	pub(crate) forms: Vec<FormStorage>,
 	pub(crate) shades: Vec<ShadeStorage>,
 	pub(crate) textures: Vec<TextureStorage>,
 	pub(crate) sketches: Vec<SketchStorage>,
 	pub(crate) effects: Vec<EffectStorage>,
 	pub(crate) layers: Vec<LayerStorage>,
-	pub(crate) bindings: Vec<wgpu::BindGroup>,
+	pub(crate) bindings: Vec<UniformStorage>,
 	pub(crate) pipelines: BTreeMap<Vec<u8>, wgpu::RenderPipeline>,
 	fullscreen_quad_shader: wgpu::ShaderModule,
+	capture: RefCell<Option<FrameCapture>>,
 }
 
 impl Painter {
+	/// Creates a painter rendering to `window`.
+	/// See [`Painter::attach`] to render to a window owned by a host application.
 	pub async fn new(window: Arc<Window>) -> Self {
-		let mut size = window.inner_size();
-		size.width = size.width.max(1);
-		size.height = size.height.max(1);
-
+		let size = window.inner_size();
 		let instance = wgpu::Instance::default();
-
 		let surface = instance.create_surface(window.clone()).unwrap();
+
+		Self::create(
+			instance,
+			Some(surface),
+			Some(window),
+			size.width,
+			size.height,
+		)
+		.await
+		.expect("Failed to find an appropriate adapter")
+	}
+
+	/// Creates a painter without window, e.g. for rendering tests or offline rendering.
+	/// Layers with window size are `width` x `height` pixels in `Rgba8UnormSrgb` format,
+	/// read them back with [`Painter::read_layer_pixels`].
+	/// Methods presenting to the window, like `draw` and `show`, panic on headless painters.
+	/// Returns `None` if no GPU adapter is available.
+	pub async fn headless(width: u32, height: u32) -> Option<Self> {
+		Self::create(wgpu::Instance::default(), None, None, width, height).await
+	}
+
+	async fn create(
+		instance: wgpu::Instance,
+		surface: Option<wgpu::Surface<'static>>,
+		window: Option<Arc<Window>>,
+		width: u32,
+		height: u32,
+	) -> Option<Self> {
+		let width = width.max(1);
+		let height = height.max(1);
+
 		let adapter = instance
 			.request_adapter(&wgpu::RequestAdapterOptions {
 				power_preference: wgpu::PowerPreference::default(),
 				force_fallback_adapter: false,
 				// Request an adapter which can render to our surface
-				compatible_surface: Some(&surface),
+				compatible_surface: surface.as_ref(),
 			})
-			.await
-			.expect("Failed to find an appropriate adapter");
+			.await?;
 
 		// Create the logical device and command queue
 		let (device, queue) = adapter
@@ -112,13 +171,25 @@ impl Painter {
 			.await
 			.expect("Failed to create device");
 
-		// We could also manually create a SurfaceConfiguration.
-		// See https://sotrh.github.io/learn-wgpu/beginner/tutorial2-surface/#state-new for example.
-		let config = surface
-			.get_default_config(&adapter, size.width, size.height)
-			.unwrap();
-
-		surface.configure(&device, &config);
+		let config = match &surface {
+			Some(surface) => {
+				let config = surface.get_default_config(&adapter, width, height).unwrap();
+				surface.configure(&device, &config);
+				config
+			}
+			// Headless painters never configure a surface,
+			// the configuration only holds the size and default format of layers.
+			None => wgpu::SurfaceConfiguration {
+				usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+				format: wgpu::TextureFormat::Rgba8UnormSrgb,
+				width,
+				height,
+				present_mode: wgpu::PresentMode::Fifo,
+				desired_maximum_frame_latency: 2,
+				alpha_mode: wgpu::CompositeAlphaMode::Auto,
+				view_formats: vec![],
+			},
+		};
 
 		let fullscreen_quad_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
 			label: None,
@@ -131,7 +202,7 @@ impl Painter {
 			adapter,
 			device,
 			queue,
-			window: window.clone(),
+			window,
 			forms: Vec::with_capacity(8),
 			shades: Vec::with_capacity(8),
 			textures: Vec::with_capacity(8),
@@ -141,6 +212,7 @@ impl Painter {
 			bindings: Vec::with_capacity(8),
 			pipelines: BTreeMap::new(),
 			fullscreen_quad_shader,
+			capture: RefCell::new(None),
 		};
 
 		let fullscreen_quad_pipeline_layout =
@@ -199,7 +271,7 @@ impl Painter {
 			fullscreen_quad_pipeline,
 		);
 
-		painter
+		Some(painter)
 	}
 
 	// form helpers
@@ -230,6 +302,24 @@ impl Painter {
 		Form::from_buffer(self, buffer, props)
 	}
 
+	/// Uploads many static geometries into a single form, and returns the draw range of each of them.
+	/// Returns an error if the geometries can't be merged, see [`BufferedGeometry::merge`].
+	pub fn merge_forms(
+		&mut self,
+		geometries: &[BufferedGeometry],
+		props: FormProps,
+	) -> Result<(Form, Vec<SectionRange>), GeometryError> {
+		let (merged, ranges) = BufferedGeometry::merge(geometries)?;
+		let index_count = merged.decoded_indices().map_or(0, |i| i.len() as u32);
+		let buffer = RenderableBuffer {
+			vertex_count: merged.buffer_vertex_count(),
+			vertex_buffer: merged.buffer,
+			index_buffer: merged.indices,
+			index_count,
+		};
+		Ok((Form::from_buffer(self, buffer, props), ranges))
+	}
+
 	// shade helpers
 
 	pub fn shade_create<Format: Into<AttribsFormat>, UType: UniformType>(
@@ -252,6 +342,19 @@ impl Painter {
 		Texture::create_2d(self, props)
 	}
 
+	pub fn texture_cube_create(&mut self, props: &TextureCubeProps) -> Texture {
+		Texture::create_cube(self, props)
+	}
+
+	/// Decodes a PNG or JPEG image into a new texture, see [`Texture::from_image_bytes`].
+	pub fn texture_from_image_bytes(
+		&mut self,
+		bytes: &[u8],
+		props: &ImageTextureProps,
+	) -> Result<Texture, image::ImageError> {
+		Texture::from_image_bytes(self, bytes, props)
+	}
+
 	pub fn sampler_create(&self, props: &SamplerProps) -> wgpu::Sampler {
 		Texture::create_sampler(self, props)
 	}
@@ -266,12 +369,72 @@ impl Painter {
 		Effect::new(self, shade, props)
 	}
 
+	/// Sets or replaces a uniform of an effect, e.g. the target of a previous effect pass,
+	/// which only exists after the layer was created.
+	///
+	/// Panics if it replaces a uniform of another kind, as the bind group layouts of the
+	/// effect shade are fixed.
+	pub fn effect_set_uniform(&mut self, effect: &Effect, index: u32, uniform: Uniform) {
+		let kind = self.bindings[uniform.0].kind;
+		if let Some(current) = self.effects[effect.0].uniforms.get(&index) {
+			let current_kind = self.bindings[current.0].kind;
+			assert!(
+				current_kind == kind,
+				"Can't replace the {:?} uniform at index {} of the effect with a {:?} uniform",
+				current_kind,
+				index,
+				kind
+			);
+		}
+		self.effects[effect.0].uniforms.insert(index, uniform);
+	}
+
+	/// Resolved shade, form, uniform bindings and vertex layout of a sketch, for debugging.
+	pub fn sketch_debug(&self, sketch: &Sketch) -> SketchDebug {
+		let s = &self.sketches[sketch.0];
+		let kind_of = |u: &Uniform| self.bindings[u.0].kind;
+
+		SketchDebug {
+			shade: s.shade,
+			form: s.form,
+			uniform_bindings: uniform_bindings(&s.uniforms, kind_of),
+			instance_bindings: s
+				.instances
+				.iter()
+				.map(|uniforms| uniform_bindings(uniforms, kind_of))
+				.collect(),
+			vertex_layout: self.shades[s.shade.0].attribs.attributes.clone(),
+		}
+	}
+
+	/// Resolved shade and uniform bindings of an effect, for debugging.
+	pub fn effect_debug(&self, effect: &Effect) -> EffectDebug {
+		let e = &self.effects[effect.0];
+
+		EffectDebug {
+			shade: e.shade,
+			uniform_bindings: uniform_bindings(&e.uniforms, |u| self.bindings[u.0].kind),
+			output_format: e.output_format,
+			pass: e.pass,
+		}
+	}
+
 	// layer utils
 
 	pub fn layer_create(&mut self, props: &LayerProps) -> Layer {
 		Layer::new(self, props)
 	}
 
+	/// Reads the painted output of a layer back to the CPU, row by row without padding.
+	/// Pixels keep the byte layout of the layer format, see [`Texture::read_pixels`].
+	/// Blocks until the GPU finished, e.g. for screenshots or headless rendering tests.
+	/// Only available on native targets, as browsers can't block on the GPU.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn read_layer_pixels(&self, layer: &Layer) -> Vec<u8> {
+		let l = &self.layers[layer.0];
+		l.target_textures[l.output_target].read_pixels(self)
+	}
+
 	// uniform utils
 
 	pub fn uniform_type_buffered(&self, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayout {
@@ -294,6 +457,15 @@ impl Painter {
 		UniformTex2D::get_layout(self, visibility)
 	}
 
+	/// Texture layout for samplers created with the props, e.g. comparison samplers for shadow maps.
+	pub fn uniform_type_tex_2d_with(
+		&self,
+		visibility: wgpu::ShaderStages,
+		sampler: &SamplerProps,
+	) -> wgpu::BindGroupLayout {
+		UniformTex2D::get_layout_with(self, visibility, sampler)
+	}
+
 	pub fn uniform_type_tex_2d_frag(&self) -> wgpu::BindGroupLayout {
 		self.uniform_type_tex_2d(wgpu::ShaderStages::FRAGMENT)
 	}
@@ -306,16 +478,100 @@ impl Painter {
 		self.uniform_type_tex_2d(wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT)
 	}
 
+	pub fn uniform_type_tex_cube(&self, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayout {
+		UniformTexCube::get_layout(self, visibility)
+	}
+
+	pub fn uniform_type_tex_cube_with(
+		&self,
+		visibility: wgpu::ShaderStages,
+		sampler: &SamplerProps,
+	) -> wgpu::BindGroupLayout {
+		UniformTexCube::get_layout_with(self, visibility, sampler)
+	}
+
+	pub fn uniform_type_tex_cube_frag(&self) -> wgpu::BindGroupLayout {
+		self.uniform_type_tex_cube(wgpu::ShaderStages::FRAGMENT)
+	}
+
 	// general utils
 
+	/// Records the layers, pipelines, draw calls and buffer updates until the next frame is presented,
+	/// and writes them to a JSON file at `path`. Browsers have no file system,
+	/// so on wasm the JSON is written to the log instead.
+	pub fn capture_next_frame(&self, path: impl Into<PathBuf>) {
+		*self.capture.borrow_mut() = Some(FrameCapture::new(path.into()));
+	}
+
+	pub(crate) fn capture_event(&self, event: impl FnOnce() -> CaptureEvent) {
+		if let Some(capture) = self.capture.borrow_mut().as_mut() {
+			capture.events.push(event());
+		}
+	}
+
+	pub(crate) fn finish_capture(&self, layer: Option<usize>) {
+		self.capture_event(|| CaptureEvent::Present { layer });
+		if let Some(capture) = self.capture.borrow_mut().take() {
+			if let Err(err) = capture.write() {
+				log::error!("Failed to write frame capture {:?}: {}", capture.path, err);
+			}
+		}
+	}
+
+	/// Creates a painter for a window of a host application, that runs its own winit event loop
+	/// instead of `create_canvas_app`. The host calls [`Painter::resize`] on window resizes,
+	/// and the paint and show methods on redraw requests.
+	/// [`Painter::request_next_frame`] does nothing, the host schedules redraws itself.
+	///
+	/// # Safety
+	///
+	/// The window must outlive the painter, which renders to a surface of the window.
+	pub async unsafe fn attach_async(window: &Window) -> Self {
+		let size = window.inner_size();
+		let instance = wgpu::Instance::default();
+		let surface = unsafe {
+			let target = wgpu::SurfaceTargetUnsafe::from_window(window)
+				.expect("Failed to get the window handles");
+			instance.create_surface_unsafe(target).unwrap()
+		};
+
+		Self::create(instance, Some(surface), None, size.width, size.height)
+			.await
+			.expect("Failed to find an appropriate adapter")
+	}
+
+	/// Same as [`Painter::attach_async`], blocking until the GPU device is ready.
+	/// Browsers can't block, use `attach_async` on wasm.
+	///
+	/// # Safety
+	///
+	/// The window must outlive the painter, which renders to a surface of the window.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub unsafe fn attach(window: &Window) -> Self {
+		pollster::block_on(unsafe { Self::attach_async(window) })
+	}
+
+	/// Reconfigures the surface with its current size,
+	/// e.g. after rendering failed with `SurfaceError::Lost` or `SurfaceError::Outdated`.
+	pub fn reconfigure_surface(&mut self) {
+		self.resize(winit::dpi::PhysicalSize {
+			width: self.config.width,
+			height: self.config.height,
+		});
+	}
+
 	pub fn request_next_frame(&self) {
-		self.window.request_redraw();
+		if let Some(window) = &self.window {
+			window.request_redraw();
+		}
 	}
 
 	pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
 		self.config.width = new_size.width.max(1);
 		self.config.height = new_size.height.max(1);
-		self.surface.configure(&self.device, &self.config);
+		if let Some(surface) = &self.surface {
+			surface.configure(&self.device, &self.config);
+		}
 
 		let layer_idxs: Vec<usize> = self
 			.layers
@@ -330,7 +586,41 @@ impl Painter {
 	}
 
 	pub fn canvas_size(&self) -> winit::dpi::PhysicalSize<u32> {
-		self.window.inner_size()
+		match &self.window {
+			Some(window) => window.inner_size(),
+			None => winit::dpi::PhysicalSize::new(self.config.width, self.config.height),
+		}
+	}
+
+	/// The window surface to present to.
+	///
+	/// # Panics
+	///
+	/// Panics on headless painters, see [`Painter::headless`].
+	pub fn surface(&self) -> &wgpu::Surface<'static> {
+		self.surface
+			.as_ref()
+			.expect("headless painters have no window surface")
+	}
+
+	/// Texture format of the window surface, which is also the default format of layers.
+	pub fn surface_format(&self) -> wgpu::TextureFormat {
+		self.config.format
+	}
+
+	/// Whether textures can be viewed with their sRGB or linear counterpart format,
+	/// which is not supported by all devices, e.g. not on WebGL2.
+	pub fn supports_view_formats(&self) -> bool {
+		self.adapter
+			.get_downlevel_capabilities()
+			.flags
+			.contains(wgpu::DownlevelFlags::VIEW_FORMATS)
+	}
+
+	/// Whether the surface encodes colors to sRGB on write,
+	/// so that shaders rendering to it output linear colors.
+	pub fn is_srgb_surface(&self) -> bool {
+		self.surface_format().is_srgb()
 	}
 
 	fn set_sketch_pipeline(
@@ -338,7 +628,8 @@ impl Painter {
 		rpass: &mut wgpu::RenderPass,
 		sketch: &Sketch,
 		layer: Option<&Layer>,
-	) {
+		pass: SketchPass,
+	) -> Vec<u8> {
 		let layer = layer.map(|l| &self.layers[l.0]);
 
 		let layer_key = match layer {
@@ -347,26 +638,38 @@ impl Painter {
 		};
 
 		let sketch = &self.sketches[sketch.0];
-		let pipeline_key = &[sketch.pipeline_key.as_slice(), layer_key].concat();
+		let pipeline_key = [sketch.pipeline_key.as_slice(), layer_key, &[pass as u8]].concat();
 
-		if !self.pipelines.contains_key(pipeline_key) {
+		if !self.pipelines.contains_key(&pipeline_key) {
 			let f = &self.forms[sketch.form.0];
 			let s = &self.shades[sketch.shade.0];
 			let format = layer.map_or(self.config.format, |l| l.format);
 
-			let vertex_shader = self
-				.device
-				.create_shader_module(wgpu::ShaderModuleDescriptor {
-					label: None,
-					source: make_spirv(&s.vertex_bytes.as_ref().unwrap()),
-				});
-
-			let fragment_shader = self
-				.device
-				.create_shader_module(wgpu::ShaderModuleDescriptor {
-					label: None,
-					source: make_spirv(&s.fragment_bytes.as_ref().unwrap()),
-				});
+			let shader_module = |source| {
+				self.device
+					.create_shader_module(wgpu::ShaderModuleDescriptor {
+						label: None,
+						source,
+					})
+			};
+			let (vertex_shader, fragment_shader) = match &s.wgsl {
+				Some(wgsl) => (
+					shader_module(wgpu::ShaderSource::Wgsl(wgsl.as_str().into())),
+					shader_module(wgpu::ShaderSource::Wgsl(wgsl.as_str().into())),
+				),
+				None => (
+					shader_module(make_spirv(&s.vertex_bytes.as_ref().unwrap())),
+					shader_module(make_spirv(&s.fragment_bytes.as_ref().unwrap())),
+				),
+			};
+
+			let color_targets = [Some(wgpu::ColorTargetState {
+				format,
+				blend: Some(sketch.blend_state),
+				write_mask: wgpu::ColorWrites::ALL,
+			})];
+
+			let sample_count = layer.map_or(1, |l| sample_count(l.multisampled));
 
 			let pipeline = self
 				.device
@@ -383,15 +686,18 @@ impl Painter {
 						}],
 						compilation_options: default(),
 					},
-					fragment: Some(wgpu::FragmentState {
-						module: &fragment_shader,
-						entry_point: None,
-						targets: &[Some(wgpu::ColorTargetState {
-							format,
-							blend: Some(sketch.blend_state),
-							write_mask: wgpu::ColorWrites::ALL,
-						})],
-						compilation_options: default(),
+					// The depth prepass only needs the fragment stage of shades that discard fragments.
+					fragment: (pass != SketchPass::DepthOnly || s.may_discard()).then(|| {
+						wgpu::FragmentState {
+							module: &fragment_shader,
+							entry_point: None,
+							targets: if pass == SketchPass::DepthOnly {
+								&[]
+							} else {
+								&color_targets
+							},
+							compilation_options: default(),
+						}
 					}),
 					primitive: wgpu::PrimitiveState {
 						topology: f.props.topology,
@@ -403,19 +709,26 @@ impl Painter {
 						unclipped_depth: false,
 						conservative: false,
 					},
-					depth_stencil: if sketch.depth_test {
+					// Sketches without depth or stencil test still need a state matching the
+					// depth stencil attachment of their layer, which is shared by all its sketches.
+					depth_stencil: depth_stencil_state(
+						sketch.depth_test,
+						sketch.stencil,
+						layer.map_or(DEFAULT_DEPTH_FORMAT, |l| l.depth_format),
+						pass,
+					)
+					.or_else(|| {
+						let l = layer.filter(|l| l.depth_texture.is_some())?;
 						Some(wgpu::DepthStencilState {
-							format: wgpu::TextureFormat::Depth24Plus,
-							depth_write_enabled: true,
-							depth_compare: wgpu::CompareFunction::Less,
+							format: l.depth_format,
+							depth_write_enabled: false,
+							depth_compare: wgpu::CompareFunction::Always,
 							stencil: default(),
 							bias: default(),
 						})
-					} else {
-						None
-					},
+					}),
 					multisample: wgpu::MultisampleState {
-						count: layer.map_or(1, |l| if l.multisampled { 4 } else { 1 }),
+						count: sample_count,
 						mask: !0,
 						alpha_to_coverage_enabled: false,
 					},
@@ -426,30 +739,55 @@ impl Painter {
 			self.pipelines.insert(pipeline_key.clone(), pipeline);
 		}
 
-		let pipeline = &self.pipelines[pipeline_key];
+		let pipeline = &self.pipelines[&pipeline_key];
 		rpass.set_pipeline(pipeline);
+
+		pipeline_key
 	}
 
+	/// Sets the pipeline of an effect. Effects rendering into the stencil target of their layer
+	/// replace its pixels, as their blend state is applied when compositing it.
 	fn set_effect_pipeline(
 		&mut self,
 		rpass: &mut wgpu::RenderPass,
 		effect: &Effect,
-		layer: &Layer,
-	) {
-		let layer = &self.layers[layer.0];
+		format: wgpu::TextureFormat,
+		depth_format: wgpu::TextureFormat,
+		stencil_target: bool,
+	) -> Vec<u8> {
 		let effect = &self.effects[effect.0];
 
-		let layer_key = layer.pipeline_key.as_slice();
-		let pipeline_key = &[effect.pipeline_key.as_slice(), layer_key].concat();
+		let pipeline_key = [
+			effect.pipeline_key.as_slice(),
+			&[
+				map_format_to_u8(format),
+				stencil_target as u8,
+				map_format_to_u8(depth_format),
+			],
+		]
+		.concat();
+		let blend_state = if stencil_target {
+			wgpu::BlendState::REPLACE
+		} else {
+			effect.blend_state
+		};
 
-		if !self.pipelines.contains_key(pipeline_key) {
+		if !self.pipelines.contains_key(&pipeline_key) {
 			let s = &self.shades[effect.shade.0];
 
+			let source = match &s.wgsl {
+				Some(wgsl) => wgpu::ShaderSource::Wgsl(wgsl.as_str().into()),
+				None => make_spirv(
+					s.fragment_bytes
+						.as_ref()
+						.expect("effect shade has neither fragment bytes nor WGSL"),
+				),
+			};
 			let fragment_shader = self
 				.device
 				.create_shader_module(wgpu::ShaderModuleDescriptor {
 					label: None,
-					source: make_spirv(&s.fragment_bytes.as_ref().unwrap()),
+					source,
 				});
 
 			let pipeline = self
@@ -467,8 +805,8 @@ impl Painter {
 						module: &fragment_shader,
 						entry_point: None,
 						targets: &[Some(wgpu::ColorTargetState {
-							format: layer.format,
-							blend: Some(effect.blend_state),
+							format,
+							blend: Some(blend_state),
 							write_mask: wgpu::ColorWrites::ALL,
 						})],
 						compilation_options: default(),
@@ -481,7 +819,12 @@ impl Painter {
 						polygon_mode: wgpu::PolygonMode::Fill,
 						..default()
 					},
-					depth_stencil: None,
+					depth_stencil: depth_stencil_state(
+						false,
+						effect.stencil,
+						depth_format,
+						SketchPass::Color,
+					),
 					multisample: wgpu::MultisampleState {
 						count: 1,
 						mask: !0,
@@ -491,11 +834,13 @@ impl Painter {
 					cache: None,
 				});
 
-			self.pipelines.insert(pipeline_key.to_vec(), pipeline);
+			self.pipelines.insert(pipeline_key.clone(), pipeline);
 		}
 
-		let pipeline = &self.pipelines[pipeline_key];
+		let pipeline = &self.pipelines[&pipeline_key];
 		rpass.set_pipeline(pipeline);
+
+		pipeline_key
 	}
 
 	fn render_sketch(
@@ -503,29 +848,52 @@ impl Painter {
 		rpass: &mut wgpu::RenderPass<'_>,
 		sketch: &Sketch,
 		layer: Option<&Layer>,
+		pass: SketchPass,
 	) {
-		self.set_sketch_pipeline(rpass, sketch, layer);
+		let pipeline = self.set_sketch_pipeline(rpass, sketch, layer, pass);
 
+		let sketch_idx = sketch.0;
 		let sketch = &self.sketches[sketch.0];
 		let form = &self.forms[sketch.form.0];
 
+		if let Some(stencil) = sketch.stencil {
+			rpass.set_stencil_reference(stencil.reference);
+		}
+
+		self.capture_event(|| CaptureEvent::Draw {
+			sketch: sketch_idx,
+			layer: layer.map(|l| l.0),
+			pipeline,
+			elements: clamp_range(
+				&sketch.draw_range,
+				if form.index_buffer.is_some() {
+					form.index_count
+				} else {
+					form.vertex_count
+				},
+			)
+			.len() as u32,
+			instances: sketch.instances.len().max(1),
+			depth_prepass: pass == SketchPass::DepthOnly,
+		});
+
 		let draw = |rpass: &mut wgpu::RenderPass| {
 			for (index, uniform) in &sketch.uniforms {
-				rpass.set_bind_group(*index, &self.bindings[uniform.0], &[]);
+				rpass.set_bind_group(*index, &self.bindings[uniform.0].binding, &[]);
 			}
 			rpass.set_vertex_buffer(0, form.vertex_buffer.slice(..));
 			if let Some(index_buffer) = &form.index_buffer {
 				rpass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-				rpass.draw_indexed(0..form.index_count, 0, 0..1);
+				rpass.draw_indexed(clamp_range(&sketch.draw_range, form.index_count), 0, 0..1);
 			} else {
-				rpass.draw(0..form.vertex_count, 0..1);
+				rpass.draw(clamp_range(&sketch.draw_range, form.vertex_count), 0..1);
 			}
 		};
 
 		if sketch.instances.len() > 0 {
 			for uniforms in &sketch.instances {
 				for (index, uniform) in uniforms {
-					rpass.set_bind_group(*index, &self.bindings[uniform.0], &[]);
+					rpass.set_bind_group(*index, &self.bindings[uniform.0].binding, &[]);
 				}
 				draw(rpass);
 			}
@@ -534,10 +902,75 @@ impl Painter {
 		}
 	}
 
-	fn render_effect(&mut self, effect: &Effect, layer: &Layer) -> Result<(), wgpu::SurfaceError> {
+	/// Renders the depth of all depth tested sketches of the layer, without shading any fragments.
+	fn paint_depth_prepass(&mut self, layer: &Layer) {
+		let l = &self.layers[layer.0];
+		let Some(depth_texture) = l.depth_texture else {
+			return;
+		};
+
+		let mut encoder = self
+			.device
+			.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+		{
+			let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+				label: None,
+				color_attachments: &[],
+				depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+					view: &self.textures[depth_texture.0].view,
+					depth_ops: Some(wgpu::Operations {
+						load: wgpu::LoadOp::Clear(1.0),
+						store: wgpu::StoreOp::Store,
+					}),
+					stencil_ops: l
+						.depth_format
+						.has_stencil_aspect()
+						.then_some(wgpu::Operations {
+							load: wgpu::LoadOp::Clear(0),
+							store: wgpu::StoreOp::Store,
+						}),
+				}),
+				timestamp_writes: None,
+				occlusion_query_set: None,
+			});
+
+			for sketch in l.sketches.clone() {
+				if self.sketches[sketch.0].depth_test {
+					self.render_sketch(&mut rpass, &sketch, Some(layer), SketchPass::DepthOnly);
+				}
+			}
+		}
+
+		self.queue.submit(Some(encoder.finish()));
+	}
+
+	fn render_effect(
+		&mut self,
+		effect: &Effect,
+		layer: &Layer,
+		target: usize,
+	) -> Result<(), wgpu::SurfaceError> {
 		let l = &self.layers[layer.0];
 
-		let view = &self.textures[l.target_textures[0].0].view;
+		let stencil = self.effects[effect.0].stencil;
+		// Effects with stencil test render into the stencil target of their target, so that they can
+		// sample their target. It is composited into the target through the mask afterwards.
+		let stencil_target = stencil.and_then(|_| l.stencil_targets.get(&target).copied());
+
+		let texture = &self.textures[l.target_textures[stencil_target.unwrap_or(target)].0];
+		let view = &texture.view;
+		let format = texture.texture.format();
+		let depth_format = l.depth_format;
+		// Layers with stencil effects always have a depth stencil texture of the size of the target.
+		let stencil_view = stencil
+			.and(l.depth_texture)
+			.map(|t| &self.textures[t.0].view);
+		let load = match (stencil_target, l.clear_color) {
+			(Some(_), _) => wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+			(None, Some(color)) => wgpu::LoadOp::Clear(color),
+			(None, None) => wgpu::LoadOp::Load,
+		};
 
 		let mut encoder = self
 			.device
@@ -550,23 +983,35 @@ impl Painter {
 					view,
 					resolve_target: None,
 					ops: wgpu::Operations {
-						load: l
-							.clear_color
-							.map_or(wgpu::LoadOp::Load, |color| wgpu::LoadOp::Clear(color)),
+						load,
 						store: wgpu::StoreOp::Store,
 					},
 				})],
-				depth_stencil_attachment: None,
+				depth_stencil_attachment: stencil_view.map(stencil_attachment),
 				timestamp_writes: None,
 				occlusion_query_set: None,
 			});
 
-			self.set_effect_pipeline(&mut rpass, effect, layer);
+			let pipeline = self.set_effect_pipeline(
+				&mut rpass,
+				effect,
+				format,
+				depth_format,
+				stencil_target.is_some(),
+			);
+			if let Some(stencil) = stencil {
+				rpass.set_stencil_reference(stencil.reference);
+			}
+			self.capture_event(|| CaptureEvent::Effect {
+				effect: effect.0,
+				layer: layer.0,
+				pipeline,
+			});
 
 			let e = &self.effects[effect.0];
 
 			for (index, uniform) in &e.uniforms {
-				rpass.set_bind_group(*index, &self.bindings[uniform.0], &[]);
+				rpass.set_bind_group(*index, &self.bindings[uniform.0].binding, &[]);
 			}
 
 			rpass.draw(0..3, 0..1);
@@ -574,11 +1019,146 @@ impl Painter {
 
 		self.queue.submit(Some(encoder.finish()));
 
+		if let Some(stencil_target) = stencil_target {
+			self.composite_stencil_target(effect, layer, stencil_target, target);
+		}
+
 		Ok(())
 	}
 
+	/// Copies the stencil target of an effect into `target` where the stencil test of the effect passes,
+	/// with the blend state of the effect. The other pixels of `target` are kept.
+	fn composite_stencil_target(
+		&mut self,
+		effect: &Effect,
+		layer: &Layer,
+		stencil_target: usize,
+		target: usize,
+	) {
+		let source = layer.get_target_uniform(self, stencil_target);
+
+		let l = &self.layers[layer.0];
+		let e = &self.effects[effect.0];
+		let stencil = e
+			.stencil
+			.expect("only effects with stencil test have a stencil target");
+		let format = self.textures[l.target_textures[target].0].texture.format();
+		let depth_format = l.depth_format;
+
+		let pipeline_key = [
+			&[
+				STENCIL_COMPOSITE_PIPELINE,
+				map_format_to_u8(format),
+				map_format_to_u8(depth_format),
+				l.binding_visibility.bits() as u8,
+			][..],
+			&blend_key(&e.blend_state),
+			&Stencil::pipeline_key(Some(stencil)),
+		]
+		.concat();
+
+		if !self.pipelines.contains_key(&pipeline_key) {
+			let shader = self
+				.device
+				.create_shader_module(wgpu::ShaderModuleDescriptor {
+					label: None,
+					source: wgpu::ShaderSource::Wgsl(COPY_PIXELS.into()),
+				});
+			let layout = self
+				.device
+				.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+					label: None,
+					bind_group_layouts: &[&UniformTex2D::get_layout_with(
+						self,
+						l.binding_visibility,
+						&l.sampler,
+					)],
+					push_constant_ranges: &[],
+				});
+			let pipeline = self
+				.device
+				.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+					label: None,
+					layout: Some(&layout),
+					vertex: wgpu::VertexState {
+						module: &self.fullscreen_quad_shader,
+						entry_point: Some("vs_main"),
+						buffers: &[],
+						compilation_options: default(),
+					},
+					fragment: Some(wgpu::FragmentState {
+						module: &shader,
+						entry_point: Some("fs_main"),
+						targets: &[Some(wgpu::ColorTargetState {
+							format,
+							blend: Some(e.blend_state),
+							write_mask: wgpu::ColorWrites::ALL,
+						})],
+						compilation_options: default(),
+					}),
+					primitive: wgpu::PrimitiveState {
+						topology: wgpu::PrimitiveTopology::TriangleStrip,
+						front_face: wgpu::FrontFace::Cw,
+						..default()
+					},
+					depth_stencil: depth_stencil_state(
+						false,
+						Some(stencil),
+						depth_format,
+						SketchPass::Color,
+					),
+					multisample: default(),
+					multiview: None,
+					cache: None,
+				});
+			self.pipelines.insert(pipeline_key.clone(), pipeline);
+		}
+
+		let depth_texture = l
+			.depth_texture
+			.expect("layers with stencil effects have a depth stencil texture");
+
+		let mut encoder = self
+			.device
+			.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+		{
+			let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+				label: None,
+				color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+					view: &self.textures[l.target_textures[target].0].view,
+					resolve_target: None,
+					ops: wgpu::Operations {
+						load: wgpu::LoadOp::Load,
+						store: wgpu::StoreOp::Store,
+					},
+				})],
+				depth_stencil_attachment: Some(stencil_attachment(
+					&self.textures[depth_texture.0].view,
+				)),
+				timestamp_writes: None,
+				occlusion_query_set: None,
+			});
+
+			rpass.set_pipeline(&self.pipelines[&pipeline_key]);
+			rpass.set_stencil_reference(stencil.reference);
+			rpass.set_bind_group(0, &self.bindings[source.uniform.0].binding, &[]);
+			rpass.draw(0..3, 0..1);
+		}
+
+		self.queue.submit(Some(encoder.finish()));
+	}
+
+	/// Draws a sketch directly to the window.
+	///
+	/// Panics if the sketch has a stencil test, as the window has no stencil buffer.
+	/// Paint such sketches in a layer with a stencil depth format instead.
 	pub fn draw<'a>(&mut self, sketch: &Sketch) -> Result<(), wgpu::SurfaceError> {
-		let frame = self.surface.get_current_texture()?;
+		assert!(
+			self.sketches[sketch.0].stencil.is_none(),
+			"sketches with stencil test can only be painted in layers with a stencil depth format"
+		);
+		let frame = self.surface().get_current_texture()?;
 
 		let view = frame
 			.texture
@@ -604,11 +1184,12 @@ impl Painter {
 				occlusion_query_set: None,
 			});
 
-			self.render_sketch(&mut rpass, sketch, None);
+			self.render_sketch(&mut rpass, sketch, None, SketchPass::Color);
 		}
 
 		self.queue.submit(Some(encoder.finish()));
 		frame.present();
+		self.finish_capture(None);
 
 		Ok(())
 	}
@@ -616,8 +1197,19 @@ impl Painter {
 	pub fn paint(&mut self, layer: &Layer) -> Result<(), wgpu::SurfaceError> {
 		let l = &self.layers[layer.0];
 
+		if l.depth_prepass && l.depth_texture.is_some() {
+			self.paint_depth_prepass(layer);
+		}
+
+		let l = &self.layers[layer.0];
 		if l.sketches.len() > 0 {
-			let view = &self.textures[l.target_textures[0].0].view;
+			let target_view = &self.textures[l.target_textures[0].0].view;
+			// Multisampled layers render into the multisampled texture,
+			// and resolve it into the first target, so that it can be sampled by effects and other layers.
+			let (view, resolve_target) = match l.multisampled_texture {
+				Some(t) => (&self.textures[t.0].view, Some(target_view)),
+				None => (target_view, None),
+			};
 
 			let mut encoder = self
 				.device
@@ -628,7 +1220,7 @@ impl Painter {
 					label: None,
 					color_attachments: &[Some(wgpu::RenderPassColorAttachment {
 						view,
-						resolve_target: None,
+						resolve_target,
 						ops: wgpu::Operations {
 							load: l
 								.clear_color
@@ -640,28 +1232,61 @@ impl Painter {
 						wgpu::RenderPassDepthStencilAttachment {
 							view: &self.textures[t.0].view,
 							depth_ops: Some(wgpu::Operations {
-								load: wgpu::LoadOp::Clear(1.0),
+								load: if l.depth_prepass {
+									wgpu::LoadOp::Load
+								} else {
+									wgpu::LoadOp::Clear(1.0)
+								},
 								store: wgpu::StoreOp::Store,
 							}),
-							stencil_ops: None,
+							// the depth prepass already cleared the stencil
+							stencil_ops: l.depth_format.has_stencil_aspect().then_some(
+								wgpu::Operations {
+									load: if l.depth_prepass {
+										wgpu::LoadOp::Load
+									} else {
+										wgpu::LoadOp::Clear(0)
+									},
+									store: wgpu::StoreOp::Store,
+								},
+							),
 						}
 					}),
 					timestamp_writes: None,
 					occlusion_query_set: None,
 				});
 
+				let depth_prepass = l.depth_prepass;
 				for sketch in l.sketches.clone() {
-					self.render_sketch(&mut rpass, &sketch, Some(layer));
+					let pass = if depth_prepass && self.sketches[sketch.0].depth_test {
+						SketchPass::ColorAfterDepthPrepass
+					} else {
+						SketchPass::Color
+					};
+					self.render_sketch(&mut rpass, &sketch, Some(layer), pass);
 				}
 			}
 
 			self.queue.submit(Some(encoder.finish()));
 		}
 
-		for effect in self.layers[layer.0].effects.clone() {
-			self.render_effect(&effect, layer)?;
+		let l = &self.layers[layer.0];
+		let effects = l.effects.clone();
+		let targets = l.effect_targets.clone();
+		for (effect, target) in effects.iter().zip(targets) {
+			self.render_effect(effect, layer, target)?;
 		}
 
+		self.capture_event(|| {
+			let l = &self.layers[layer.0];
+			CaptureEvent::Layer {
+				layer: layer.0,
+				sketches: l.sketches.len(),
+				effects: l.effects.len(),
+				draw_calls: layer.geometry_stats(self).draw_calls,
+			}
+		});
+
 		Ok(())
 	}
 
@@ -672,8 +1297,23 @@ impl Painter {
 		Ok(())
 	}
 
+	/// Paints all layers of the graph, each one after its dependencies.
+	pub fn execute(&mut self, graph: &RenderGraph) -> Result<(), wgpu::SurfaceError> {
+		self.compose(graph.layers())
+	}
+
+	/// Renders the layer output to the window, see [`ShowOpts`] for the default color conversion.
 	pub fn show(&mut self, layer: &Layer) -> Result<(), wgpu::SurfaceError> {
-		let frame = self.surface.get_current_texture()?;
+		self.show_with(layer, ShowOpts::default())
+	}
+
+	/// Renders the layer output to the window, with explicit control over the sRGB conversion.
+	///
+	/// # Panics
+	///
+	/// Panics on headless painters, as they have no window surface.
+	pub fn show_with(&mut self, layer: &Layer, opts: ShowOpts) -> Result<(), wgpu::SurfaceError> {
+		let frame = self.surface().get_current_texture()?;
 
 		let view = frame
 			.texture
@@ -683,8 +1323,13 @@ impl Painter {
 			.device
 			.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-		let uniform = layer.get_uniform(self).uniform;
-		let binding = &self.bindings[uniform.0];
+		let output = &self.layers[layer.0];
+		let layer_format = self.textures[output.target_textures[output.output_target].0]
+			.texture
+			.format();
+		let view_format = show_view_format(layer_format, self.is_srgb_surface(), opts);
+		let uniform = layer.get_output_view_uniform(self, view_format).uniform;
+		let binding = &self.bindings[uniform.0].binding;
 
 		let pipeline = &self.pipelines[FULL_SCREEN_TEXTURE_PIPELINE];
 
@@ -710,6 +1355,7 @@ impl Painter {
 
 		self.queue.submit(Some(encoder.finish()));
 		frame.present();
+		self.finish_capture(Some(layer.0));
 
 		Ok(())
 	}
@@ -754,6 +1400,105 @@ impl Painter {
 	}
 }
 
+/// Color conversion of [`Painter::show_with`]. Showing needs a window surface,
+/// so headless painters can't show layers, see [`Painter::headless`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowOpts {
+	/// The layer holds linear colors, that sRGB surfaces encode on write. This is the default,
+	/// and matches layers with sRGB formats, and linear layers that shaders write linear colors to.
+	///
+	/// If false, the layer already holds display encoded colors, and its bytes are copied to the
+	/// surface unchanged, e.g. for unorm layers with encoded colors on an sRGB surface.
+	/// Float formats have no sRGB counterpart and are always sampled as linear.
+	///
+	/// Surfaces without sRGB format receive the sampled values as they are,
+	/// so linear input is not encoded on them.
+	pub assume_linear_input: bool,
+}
+
+impl Default for ShowOpts {
+	fn default() -> Self {
+		Self {
+			assume_linear_input: true,
+		}
+	}
+}
+
+/// Format of the view, through which `show` samples a layer output of `layer_format`.
+/// Encoded input is sampled so that the surface writes back the stored bytes:
+/// decoded as sRGB for sRGB surfaces, and as raw unorm values otherwise.
+pub(crate) fn show_view_format(
+	layer_format: wgpu::TextureFormat,
+	surface_srgb: bool,
+	opts: ShowOpts,
+) -> wgpu::TextureFormat {
+	if opts.assume_linear_input {
+		layer_format
+	} else if surface_srgb {
+		layer_format.add_srgb_suffix()
+	} else {
+		layer_format.remove_srgb_suffix()
+	}
+}
+
+/// Pipeline variants of sketches, for layers with a depth prepass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SketchPass {
+	Color = 0,
+	/// Writes only depth, without fragment shader.
+	DepthOnly = 1,
+	/// Shades only the fragments, that are visible in the depth of the prepass.
+	ColorAfterDepthPrepass = 2,
+}
+
+/// Depth stencil state of sketches, and of effects without depth test.
+fn depth_stencil_state(
+	depth_test: bool,
+	stencil: Option<Stencil>,
+	format: wgpu::TextureFormat,
+	pass: SketchPass,
+) -> Option<wgpu::DepthStencilState> {
+	let (depth_write_enabled, depth_compare) = match pass {
+		_ if !depth_test => (false, wgpu::CompareFunction::Always),
+		SketchPass::Color | SketchPass::DepthOnly => (true, wgpu::CompareFunction::Less),
+		SketchPass::ColorAfterDepthPrepass => (false, wgpu::CompareFunction::Equal),
+	};
+	// The depth prepass only writes depth, stencil operations are applied once in the color pass.
+	let stencil = stencil.filter(|_| pass != SketchPass::DepthOnly);
+	(depth_test || stencil.is_some()).then(|| wgpu::DepthStencilState {
+		format,
+		depth_write_enabled,
+		depth_compare,
+		stencil: stencil.map_or(default(), |s| s.state()),
+		bias: default(),
+	})
+}
+
+/// Depth stencil attachment of effect passes, that test the stencil written by the sketches of the layer.
+fn stencil_attachment(view: &wgpu::TextureView) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+	wgpu::RenderPassDepthStencilAttachment {
+		view,
+		depth_ops: None,
+		stencil_ops: Some(wgpu::Operations {
+			load: wgpu::LoadOp::Load,
+			store: wgpu::StoreOp::Store,
+		}),
+	}
+}
+
+pub(crate) fn clamp_range(
+	range: &Option<std::ops::Range<u32>>,
+	count: u32,
+) -> std::ops::Range<u32> {
+	match range {
+		Some(range) => {
+			let end = range.end.min(count);
+			range.start.min(end)..end
+		}
+		None => 0..count,
+	}
+}
+
 pub(crate) fn get_padded_size(unpadded_size: u64) -> u64 {
 	// Valid vulkan usage is
 	// 1. buffer size must be a multiple of COPY_BUFFER_ALIGNMENT.
@@ -762,3 +1507,147 @@ pub(crate) fn get_padded_size(unpadded_size: u64) -> u64 {
 	let align_mask = wgpu::COPY_BUFFER_ALIGNMENT - 1;
 	((unpadded_size + align_mask) & !align_mask).max(wgpu::COPY_BUFFER_ALIGNMENT)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn clamped_draw_ranges() {
+		assert_eq!(clamp_range(&None, 6), 0..6);
+		assert_eq!(clamp_range(&Some(3..6), 12), 3..6);
+		assert_eq!(clamp_range(&Some(3..12), 6), 3..6);
+		// ranges past the end of the buffer draw nothing
+		assert_eq!(clamp_range(&Some(9..12), 6), 6..6);
+		// inverted ranges draw nothing, instead of failing validation
+		assert_eq!(
+			clamp_range(&Some(std::ops::Range { start: 5, end: 2 }), 6),
+			2..2
+		);
+		assert_eq!(
+			clamp_range(&Some(std::ops::Range { start: 9, end: 3 }), 6),
+			3..3
+		);
+	}
+
+	#[test]
+	fn depth_prepass_states() {
+		assert_eq!(
+			depth_stencil_state(false, None, DEFAULT_DEPTH_FORMAT, SketchPass::DepthOnly),
+			None
+		);
+		assert_eq!(
+			depth_stencil_state(
+				false,
+				None,
+				DEFAULT_DEPTH_FORMAT,
+				SketchPass::ColorAfterDepthPrepass
+			),
+			None
+		);
+
+		let prepass =
+			depth_stencil_state(true, None, DEFAULT_DEPTH_FORMAT, SketchPass::DepthOnly).unwrap();
+		assert!(prepass.depth_write_enabled);
+		assert_eq!(prepass.depth_compare, wgpu::CompareFunction::Less);
+		assert_eq!(
+			depth_stencil_state(true, None, DEFAULT_DEPTH_FORMAT, SketchPass::Color),
+			Some(prepass)
+		);
+
+		let color = depth_stencil_state(
+			true,
+			None,
+			DEFAULT_DEPTH_FORMAT,
+			SketchPass::ColorAfterDepthPrepass,
+		)
+		.unwrap();
+		assert!(!color.depth_write_enabled);
+		assert_eq!(color.depth_compare, wgpu::CompareFunction::Equal);
+
+		let depth32 = wgpu::TextureFormat::Depth32Float;
+		let state = depth_stencil_state(true, None, depth32, SketchPass::Color).unwrap();
+		assert_eq!(state.format, depth32);
+	}
+
+	#[test]
+	fn stencil_states() {
+		let format = wgpu::TextureFormat::Depth24PlusStencil8;
+
+		// mask sketch without depth test
+		let mask =
+			depth_stencil_state(false, Some(Stencil::write(1)), format, SketchPass::Color).unwrap();
+		assert!(!mask.depth_write_enabled);
+		assert_eq!(mask.depth_compare, wgpu::CompareFunction::Always);
+		assert_eq!(mask.stencil.front.compare, wgpu::CompareFunction::Always);
+		assert_eq!(mask.stencil.front.pass_op, wgpu::StencilOperation::Replace);
+		assert_eq!(mask.stencil.back, mask.stencil.front);
+
+		// effect, that only writes inside the mask
+		let effect =
+			depth_stencil_state(false, Some(Stencil::equal(1)), format, SketchPass::Color).unwrap();
+		assert!(!effect.depth_write_enabled);
+		assert_eq!(effect.stencil.front.compare, wgpu::CompareFunction::Equal);
+		assert_eq!(effect.stencil.front.pass_op, wgpu::StencilOperation::Keep);
+		assert_eq!(effect.stencil.front.fail_op, wgpu::StencilOperation::Keep);
+		assert!(effect.stencil.is_enabled());
+
+		// depth tested sketches keep their depth state, and apply the stencil only in color passes
+		let depth_tested =
+			depth_stencil_state(true, Some(Stencil::write(2)), format, SketchPass::Color).unwrap();
+		assert!(depth_tested.depth_write_enabled);
+		assert_eq!(depth_tested.depth_compare, wgpu::CompareFunction::Less);
+		assert_eq!(
+			depth_tested.stencil.front.pass_op,
+			wgpu::StencilOperation::Replace
+		);
+		let prepass =
+			depth_stencil_state(true, Some(Stencil::write(2)), format, SketchPass::DepthOnly)
+				.unwrap();
+		assert!(!prepass.stencil.is_enabled());
+
+		assert_eq!(
+			depth_stencil_state(false, None, format, SketchPass::Color),
+			None
+		);
+		assert_ne!(
+			Stencil::pipeline_key(Some(Stencil::equal(1))),
+			Stencil::pipeline_key(Some(Stencil::not_equal(1)))
+		);
+		assert_eq!(
+			Stencil::pipeline_key(Some(Stencil::equal(1))),
+			Stencil::pipeline_key(Some(Stencil::equal(2)))
+		);
+		assert_ne!(
+			Stencil::pipeline_key(None),
+			Stencil::pipeline_key(Some(Stencil::write(0)))
+		);
+	}
+
+	#[test]
+	fn show_conversion() {
+		use wgpu::TextureFormat::{Rgba16Float, Rgba8Unorm, Rgba8UnormSrgb};
+
+		let linear = ShowOpts::default();
+		let encoded = ShowOpts {
+			assume_linear_input: false,
+		};
+		assert!(linear.assume_linear_input);
+
+		// linear input is sampled as stored, and encoded by sRGB surfaces
+		assert_eq!(show_view_format(Rgba8Unorm, true, linear), Rgba8Unorm);
+
+		// encoded input is sampled so that the surface writes back the stored bytes,
+		// see `gpu_tests::show_mid_gray` for the resulting values
+		assert_eq!(show_view_format(Rgba8Unorm, true, encoded), Rgba8UnormSrgb);
+		assert_eq!(show_view_format(Rgba8UnormSrgb, false, encoded), Rgba8Unorm);
+
+		// sRGB layers store encoded bytes of linear colors, and keep them on sRGB surfaces
+		assert_eq!(
+			show_view_format(Rgba8UnormSrgb, true, linear),
+			Rgba8UnormSrgb
+		);
+
+		assert_eq!(show_view_format(Rgba16Float, true, encoded), Rgba16Float);
+	}
+}
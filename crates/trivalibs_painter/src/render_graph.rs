@@ -0,0 +1,173 @@
+use super::layer::Layer;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RenderGraphError {
+	/// The dependencies of the contained layer form a cycle.
+	Cycle(Layer),
+}
+
+impl std::fmt::Display for RenderGraphError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			RenderGraphError::Cycle(layer) => {
+				write!(
+					f,
+					"render graph has a dependency cycle at layer {}",
+					layer.0
+				)
+			}
+		}
+	}
+}
+
+impl std::error::Error for RenderGraphError {}
+
+/// An ordered list of layers, where every layer is painted after all of its dependencies.
+/// Create it with a [`RenderGraphBuilder`] and run it with [`crate::Painter::execute`].
+#[derive(Debug, Clone)]
+pub struct RenderGraph {
+	pub(crate) layers: Vec<Layer>,
+}
+
+impl RenderGraph {
+	pub fn builder() -> RenderGraphBuilder {
+		RenderGraphBuilder::new()
+	}
+
+	/// The layers in the order in which they will be painted.
+	pub fn layers(&self) -> &[Layer] {
+		&self.layers
+	}
+}
+
+#[derive(Default)]
+pub struct RenderGraphBuilder {
+	nodes: Vec<(Layer, Vec<Layer>)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VisitState {
+	New,
+	Visiting,
+	Done,
+}
+
+impl RenderGraphBuilder {
+	pub fn new() -> Self {
+		Self { nodes: Vec::new() }
+	}
+
+	/// Registers a layer, that reads from all layers in `dependencies`.
+	/// Dependencies that are not registered themselves are painted without further dependencies.
+	/// Adding the same layer twice merges its dependencies.
+	pub fn add_layer(&mut self, layer: Layer, dependencies: &[Layer]) -> &mut Self {
+		let idx = self.node_index(layer);
+		self.nodes[idx].1.extend_from_slice(dependencies);
+		for dep in dependencies {
+			self.node_index(*dep);
+		}
+		self
+	}
+
+	fn node_index(&mut self, layer: Layer) -> usize {
+		if let Some(idx) = self.nodes.iter().position(|(l, _)| *l == layer) {
+			idx
+		} else {
+			self.nodes.push((layer, vec![]));
+			self.nodes.len() - 1
+		}
+	}
+
+	/// Orders the registered layers topologically.
+	/// Independent layers keep the order in which they were added.
+	pub fn build(&self) -> Result<RenderGraph, RenderGraphError> {
+		let mut states = vec![VisitState::New; self.nodes.len()];
+		let mut layers = Vec::with_capacity(self.nodes.len());
+
+		for idx in 0..self.nodes.len() {
+			self.visit(idx, &mut states, &mut layers)?;
+		}
+
+		Ok(RenderGraph { layers })
+	}
+
+	fn visit(
+		&self,
+		idx: usize,
+		states: &mut [VisitState],
+		layers: &mut Vec<Layer>,
+	) -> Result<(), RenderGraphError> {
+		match states[idx] {
+			VisitState::Done => return Ok(()),
+			VisitState::Visiting => return Err(RenderGraphError::Cycle(self.nodes[idx].0)),
+			VisitState::New => {}
+		}
+
+		states[idx] = VisitState::Visiting;
+
+		for dep in &self.nodes[idx].1 {
+			let dep_idx = self.nodes.iter().position(|(l, _)| l == dep).unwrap();
+			self.visit(dep_idx, states, layers)?;
+		}
+
+		states[idx] = VisitState::Done;
+		layers.push(self.nodes[idx].0);
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn orders_layers_by_dependencies() {
+		let a = Layer(0);
+		let b = Layer(1);
+		let c = Layer(2);
+
+		let graph = RenderGraph::builder()
+			.add_layer(c, &[b])
+			.add_layer(b, &[a])
+			.add_layer(a, &[])
+			.build()
+			.unwrap();
+
+		assert_eq!(graph.layers(), &[a, b, c]);
+	}
+
+	#[test]
+	fn keeps_insertion_order_of_independent_layers() {
+		let a = Layer(0);
+		let b = Layer(1);
+		let c = Layer(2);
+
+		let graph = RenderGraph::builder()
+			.add_layer(b, &[])
+			.add_layer(c, &[a, b])
+			.build()
+			.unwrap();
+
+		assert_eq!(graph.layers(), &[b, a, c]);
+	}
+
+	#[test]
+	fn detects_cycles() {
+		let a = Layer(0);
+		let b = Layer(1);
+		let c = Layer(2);
+
+		let result = RenderGraph::builder()
+			.add_layer(a, &[c])
+			.add_layer(b, &[a])
+			.add_layer(c, &[b])
+			.build();
+
+		assert!(matches!(result, Err(RenderGraphError::Cycle(_))));
+
+		let result = RenderGraph::builder().add_layer(a, &[a]).build();
+
+		assert_eq!(result.unwrap_err(), RenderGraphError::Cycle(a));
+	}
+}
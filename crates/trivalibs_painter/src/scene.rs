@@ -0,0 +1,281 @@
+use super::{
+	form::Form,
+	layer::{Layer, LayerProps},
+	painter::UniformType,
+	shade::{Shade, ShadeProps},
+	shaders::SCENE,
+	sketch::{Sketch, SketchProps},
+	uniform::{Mat3U, UniformBuffer},
+	Painter,
+};
+use std::collections::BTreeMap;
+use trivalibs_core::{
+	glam::{vec3, Mat3, Mat4, Vec3, Vec4},
+	rendering::{
+		camera::PerspectiveCamera, scene::normal_mat, transform::Transform, RenderableBuffer,
+	},
+};
+
+/// Surface of a scene entry, drawn with a fixed directional light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SceneMaterial {
+	/// Linear RGBA color.
+	pub color: Vec4,
+}
+
+impl Default for SceneMaterial {
+	fn default() -> Self {
+		Self { color: Vec4::ONE }
+	}
+}
+
+impl SceneMaterial {
+	pub fn color(color: Vec4) -> Self {
+		Self { color }
+	}
+}
+
+impl From<Vec4> for SceneMaterial {
+	fn from(color: Vec4) -> Self {
+		Self::color(color)
+	}
+}
+
+/// A form drawn at a transform, see [`Scene::add`].
+pub struct SceneEntry {
+	pub form: Form,
+	pub transform: Transform,
+	pub material: SceneMaterial,
+	sketch: Sketch,
+	mvp: UniformBuffer<Mat4>,
+	normal: UniformBuffer<Mat3U>,
+	color: UniformBuffer<Vec4>,
+}
+
+/// Convenience layer over forms, shades, sketches and uniforms, to draw meshes with a camera
+/// without writing shaders. Forms need a position and a normal attribute, both `Float32x3`,
+/// e.g. [`cube_buffer`] or a `MeshGeometry` exported with face or vertex normals.
+///
+/// Add the entries first, and create the layer with [`Scene::layer_props`] or [`Scene::sketches`],
+/// as layers don't pick up sketches added later.
+pub struct Scene {
+	pub camera: PerspectiveCamera,
+	shade: Shade,
+	vert_type: wgpu::BindGroupLayout,
+	frag_type: wgpu::BindGroupLayout,
+	entries: Vec<SceneEntry>,
+}
+
+impl Scene {
+	pub fn new(painter: &mut Painter, camera: PerspectiveCamera) -> Self {
+		let vert_type = painter.uniform_type_buffered_vert();
+		let frag_type = painter.uniform_type_buffered_frag();
+
+		let shade = painter.shade_create(ShadeProps {
+			vertex_format: &[wgpu::VertexFormat::Float32x3, wgpu::VertexFormat::Float32x3],
+			uniform_types: &[&vert_type, &vert_type, &frag_type],
+		});
+		shade.set_wgsl(painter, SCENE);
+
+		Self {
+			camera,
+			shade,
+			vert_type,
+			frag_type,
+			entries: vec![],
+		}
+	}
+
+	/// Adds a depth tested sketch of the form, and returns the index of the entry.
+	pub fn add(
+		&mut self,
+		painter: &mut Painter,
+		form: Form,
+		transform: Transform,
+		material: impl Into<SceneMaterial>,
+	) -> usize {
+		let material = material.into();
+		let mvp = self.vert_type.create_buff(painter, Mat4::IDENTITY);
+		let normal = self.vert_type.create_mat3(painter, Mat3::IDENTITY);
+		let color = self.frag_type.create_buff(painter, material.color);
+
+		let sketch = painter.sketch_create(
+			form,
+			self.shade,
+			&SketchProps {
+				uniforms: BTreeMap::from([
+					(0, mvp.uniform),
+					(1, normal.uniform),
+					(2, color.uniform),
+				]),
+				depth_test: true,
+				..SketchProps::default()
+			},
+		);
+
+		self.entries.push(SceneEntry {
+			form,
+			transform,
+			material,
+			sketch,
+			mvp,
+			normal,
+			color,
+		});
+		self.entries.len() - 1
+	}
+
+	pub fn entries(&self) -> &[SceneEntry] {
+		&self.entries
+	}
+
+	pub fn transform_mut(&mut self, index: usize) -> &mut Transform {
+		&mut self.entries[index].transform
+	}
+
+	pub fn set_material(&mut self, index: usize, material: SceneMaterial) {
+		self.entries[index].material = material;
+	}
+
+	/// Sketches of all entries, to create the layer that the scene is rendered to.
+	pub fn sketches(&self) -> Vec<Sketch> {
+		self.entries.iter().map(|e| e.sketch).collect()
+	}
+
+	/// Props of a window sized layer with the sketches of all entries, cleared to black.
+	/// Spread them into a struct literal to customize the layer.
+	pub fn layer_props(&self) -> LayerProps {
+		LayerProps {
+			sketches: self.sketches(),
+			clear_color: Some(wgpu::Color::BLACK),
+			..LayerProps::default()
+		}
+	}
+
+	/// Updates the uniforms of all entries from their transforms and materials, and paints the layer.
+	/// The camera aspect ratio follows the layer size.
+	pub fn render(&self, painter: &mut Painter, layer: &Layer) -> Result<(), wgpu::SurfaceError> {
+		let (width, height) = layer.current_size(painter);
+		let camera = camera_with_aspect(&self.camera, width as f32 / height.max(1) as f32);
+
+		for entry in &self.entries {
+			let (mvp, normal) = entry_matrices(&camera, &entry.transform);
+			entry.mvp.update(painter, mvp);
+			entry.normal.update_mat3(painter, normal);
+			entry.color.update(painter, entry.material.color);
+		}
+
+		painter.paint(layer)
+	}
+}
+
+fn camera_with_aspect(camera: &PerspectiveCamera, aspect_ratio: f32) -> PerspectiveCamera {
+	let mut camera = camera.clone();
+	camera.set_aspect_ratio(aspect_ratio);
+	camera.recalculate_projection();
+	camera
+}
+
+/// Model view projection and world normal matrix of an entry.
+fn entry_matrices(camera: &PerspectiveCamera, transform: &Transform) -> (Mat4, Mat3) {
+	let model = transform.compute_matrix();
+	(camera.view_proj_mat() * model, normal_mat(model))
+}
+
+/// Indexed cube centered at the origin, with position and face normal attributes
+/// as expected by the scene shader.
+pub fn cube_buffer(size: f32) -> RenderableBuffer {
+	let half = size * 0.5;
+	let mut vertices = Vec::<[Vec3; 2]>::with_capacity(24);
+	let mut indices = Vec::<u32>::with_capacity(36);
+
+	for normal in [
+		Vec3::X,
+		Vec3::NEG_X,
+		Vec3::Y,
+		Vec3::NEG_Y,
+		Vec3::Z,
+		Vec3::NEG_Z,
+	] {
+		let u = if normal.y == 0.0 {
+			vec3(normal.z, 0.0, -normal.x)
+		} else {
+			Vec3::X
+		};
+		let v = normal.cross(u);
+		let start = vertices.len() as u32;
+		for (du, dv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+			vertices.push([(normal + u * du + v * dv) * half, normal]);
+		}
+		indices.extend([0, 1, 2, 0, 2, 3].map(|i| start + i));
+	}
+
+	RenderableBuffer {
+		vertex_count: vertices.len() as u32,
+		index_count: indices.len() as u32,
+		vertex_buffer: bytemuck::cast_slice(&vertices).to_vec(),
+		index_buffer: Some(bytemuck::cast_slice(&indices).to_vec()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{camera_with_aspect, cube_buffer, entry_matrices};
+	use trivalibs_core::{
+		glam::{vec3, Vec3},
+		rendering::{
+			camera::{CamProps, PerspectiveCamera},
+			transform::Transform,
+		},
+	};
+
+	#[test]
+	fn cube_faces_point_outwards() {
+		let cube = cube_buffer(2.0);
+		assert_eq!(cube.vertex_count, 24);
+		assert_eq!(cube.index_count, 36);
+
+		let vertices: &[[Vec3; 2]] = bytemuck::cast_slice(&cube.vertex_buffer);
+		let indices: &[u32] = bytemuck::cast_slice(cube.index_buffer.as_ref().unwrap());
+		for triangle in indices.chunks_exact(3) {
+			let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize]);
+			let winding = (b[0] - a[0]).cross(c[0] - a[0]);
+			assert!(winding.dot(a[1]) > 0.0);
+		}
+		for [pos, normal] in vertices {
+			assert_eq!(pos.abs(), Vec3::ONE);
+			assert_eq!(pos.dot(*normal), 1.0);
+		}
+	}
+
+	#[test]
+	fn entries_at_transformed_positions() {
+		let camera = PerspectiveCamera::create(CamProps {
+			fov: Some(0.8),
+			translation: Some(vec3(0.0, 0.0, 10.0)),
+			..Default::default()
+		});
+		let camera = camera_with_aspect(&camera, 16.0 / 9.0);
+		let cube = cube_buffer(1.0);
+		let vertices: &[[Vec3; 2]] = bytemuck::cast_slice(&cube.vertex_buffer);
+
+		let left = Transform::from_xyz(-3.0, 0.0, 0.0);
+		let right = Transform::from_xyz(3.0, 1.0, 0.0).with_scale(Vec3::splat(0.5));
+
+		for (transform, sign) in [(left, -1.0), (right, 1.0)] {
+			let (mvp, normal) = entry_matrices(&camera, &transform);
+			assert_eq!(normal * Vec3::Y, Vec3::Y / transform.scale.y);
+
+			let center = mvp.project_point3(Vec3::ZERO);
+			let expected = camera.world_to_ndc(transform.translation).unwrap();
+			assert!(center.abs_diff_eq(expected, 1e-5));
+
+			for [pos, _] in vertices {
+				let ndc = mvp.project_point3(*pos);
+				assert!(ndc.x * sign > 0.0);
+				assert!(ndc.x.abs() < 1.0 && ndc.y.abs() < 1.0);
+				assert!(ndc.z > 0.0 && ndc.z < 1.0);
+			}
+		}
+	}
+}
@@ -6,15 +6,89 @@ pub(crate) struct ShadeStorage {
 	pub vertex_bytes: Option<Vec<u8>>,
 	pub fragment_path: Option<String>,
 	pub fragment_bytes: Option<Vec<u8>>,
+	pub wgsl: Option<String>,
 	pub attribs: AttribsFormat,
 	pub pipeline_layout: wgpu::PipelineLayout,
 }
 
+impl ShadeStorage {
+	/// Whether the fragment shader may discard fragments.
+	/// The depth prepass runs the fragment stage of such shades, so that discarded fragments don't write depth.
+	pub(crate) fn may_discard(&self) -> bool {
+		match (&self.wgsl, &self.fragment_bytes) {
+			(Some(wgsl), _) => wgsl_discards(wgsl),
+			(None, Some(bytes)) => spirv_discards(bytes),
+			(None, None) => false,
+		}
+	}
+}
+
+fn wgsl_discards(wgsl: &str) -> bool {
+	wgsl.split(|c: char| !c.is_alphanumeric() && c != '_')
+		.any(|word| word == "discard")
+}
+
+/// Whether a SPIR-V module contains `OpKill`, `OpTerminateInvocation` or `OpDemoteToHelperInvocation`.
+fn spirv_discards(bytes: &[u8]) -> bool {
+	const SPIRV_MAGIC: u32 = 0x0723_0203;
+	const DISCARD_OPS: [u32; 3] = [252, 4416, 5380];
+
+	let words = bytes
+		.chunks_exact(4)
+		.map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]));
+	let words: Vec<u32> = match words.clone().next() {
+		Some(SPIRV_MAGIC) => words.collect(),
+		Some(magic) if magic.swap_bytes() == SPIRV_MAGIC => words.map(u32::swap_bytes).collect(),
+		_ => return false,
+	};
+
+	// the header has 5 words, followed by the instructions
+	let mut i = 5;
+	while i < words.len() {
+		let word_count = (words[i] >> 16) as usize;
+		if DISCARD_OPS.contains(&(words[i] & 0xffff)) {
+			return true;
+		}
+		if word_count == 0 {
+			break;
+		}
+		i += word_count;
+	}
+	false
+}
+
 pub struct ShadeProps<'a, Format: Into<AttribsFormat>, UType: UniformType> {
 	pub vertex_format: Format,
 	pub uniform_types: &'a [&'a UType],
 }
 
+impl<'a, Format: Into<AttribsFormat>, UType: UniformType> ShadeProps<'a, Format, UType> {
+	/// Starts a builder for a shade with the given vertex format and no uniforms.
+	pub fn builder(vertex_format: Format) -> ShadePropsBuilder<'a, Format, UType> {
+		ShadePropsBuilder(ShadeProps {
+			vertex_format,
+			uniform_types: &[],
+		})
+	}
+}
+
+/// Fluent alternative to a [`ShadeProps`] struct literal. `ShadeProps` has no default,
+/// as the vertex format is always required, so the builder starts from it.
+pub struct ShadePropsBuilder<'a, Format: Into<AttribsFormat>, UType: UniformType>(
+	ShadeProps<'a, Format, UType>,
+);
+
+impl<'a, Format: Into<AttribsFormat>, UType: UniformType> ShadePropsBuilder<'a, Format, UType> {
+	pub fn uniform_types(mut self, uniform_types: &'a [&'a UType]) -> Self {
+		self.0.uniform_types = uniform_types;
+		self
+	}
+
+	pub fn build(self) -> ShadeProps<'a, Format, UType> {
+		self.0
+	}
+}
+
 pub struct ShadeEffectProps<'a, UType: UniformType> {
 	pub uniform_types: &'a [&'a UType],
 }
@@ -92,7 +166,7 @@ impl Into<AttribsFormat> for wgpu::VertexFormat {
 	}
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Shade(pub(crate) usize);
 
 impl Shade {
@@ -121,6 +195,7 @@ impl Shade {
 			vertex_bytes: None,
 			fragment_path: None,
 			fragment_bytes: None,
+			wgsl: None,
 			attribs: format,
 			pipeline_layout,
 		};
@@ -156,6 +231,7 @@ impl Shade {
 			vertex_bytes: None,
 			fragment_path: None,
 			fragment_bytes: None,
+			wgsl: None,
 			attribs: format,
 			pipeline_layout,
 		};
@@ -190,6 +266,14 @@ impl Shade {
 		painter.shades[self.0].fragment_bytes = Some(bytes);
 	}
 
+	/// Uses a WGSL module with one vertex and one fragment entry point for both stages,
+	/// instead of SPIR-V vertex and fragment bytes.
+	/// Modules of effect shades only need the fragment entry point, as effects are drawn
+	/// with the built-in full screen triangle, which passes the texture coordinate at location 0.
+	pub fn set_wgsl(&self, painter: &mut Painter, source: &str) {
+		painter.shades[self.0].wgsl = Some(source.to_string());
+	}
+
 	pub(crate) fn load_fragment_from_path(&self, painter: &mut Painter) {
 		if let Some(shader_path) = &painter.shades[self.0].fragment_path {
 			let bytes = fs::read(shader_path).expect("Failed to read fragment shader file");
@@ -240,3 +324,42 @@ macro_rules! load_vertex_shader {
 		$shade.set_vertex_bytes($painter, include_bytes!($path).to_vec());
 	};
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use wgpu::VertexFormat::{Float32x2, Float32x3};
+
+	#[test]
+	fn builder_equals_struct_literal() {
+		let literal: ShadeProps<_, wgpu::BindGroupLayout> = ShadeProps {
+			vertex_format: vec![Float32x3, Float32x2],
+			uniform_types: &[],
+		};
+		let built: ShadeProps<_, wgpu::BindGroupLayout> =
+			ShadeProps::builder(vec![Float32x3, Float32x2]).build();
+
+		assert_eq!(built.vertex_format, literal.vertex_format);
+		assert_eq!(built.uniform_types.len(), literal.uniform_types.len());
+	}
+
+	#[test]
+	fn discard_detection() {
+		assert!(wgsl_discards("if a < 0.5 {\n\tdiscard;\n}"));
+		assert!(!wgsl_discards("let discarded = 1.0;"));
+
+		let spirv = |ops: &[u32]| {
+			[0x0723_0203, 0x0001_0000, 0, 8, 0]
+				.iter()
+				.chain(ops)
+				.flat_map(|w| w.to_le_bytes())
+				.collect::<Vec<u8>>()
+		};
+		let op_return = (1 << 16) | 253;
+		let op_kill = (1 << 16) | 252;
+		assert!(spirv_discards(&spirv(&[op_return, op_kill])));
+		// OpName with the opcode of OpKill as operand
+		assert!(!spirv_discards(&spirv(&[(3 << 16) | 5, 1, 252, op_return])));
+		assert!(!spirv_discards(&[]));
+	}
+}
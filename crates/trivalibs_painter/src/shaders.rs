@@ -22,3 +22,66 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4f {
 	return textureSample(ourTexture, ourSampler, in.coord);
 }
 "#;
+
+/// Copies the pixels of a texture of the same size as the target, e.g. to composite
+/// the target of a stencil effect through the mask. Drawn with the vertex stage of [`FULL_SCREEN_QUAD`].
+pub const COPY_PIXELS: &str = r#"
+@group(0) @binding(0) var source: texture_2d<f32>;
+
+@fragment
+fn fs_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+	return textureLoad(source, vec2i(position.xy), 0);
+}
+"#;
+
+/// Default shader of [`crate::scene::Scene`]. Position and normal attributes,
+/// model view projection and world normal matrix in the vertex stage, and a material color.
+pub const SCENE: &str = r#"
+@group(0) @binding(0) var<uniform> mvp: mat4x4f;
+@group(1) @binding(0) var<uniform> normal_mat: mat3x3f;
+@group(2) @binding(0) var<uniform> color: vec4f;
+
+struct VertexOutput {
+  @builtin(position) position: vec4f,
+  @location(0) normal: vec3f,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3f, @location(1) normal: vec3f) -> VertexOutput {
+  var out: VertexOutput;
+  out.position = mvp * vec4f(position, 1.0);
+  out.normal = normal_mat * normal;
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+  let light = dot(normalize(in.normal), normalize(vec3f(0.4, 1.0, 0.7))) * 0.4 + 0.6;
+  return vec4f(color.rgb * light, color.a);
+}
+"#;
+
+/// Downsamples the previous mip level of a texture into the next one, see `Texture::generate_mipmaps`.
+pub const MIPMAP: &str = r#"
+struct VertexOutput {
+  @builtin(position) position: vec4f,
+  @location(0) uv: vec2f,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+  var out: VertexOutput;
+  let coord = vec2f(f32((vertex_index << 1) & 2), f32(vertex_index & 2));
+  out.position = vec4f(coord * 2.0 - 1.0, 0.0, 1.0);
+  out.uv = vec2f(coord.x, 1.0 - coord.y);
+  return out;
+}
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+  return textureSample(source, source_sampler, in.uv);
+}
+"#;
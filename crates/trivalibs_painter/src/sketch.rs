@@ -1,5 +1,11 @@
-use super::{form::Form, shade::Shade, uniform::Uniform, Painter};
-use std::collections::BTreeMap;
+use super::{
+	form::Form,
+	shade::Shade,
+	stencil::Stencil,
+	uniform::{Uniform, UniformKind},
+	Painter,
+};
+use std::{collections::BTreeMap, ops::Range};
 
 pub(crate) struct SketchStorage {
 	pub uniforms: BTreeMap<u32, Uniform>,
@@ -10,14 +16,22 @@ pub(crate) struct SketchStorage {
 	pub depth_test: bool,
 	pub cull_mode: Option<wgpu::Face>,
 	pub blend_state: wgpu::BlendState,
+	pub draw_range: Option<Range<u32>>,
+	pub stencil: Option<Stencil>,
 }
 
+#[derive(Debug, PartialEq)]
 pub struct SketchProps {
 	pub uniforms: BTreeMap<u32, Uniform>,
 	pub instances: Vec<BTreeMap<u32, Uniform>>,
 	pub cull_mode: Option<wgpu::Face>,
 	pub depth_test: bool,
 	pub blend_state: wgpu::BlendState,
+	/// Restricts drawing to a range of the form's indices, or vertices if the form has no index buffer.
+	/// Allows to draw sections of a combined buffer with different shades and uniforms.
+	pub draw_range: Option<Range<u32>>,
+	/// Stencil test and write of the sketch, e.g. [`Stencil::write`] to mark a mask for effects.
+	pub stencil: Option<Stencil>,
 }
 
 impl Default for SketchProps {
@@ -28,11 +42,75 @@ impl Default for SketchProps {
 			cull_mode: Some(wgpu::Face::Back),
 			depth_test: false,
 			blend_state: wgpu::BlendState::REPLACE,
+			draw_range: None,
+			stencil: None,
 		}
 	}
 }
 
-#[derive(Clone, Copy, Debug)]
+impl SketchProps {
+	pub fn builder() -> SketchPropsBuilder {
+		SketchPropsBuilder(SketchProps::default())
+	}
+}
+
+/// Fluent alternative to spreading [`SketchProps::default`] into a struct literal.
+pub struct SketchPropsBuilder(SketchProps);
+
+impl SketchPropsBuilder {
+	pub fn uniforms(mut self, uniforms: BTreeMap<u32, Uniform>) -> Self {
+		self.0.uniforms = uniforms;
+		self
+	}
+
+	pub fn instances(mut self, instances: Vec<BTreeMap<u32, Uniform>>) -> Self {
+		self.0.instances = instances;
+		self
+	}
+
+	pub fn cull_mode(mut self, cull_mode: Option<wgpu::Face>) -> Self {
+		self.0.cull_mode = cull_mode;
+		self
+	}
+
+	pub fn depth_test(mut self, depth_test: bool) -> Self {
+		self.0.depth_test = depth_test;
+		self
+	}
+
+	pub fn blend_state(mut self, blend_state: wgpu::BlendState) -> Self {
+		self.0.blend_state = blend_state;
+		self
+	}
+
+	pub fn draw_range(mut self, draw_range: Range<u32>) -> Self {
+		self.0.draw_range = Some(draw_range);
+		self
+	}
+
+	pub fn stencil(mut self, stencil: Stencil) -> Self {
+		self.0.stencil = Some(stencil);
+		self
+	}
+
+	pub fn build(self) -> SketchProps {
+		self.0
+	}
+}
+
+/// Resolved state of a sketch, see [`Painter::sketch_debug`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SketchDebug {
+	pub shade: Shade,
+	pub form: Form,
+	/// Bind group index and kind of each sketch uniform.
+	pub uniform_bindings: Vec<(u32, UniformKind)>,
+	/// Uniform bindings set for each instance, on top of the sketch uniforms.
+	pub instance_bindings: Vec<Vec<(u32, UniformKind)>>,
+	pub vertex_layout: Vec<wgpu::VertexAttribute>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Sketch(pub(crate) usize);
 
 impl Sketch {
@@ -62,6 +140,7 @@ impl Sketch {
 			(props.blend_state.color.operation as u8)
 				.to_le_bytes()
 				.to_vec(),
+			Stencil::pipeline_key(props.stencil).to_vec(),
 		]
 		.into_iter()
 		.flatten()
@@ -76,6 +155,8 @@ impl Sketch {
 			depth_test: props.depth_test,
 			cull_mode: props.cull_mode,
 			blend_state: props.blend_state,
+			draw_range: props.draw_range.clone(),
+			stencil: props.stencil,
 		};
 
 		painter.sketches.push(sketch);
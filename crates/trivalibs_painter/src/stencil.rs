@@ -0,0 +1,66 @@
+/// Stencil test of a sketch or effect, against the stencil aspect of the layer depth texture.
+/// Layers with stencil tests need a depth format with stencil aspect, e.g. `Depth24PlusStencil8`.
+/// The stencil is cleared to 0 at the start of each paint of the layer.
+///
+/// Sketches with [`Stencil::write`] mark a mask region,
+/// and effects with [`Stencil::equal`] only write to the pixels inside of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stencil {
+	/// Compares `reference` with the stored stencil value. Fragments failing it are discarded.
+	pub compare: wgpu::CompareFunction,
+	/// Applied to the stored stencil value of fragments passing the stencil and depth tests.
+	pub pass_op: wgpu::StencilOperation,
+	pub reference: u32,
+}
+
+impl Stencil {
+	/// Draws everywhere, and writes `reference` into the stencil.
+	pub fn write(reference: u32) -> Self {
+		Self {
+			compare: wgpu::CompareFunction::Always,
+			pass_op: wgpu::StencilOperation::Replace,
+			reference,
+		}
+	}
+
+	/// Only draws where the stencil equals `reference`.
+	pub fn equal(reference: u32) -> Self {
+		Self {
+			compare: wgpu::CompareFunction::Equal,
+			pass_op: wgpu::StencilOperation::Keep,
+			reference,
+		}
+	}
+
+	/// Only draws where the stencil differs from `reference`.
+	pub fn not_equal(reference: u32) -> Self {
+		Self {
+			compare: wgpu::CompareFunction::NotEqual,
+			pass_op: wgpu::StencilOperation::Keep,
+			reference,
+		}
+	}
+
+	pub(crate) fn state(&self) -> wgpu::StencilState {
+		let face = wgpu::StencilFaceState {
+			compare: self.compare,
+			fail_op: wgpu::StencilOperation::Keep,
+			depth_fail_op: wgpu::StencilOperation::Keep,
+			pass_op: self.pass_op,
+		};
+		wgpu::StencilState {
+			front: face,
+			back: face,
+			read_mask: !0,
+			write_mask: !0,
+		}
+	}
+
+	/// Pipeline key of the stencil state. The reference is set per render pass.
+	pub(crate) fn pipeline_key(stencil: Option<Stencil>) -> [u8; 2] {
+		match stencil {
+			Some(s) => [s.compare as u8, s.pass_op as u8 + 1],
+			None => [0, 0],
+		}
+	}
+}
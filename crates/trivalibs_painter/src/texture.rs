@@ -1,4 +1,9 @@
-use super::{uniform::Uniform, Painter};
+use super::{
+	layer::map_format_to_u8,
+	shaders::MIPMAP,
+	uniform::{Uniform, UniformKind, UniformStorage},
+	Painter,
+};
 use trivalibs_core::utils::default;
 use wgpu::BindGroupLayout;
 
@@ -7,19 +12,117 @@ pub struct Texture2DProps {
 	pub height: u32,
 	pub format: wgpu::TextureFormat,
 	pub usage: wgpu::TextureUsages,
+	/// Number of mip levels, 1 for none. Use [`full_mip_level_count`] for a complete chain,
+	/// and fill the levels with [`Texture::generate_mipmaps`]. Multisampled textures always have 1.
+	pub mip_levels: u32,
 }
 
+/// Number of mip levels down to 1x1 for a texture of the given size.
+pub fn full_mip_level_count(width: u32, height: u32) -> u32 {
+	32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Pipeline key prefix of the mipmap blit pipelines, followed by the texture format.
+const MIPMAP_PIPELINE: u8 = 0xfe;
+
+/// Size, sample count and format of a depth texture.
+/// Create it with [`TextureDepthProps::new`] and override the remaining fields as needed,
+/// so that new fields don't break existing code.
+#[non_exhaustive]
 pub struct TextureDepthProps {
 	pub width: u32,
 	pub height: u32,
+	pub multisampled: bool,
+	pub format: wgpu::TextureFormat,
+}
+
+impl TextureDepthProps {
+	/// Single sampled depth texture of [`DEFAULT_DEPTH_FORMAT`].
+	pub fn new(width: u32, height: u32) -> Self {
+		Self {
+			width,
+			height,
+			multisampled: false,
+			format: DEFAULT_DEPTH_FORMAT,
+		}
+	}
+}
+
+/// Faces of a cubemap, in the order +X, -X, +Y, -Y, +Z, -Z as sampled by `texture_cube` in shaders.
+/// Each face has `size` x `size` pixels of the format.
+pub struct TextureCubeProps<'a> {
+	pub size: u32,
+	pub format: wgpu::TextureFormat,
+	pub faces: [&'a [u8]; 6],
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TextureError {
+	/// The format has no byte size per pixel, so texture data can't be written in it.
+	UncopyableFormat(wgpu::TextureFormat),
+}
+
+impl std::fmt::Display for TextureError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			TextureError::UncopyableFormat(format) => {
+				write!(
+					f,
+					"texture data can't be written in the {:?} format",
+					format
+				)
+			}
+		}
+	}
 }
 
+impl std::error::Error for TextureError {}
+
+/// Format and usage of a texture decoded from an image file, see [`Texture::from_image_bytes`].
+/// The format needs 4 bytes per pixel, as the image is decoded to RGBA8.
+pub struct ImageTextureProps {
+	pub format: wgpu::TextureFormat,
+	pub usage: wgpu::TextureUsages,
+	/// Creates and fills a full mip chain, and adds `RENDER_ATTACHMENT` to the usage for it.
+	pub mipmaps: bool,
+}
+
+impl Default for ImageTextureProps {
+	fn default() -> Self {
+		Self {
+			format: wgpu::TextureFormat::Rgba8UnormSrgb,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			mipmaps: false,
+		}
+	}
+}
+
+/// Depth format of layers, that don't request a specific one.
+pub const DEFAULT_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24Plus;
+
+/// Sample count of multisampled render targets.
+pub(crate) const MULTISAMPLE_COUNT: u32 = 4;
+
+pub(crate) fn sample_count(multisampled: bool) -> u32 {
+	if multisampled {
+		MULTISAMPLE_COUNT
+	} else {
+		1
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct SamplerProps {
 	pub address_mode_u: wgpu::AddressMode,
 	pub address_mode_v: wgpu::AddressMode,
+	/// Addressing of the third coordinate, only relevant for cubemaps and 3D textures.
+	pub address_mode_w: wgpu::AddressMode,
 	pub mag_filter: wgpu::FilterMode,
 	pub min_filter: wgpu::FilterMode,
-	pub sample_depth: bool,
+	/// Filter between mip levels, only relevant for textures with mipmaps.
+	pub mipmap_filter: wgpu::FilterMode,
+	/// Makes this a comparison sampler, e.g. `LessEqual` for shadow maps.
+	pub compare: Option<wgpu::CompareFunction>,
 }
 
 impl Default for SamplerProps {
@@ -27,13 +130,123 @@ impl Default for SamplerProps {
 		Self {
 			address_mode_u: wgpu::AddressMode::ClampToEdge,
 			address_mode_v: wgpu::AddressMode::ClampToEdge,
+			address_mode_w: wgpu::AddressMode::ClampToEdge,
 			mag_filter: wgpu::FilterMode::Linear,
 			min_filter: wgpu::FilterMode::Linear,
-			sample_depth: false,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			compare: None,
 		}
 	}
 }
 
+impl SamplerProps {
+	/// Samples the nearest texel without any filtering, e.g. for pixel art or data textures.
+	pub fn nearest() -> Self {
+		Self {
+			mag_filter: wgpu::FilterMode::Nearest,
+			min_filter: wgpu::FilterMode::Nearest,
+			mipmap_filter: wgpu::FilterMode::Nearest,
+			..default()
+		}
+	}
+
+	/// Repeats the texture in all directions instead of clamping to its edge.
+	pub fn repeat(self) -> Self {
+		Self {
+			address_mode_u: wgpu::AddressMode::Repeat,
+			address_mode_v: wgpu::AddressMode::Repeat,
+			address_mode_w: wgpu::AddressMode::Repeat,
+			..self
+		}
+	}
+}
+
+/// Texture to buffer copies need rows aligned to `COPY_BYTES_PER_ROW_ALIGNMENT` (256 bytes).
+#[cfg(not(target_arch = "wasm32"))]
+fn padded_bytes_per_row(row_bytes: u32) -> u32 {
+	let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+	row_bytes.div_ceil(align) * align
+}
+
+/// Strips the row padding of a texture copy.
+#[cfg(not(target_arch = "wasm32"))]
+fn unpad_rows(data: &[u8], row_bytes: u32, padded_row_bytes: u32) -> Vec<u8> {
+	data.chunks(padded_row_bytes as usize)
+		.flat_map(|row| &row[..row_bytes as usize])
+		.copied()
+		.collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn bgra_to_rgba(pixels: &mut [u8]) {
+	for pixel in pixels.chunks_exact_mut(4) {
+		pixel.swap(0, 2);
+	}
+}
+
+fn sampler_descriptor(props: &SamplerProps) -> wgpu::SamplerDescriptor<'static> {
+	wgpu::SamplerDescriptor {
+		address_mode_u: props.address_mode_u,
+		address_mode_v: props.address_mode_v,
+		address_mode_w: props.address_mode_w,
+		mag_filter: props.mag_filter,
+		min_filter: props.min_filter,
+		mipmap_filter: props.mipmap_filter,
+		compare: props.compare,
+		..Default::default()
+	}
+}
+
+/// Direction in which [`Texture::create_gradient`] blends from the first to the second color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientDirection {
+	/// From left to right.
+	Horizontal,
+	/// From the top row to the bottom row.
+	Vertical,
+	/// From the top left to the bottom right corner.
+	Diagonal,
+}
+
+/// RGBA pixels of a square checkerboard with `cols` tiles per row, starting with `color_a` at the top left.
+fn checkerboard_pixels(size: u32, cols: u32, color_a: [u8; 4], color_b: [u8; 4]) -> Vec<u8> {
+	let cols = cols.clamp(1, size.max(1));
+	(0..size * size)
+		.flat_map(|i| {
+			let (x, y) = (i % size, i / size);
+			let tile = x * cols / size + y * cols / size;
+			if tile.is_multiple_of(2) {
+				color_a
+			} else {
+				color_b
+			}
+		})
+		.collect()
+}
+
+/// RGBA pixels of a square gradient, with the pixel centers interpolated between the colors.
+fn gradient_pixels(size: u32, from: [u8; 4], to: [u8; 4], direction: GradientDirection) -> Vec<u8> {
+	let last = (size.max(2) - 1) as f32;
+	(0..size * size)
+		.flat_map(|i| {
+			let (x, y) = ((i % size) as f32, (i / size) as f32);
+			let t = match direction {
+				GradientDirection::Horizontal => x / last,
+				GradientDirection::Vertical => y / last,
+				GradientDirection::Diagonal => (x + y) / (2.0 * last),
+			};
+			[0, 1, 2, 3]
+				.map(|c| (from[c] as f32 + (to[c] as f32 - from[c] as f32) * t).round() as u8)
+		})
+		.collect()
+}
+
+/// Decodes PNG or JPEG bytes to RGBA8 pixels, and returns them with the width and height.
+fn decode_rgba8(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), image::ImageError> {
+	let image = image::load_from_memory(bytes)?.into_rgba8();
+	Ok((image.width(), image.height(), image.into_raw()))
+}
+
 pub(crate) struct TextureStorage {
 	pub texture: wgpu::Texture,
 	pub view: wgpu::TextureView,
@@ -42,21 +255,87 @@ pub(crate) struct TextureStorage {
 #[derive(Clone, Copy)]
 pub struct Texture(pub(crate) usize);
 
-fn create_2d(painter: &mut Painter, props: &Texture2DProps) -> wgpu::Texture {
-	painter.device.create_texture(&wgpu::TextureDescriptor {
+/// The sRGB or linear counterpart of a format, that views of the texture can reinterpret it as.
+pub(crate) fn srgb_view_formats(format: wgpu::TextureFormat) -> Vec<wgpu::TextureFormat> {
+	let srgb = format.add_srgb_suffix();
+	let linear = format.remove_srgb_suffix();
+	[srgb, linear]
+		.into_iter()
+		.filter(|f| *f != format)
+		.collect()
+}
+
+fn texture_2d_descriptor<'a>(
+	props: &Texture2DProps,
+	multisampled: bool,
+	view_formats: &'a [wgpu::TextureFormat],
+) -> wgpu::TextureDescriptor<'a> {
+	wgpu::TextureDescriptor {
 		label: None,
 		size: wgpu::Extent3d {
 			width: props.width,
 			height: props.height,
 			depth_or_array_layers: 1,
 		},
-		mip_level_count: 1,
-		sample_count: 1,
+		mip_level_count: if multisampled {
+			1
+		} else {
+			props
+				.mip_levels
+				.clamp(1, full_mip_level_count(props.width, props.height))
+		},
+		sample_count: sample_count(multisampled),
 		dimension: wgpu::TextureDimension::D2,
 		format: props.format,
 		usage: props.usage,
+		view_formats,
+	}
+}
+
+fn create_2d(painter: &mut Painter, props: &Texture2DProps, multisampled: bool) -> wgpu::Texture {
+	// Listing view formats fails validation on devices without support, e.g. on WebGL2.
+	let view_formats = if painter.supports_view_formats() {
+		srgb_view_formats(props.format)
+	} else {
+		vec![]
+	};
+	painter
+		.device
+		.create_texture(&texture_2d_descriptor(props, multisampled, &view_formats))
+}
+
+fn cube_descriptor(size: u32, format: wgpu::TextureFormat) -> wgpu::TextureDescriptor<'static> {
+	wgpu::TextureDescriptor {
+		label: None,
+		size: wgpu::Extent3d {
+			width: size,
+			height: size,
+			depth_or_array_layers: 6,
+		},
+		mip_level_count: 1,
+		sample_count: 1,
+		dimension: wgpu::TextureDimension::D2,
+		format,
+		usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
 		view_formats: &[],
-	})
+	}
+}
+
+fn cube_view_descriptor() -> wgpu::TextureViewDescriptor<'static> {
+	wgpu::TextureViewDescriptor {
+		dimension: Some(wgpu::TextureViewDimension::Cube),
+		array_layer_count: Some(6),
+		..default()
+	}
+}
+
+/// Byte length of one row of a cubemap face, or an error for formats without a single
+/// copyable aspect, e.g. combined depth stencil formats.
+fn cube_row_len(size: u32, format: wgpu::TextureFormat) -> Result<u32, TextureError> {
+	format
+		.block_copy_size(None)
+		.map(|block_size| size * block_size)
+		.ok_or(TextureError::UncopyableFormat(format))
 }
 
 fn create_depth(painter: &mut Painter, props: &TextureDepthProps) -> wgpu::Texture {
@@ -68,9 +347,9 @@ fn create_depth(painter: &mut Painter, props: &TextureDepthProps) -> wgpu::Textu
 			depth_or_array_layers: 1,
 		},
 		mip_level_count: 1,
-		sample_count: 1,
+		sample_count: sample_count(props.multisampled),
 		dimension: wgpu::TextureDimension::D2,
-		format: wgpu::TextureFormat::Depth24Plus,
+		format: props.format,
 		usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
 		view_formats: &[],
 	})
@@ -78,7 +357,20 @@ fn create_depth(painter: &mut Painter, props: &TextureDepthProps) -> wgpu::Textu
 
 impl Texture {
 	pub fn create_2d(painter: &mut Painter, props: &Texture2DProps) -> Self {
-		let texture = create_2d(painter, props);
+		Self::create_2d_samples(painter, props, false)
+	}
+
+	/// Multisampled render target, that is resolved into a single sampled texture for sampling.
+	pub fn create_multisampled(painter: &mut Painter, props: &Texture2DProps) -> Self {
+		Self::create_2d_samples(painter, props, true)
+	}
+
+	fn create_2d_samples(
+		painter: &mut Painter,
+		props: &Texture2DProps,
+		multisampled: bool,
+	) -> Self {
+		let texture = create_2d(painter, props, multisampled);
 		let view = texture.create_view(&default());
 		let storage = TextureStorage { texture, view };
 		painter.textures.push(storage);
@@ -86,8 +378,69 @@ impl Texture {
 		Self(painter.textures.len() - 1)
 	}
 
+	/// Cubemap filled with the six faces, e.g. for skyboxes and environment lighting.
+	/// Panics if a face doesn't have the byte length of `size` x `size` pixels,
+	/// or if the faces can't be written in `format`, see [`Texture::try_create_cube`].
+	pub fn create_cube(painter: &mut Painter, props: &TextureCubeProps) -> Self {
+		Self::try_create_cube(painter, props).unwrap_or_else(|e| panic!("{}", e))
+	}
+
+	/// Cubemap filled with the six faces, or an error if `format` has no byte size per pixel.
+	/// Panics if a face doesn't have the byte length of `size` x `size` pixels.
+	pub fn try_create_cube(
+		painter: &mut Painter,
+		props: &TextureCubeProps,
+	) -> Result<Self, TextureError> {
+		let row_len = cube_row_len(props.size, props.format)?;
+		for (i, face) in props.faces.iter().enumerate() {
+			assert_eq!(
+				face.len(),
+				(row_len * props.size) as usize,
+				"cubemap face {} has the wrong size",
+				i
+			);
+		}
+
+		let texture = painter
+			.device
+			.create_texture(&cube_descriptor(props.size, props.format));
+
+		for (layer, face) in props.faces.iter().enumerate() {
+			painter.queue.write_texture(
+				wgpu::ImageCopyTexture {
+					texture: &texture,
+					mip_level: 0,
+					origin: wgpu::Origin3d {
+						x: 0,
+						y: 0,
+						z: layer as u32,
+					},
+					aspect: wgpu::TextureAspect::All,
+				},
+				face,
+				wgpu::ImageDataLayout {
+					offset: 0,
+					bytes_per_row: Some(row_len),
+					rows_per_image: Some(props.size),
+				},
+				wgpu::Extent3d {
+					width: props.size,
+					height: props.size,
+					depth_or_array_layers: 1,
+				},
+			);
+		}
+
+		let view = texture.create_view(&cube_view_descriptor());
+		painter.textures.push(TextureStorage { texture, view });
+
+		Ok(Self(painter.textures.len() - 1))
+	}
+
+	/// Replaces the texture with a new one, keeping its sample count.
 	pub fn replace_2d(&self, painter: &mut Painter, props: &Texture2DProps) {
-		let texture = create_2d(painter, props);
+		let multisampled = painter.textures[self.0].texture.sample_count() > 1;
+		let texture = create_2d(painter, props, multisampled);
 		let view = texture.create_view(&default());
 		let storage = TextureStorage { texture, view };
 
@@ -141,17 +494,327 @@ impl Texture {
 		);
 	}
 
+	/// Copies the first mip level back to the CPU, blocking until the GPU finished.
+	/// Returns tightly packed rows in the byte layout of the texture format,
+	/// e.g. 4 bytes per pixel for 8 bit formats, and 8 for `Rgba16Float`.
+	/// `Bgra8` formats are converted to RGBA. The texture needs `COPY_SRC` usage.
+	/// Only available on native targets, as browsers can't block on the GPU.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn read_pixels(&self, painter: &Painter) -> Vec<u8> {
+		let texture = &painter.textures[self.0].texture;
+		assert!(
+			texture.usage().contains(wgpu::TextureUsages::COPY_SRC),
+			"reading pixels requires COPY_SRC usage"
+		);
+
+		let size = texture.size();
+		let format = texture.format();
+		let row_bytes = format.block_copy_size(None).expect("color texture format") * size.width;
+		let padded_row_bytes = padded_bytes_per_row(row_bytes);
+
+		let buffer = painter.device.create_buffer(&wgpu::BufferDescriptor {
+			label: Some("read_pixels_buffer"),
+			size: (padded_row_bytes * size.height) as u64,
+			usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+			mapped_at_creation: false,
+		});
+
+		let mut encoder = painter
+			.device
+			.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+				label: Some("read_pixels_encoder"),
+			});
+		encoder.copy_texture_to_buffer(
+			texture.as_image_copy(),
+			wgpu::ImageCopyBuffer {
+				buffer: &buffer,
+				layout: wgpu::ImageDataLayout {
+					offset: 0,
+					bytes_per_row: Some(padded_row_bytes),
+					rows_per_image: Some(size.height),
+				},
+			},
+			wgpu::Extent3d {
+				depth_or_array_layers: 1,
+				..size
+			},
+		);
+		painter.queue.submit(Some(encoder.finish()));
+
+		let slice = buffer.slice(..);
+		let (sender, receiver) = std::sync::mpsc::channel();
+		slice.map_async(wgpu::MapMode::Read, move |result| {
+			let _ = sender.send(result);
+		});
+		painter.device.poll(wgpu::Maintain::Wait);
+		receiver
+			.recv()
+			.expect("buffer mapping callback")
+			.expect("failed to map pixel buffer");
+
+		let mut pixels = unpad_rows(&slice.get_mapped_range(), row_bytes, padded_row_bytes);
+		buffer.unmap();
+
+		if matches!(
+			format,
+			wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+		) {
+			bgra_to_rgba(&mut pixels);
+		}
+		pixels
+	}
+
+	fn create_filled(painter: &mut Painter, size: u32, pixels: &[u8]) -> Self {
+		let texture = Self::create_2d(
+			painter,
+			&Texture2DProps {
+				width: size,
+				height: size,
+				format: wgpu::TextureFormat::Rgba8UnormSrgb,
+				usage: wgpu::TextureUsages::TEXTURE_BINDING
+					| wgpu::TextureUsages::COPY_DST
+					| wgpu::TextureUsages::COPY_SRC,
+				mip_levels: 1,
+			},
+		);
+		texture.fill_2d(painter, pixels);
+		texture
+	}
+
+	/// Square `Rgba8UnormSrgb` test texture with `cols` x `cols` tiles,
+	/// alternating between the sRGB colors and starting with `color_a` at the top left.
+	/// Useful to check UVs and sampler settings of a new shader.
+	pub fn create_checkerboard(
+		painter: &mut Painter,
+		size: u32,
+		cols: u32,
+		color_a: [u8; 4],
+		color_b: [u8; 4],
+	) -> Self {
+		let pixels = checkerboard_pixels(size, cols, color_a, color_b);
+		Self::create_filled(painter, size, &pixels)
+	}
+
+	/// Square `Rgba8UnormSrgb` test texture, blending between the sRGB colors in the given direction.
+	pub fn create_gradient(
+		painter: &mut Painter,
+		size: u32,
+		from: [u8; 4],
+		to: [u8; 4],
+		direction: GradientDirection,
+	) -> Self {
+		let pixels = gradient_pixels(size, from, to, direction);
+		Self::create_filled(painter, size, &pixels)
+	}
+
+	/// Decodes a PNG or JPEG image and uploads it into a new texture of the image size.
+	pub fn from_image_bytes(
+		painter: &mut Painter,
+		bytes: &[u8],
+		props: &ImageTextureProps,
+	) -> Result<Self, image::ImageError> {
+		let (width, height, pixels) = decode_rgba8(bytes)?;
+		let texture = Self::create_2d(
+			painter,
+			&Texture2DProps {
+				width,
+				height,
+				format: props.format,
+				usage: if props.mipmaps {
+					props.usage | wgpu::TextureUsages::RENDER_ATTACHMENT
+				} else {
+					props.usage
+				},
+				mip_levels: if props.mipmaps {
+					full_mip_level_count(width, height)
+				} else {
+					1
+				},
+			},
+		);
+		texture.fill_2d(painter, &pixels);
+		if props.mipmaps {
+			texture.generate_mipmaps(painter);
+		}
+		Ok(texture)
+	}
+
+	pub fn mip_level_count(&self, painter: &Painter) -> u32 {
+		painter.textures[self.0].texture.mip_level_count()
+	}
+
+	/// Fills all mip levels after the first one, by rendering each level downsampled from the previous one.
+	/// The texture needs a filterable color format and `RENDER_ATTACHMENT` usage.
+	/// Call it again after filling the first level with new data.
+	pub fn generate_mipmaps(&self, painter: &mut Painter) {
+		let texture = &painter.textures[self.0].texture;
+		let levels = texture.mip_level_count();
+		if levels < 2 {
+			return;
+		}
+		assert!(
+			texture
+				.usage()
+				.contains(wgpu::TextureUsages::RENDER_ATTACHMENT),
+			"generating mipmaps requires RENDER_ATTACHMENT usage"
+		);
+
+		let format = texture.format();
+		let pipeline_key = vec![MIPMAP_PIPELINE, map_format_to_u8(format)];
+		if !painter.pipelines.contains_key(&pipeline_key) {
+			let shader = painter
+				.device
+				.create_shader_module(wgpu::ShaderModuleDescriptor {
+					label: None,
+					source: wgpu::ShaderSource::Wgsl(MIPMAP.into()),
+				});
+			let pipeline = painter
+				.device
+				.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+					label: None,
+					layout: None,
+					vertex: wgpu::VertexState {
+						module: &shader,
+						entry_point: Some("vs_main"),
+						buffers: &[],
+						compilation_options: default(),
+					},
+					fragment: Some(wgpu::FragmentState {
+						module: &shader,
+						entry_point: Some("fs_main"),
+						targets: &[Some(format.into())],
+						compilation_options: default(),
+					}),
+					primitive: default(),
+					depth_stencil: None,
+					multisample: default(),
+					multiview: None,
+					cache: None,
+				});
+			painter.pipelines.insert(pipeline_key.clone(), pipeline);
+		}
+
+		let pipeline = &painter.pipelines[&pipeline_key];
+		let texture = &painter.textures[self.0].texture;
+		let sampler = Self::create_sampler(painter, &default());
+		let views = (0..levels)
+			.map(|level| {
+				texture.create_view(&wgpu::TextureViewDescriptor {
+					base_mip_level: level,
+					mip_level_count: Some(1),
+					..default()
+				})
+			})
+			.collect::<Vec<_>>();
+
+		let mut encoder = painter
+			.device
+			.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+		for level in 1..views.len() {
+			let bind_group = painter
+				.device
+				.create_bind_group(&wgpu::BindGroupDescriptor {
+					label: None,
+					layout: &pipeline.get_bind_group_layout(0),
+					entries: &[
+						wgpu::BindGroupEntry {
+							binding: 0,
+							resource: wgpu::BindingResource::TextureView(&views[level - 1]),
+						},
+						wgpu::BindGroupEntry {
+							binding: 1,
+							resource: wgpu::BindingResource::Sampler(&sampler),
+						},
+					],
+				});
+
+			let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+				label: None,
+				color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+					view: &views[level],
+					resolve_target: None,
+					ops: wgpu::Operations {
+						load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+						store: wgpu::StoreOp::Store,
+					},
+				})],
+				depth_stencil_attachment: None,
+				timestamp_writes: None,
+				occlusion_query_set: None,
+			});
+			rpass.set_pipeline(pipeline);
+			rpass.set_bind_group(0, &bind_group, &[]);
+			rpass.draw(0..3, 0..1);
+		}
+
+		painter.queue.submit(Some(encoder.finish()));
+	}
+
 	pub fn create_sampler(painter: &Painter, props: &SamplerProps) -> wgpu::Sampler {
-		painter.device.create_sampler(&wgpu::SamplerDescriptor {
-			address_mode_u: props.address_mode_u,
-			address_mode_v: props.address_mode_v,
-			address_mode_w: wgpu::AddressMode::ClampToEdge,
-			mag_filter: props.mag_filter,
-			min_filter: props.min_filter,
-			mipmap_filter: wgpu::FilterMode::Nearest,
-			compare: props.sample_depth.then(|| wgpu::CompareFunction::LessEqual),
-			..Default::default()
-		})
+		painter.device.create_sampler(&sampler_descriptor(props))
+	}
+
+	/// Returns `None` if the texture can't be viewed as `format`,
+	/// because it is no sRGB counterpart, or the device doesn't support reinterpreting formats.
+	pub(crate) fn reinterpreted_view(
+		&self,
+		painter: &mut Painter,
+		format: wgpu::TextureFormat,
+		layout: &BindGroupLayout,
+		sampler: &wgpu::Sampler,
+	) -> Option<UniformTex2D> {
+		let texture_format = painter.textures[self.0].texture.format();
+		if format == texture_format {
+			return Some(UniformTex2D::new(painter, layout, *self, sampler));
+		}
+		if !painter.supports_view_formats() || !srgb_view_formats(texture_format).contains(&format)
+		{
+			return None;
+		}
+
+		let view = painter.textures[self.0]
+			.texture
+			.create_view(&wgpu::TextureViewDescriptor {
+				format: Some(format),
+				..default()
+			});
+		Some(UniformTex2D::with_view(
+			painter,
+			layout,
+			*self,
+			Some(&view),
+			sampler,
+		))
+	}
+
+	/// Texture uniform that samples a unorm 2D texture as sRGB, decoding the color values to linear.
+	/// Textures that are sRGB already are sampled with their default view.
+	/// Returns `None` if the device can't reinterpret texture formats, e.g. on WebGL2.
+	pub fn srgb_view(
+		&self,
+		painter: &mut Painter,
+		layout: &BindGroupLayout,
+		sampler: &wgpu::Sampler,
+	) -> Option<UniformTex2D> {
+		let format = painter.textures[self.0].texture.format().add_srgb_suffix();
+		self.reinterpreted_view(painter, format, layout, sampler)
+	}
+
+	/// Texture uniform that samples an sRGB 2D texture as unorm, returning the stored values as they are.
+	/// Textures that are linear already are sampled with their default view.
+	/// Returns `None` if the device can't reinterpret texture formats, e.g. on WebGL2.
+	pub fn linear_view(
+		&self,
+		painter: &mut Painter,
+		layout: &BindGroupLayout,
+		sampler: &wgpu::Sampler,
+	) -> Option<UniformTex2D> {
+		let format = painter.textures[self.0]
+			.texture
+			.format()
+			.remove_srgb_suffix();
+		self.reinterpreted_view(painter, format, layout, sampler)
 	}
 
 	pub fn destroy(self, painter: &mut Painter) {
@@ -166,31 +829,100 @@ pub struct UniformTex2D {
 	pub uniform: Uniform,
 }
 
+/// Sample type of the texture and binding type of the sampler in a texture layout.
+/// Comparison samplers, e.g. for shadow maps, sample depth textures as `texture_depth_2d`.
+fn sampler_binding_types(
+	sampler: &SamplerProps,
+) -> (wgpu::TextureSampleType, wgpu::SamplerBindingType) {
+	match sampler.compare {
+		Some(_) => (
+			wgpu::TextureSampleType::Depth,
+			wgpu::SamplerBindingType::Comparison,
+		),
+		None => (
+			wgpu::TextureSampleType::Float { filterable: true },
+			wgpu::SamplerBindingType::Filtering,
+		),
+	}
+}
+
+fn texture_layout(
+	painter: &Painter,
+	visibility: wgpu::ShaderStages,
+	view_dimension: wgpu::TextureViewDimension,
+	sampler: &SamplerProps,
+) -> wgpu::BindGroupLayout {
+	let (sample_type, sampler_type) = sampler_binding_types(sampler);
+	painter
+		.device
+		.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility,
+					ty: wgpu::BindingType::Texture {
+						multisampled: false,
+						view_dimension,
+						sample_type,
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility,
+					ty: wgpu::BindingType::Sampler(sampler_type),
+					count: None,
+				},
+			],
+			label: None,
+		})
+}
+
+fn texture_binding(
+	painter: &mut Painter,
+	layout: &BindGroupLayout,
+	texture: Texture,
+	view: Option<&wgpu::TextureView>,
+	sampler: &wgpu::Sampler,
+	kind: UniformKind,
+) -> Uniform {
+	let view = view.unwrap_or(&painter.textures[texture.0].view);
+
+	let binding = painter
+		.device
+		.create_bind_group(&wgpu::BindGroupDescriptor {
+			layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(sampler),
+				},
+			],
+			label: None,
+		});
+
+	painter.bindings.push(UniformStorage { binding, kind });
+
+	Uniform(painter.bindings.len() - 1)
+}
+
 impl UniformTex2D {
 	pub fn get_layout(painter: &Painter, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayout {
-		painter
-			.device
-			.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-				entries: &[
-					wgpu::BindGroupLayoutEntry {
-						binding: 0,
-						visibility,
-						ty: wgpu::BindingType::Texture {
-							multisampled: false,
-							view_dimension: wgpu::TextureViewDimension::D2,
-							sample_type: wgpu::TextureSampleType::Float { filterable: true },
-						},
-						count: None,
-					},
-					wgpu::BindGroupLayoutEntry {
-						binding: 1,
-						visibility,
-						ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-						count: None,
-					},
-				],
-				label: None,
-			})
+		Self::get_layout_with(painter, visibility, &default())
+	}
+
+	/// Layout for textures bound with samplers of the props,
+	/// e.g. comparison samplers on depth textures.
+	pub fn get_layout_with(
+		painter: &Painter,
+		visibility: wgpu::ShaderStages,
+		sampler: &SamplerProps,
+	) -> wgpu::BindGroupLayout {
+		texture_layout(painter, visibility, wgpu::TextureViewDimension::D2, sampler)
 	}
 
 	pub fn new(
@@ -199,29 +931,291 @@ impl UniformTex2D {
 		texture: Texture,
 		sampler: &wgpu::Sampler,
 	) -> Self {
-		let t = &painter.textures[texture.0];
+		Self::with_view(painter, layout, texture, None, sampler)
+	}
 
-		let binding = painter
-			.device
-			.create_bind_group(&wgpu::BindGroupDescriptor {
-				layout,
-				entries: &[
-					wgpu::BindGroupEntry {
-						binding: 0,
-						resource: wgpu::BindingResource::TextureView(&t.view),
-					},
-					wgpu::BindGroupEntry {
-						binding: 1,
-						resource: wgpu::BindingResource::Sampler(sampler),
-					},
-				],
-				label: None,
-			});
+	fn with_view(
+		painter: &mut Painter,
+		layout: &BindGroupLayout,
+		texture: Texture,
+		view: Option<&wgpu::TextureView>,
+		sampler: &wgpu::Sampler,
+	) -> Self {
+		let uniform = texture_binding(
+			painter,
+			layout,
+			texture,
+			view,
+			sampler,
+			UniformKind::Texture,
+		);
 
-		painter.bindings.push(binding);
+		UniformTex2D { texture, uniform }
+	}
+}
 
-		let uniform = Uniform(painter.bindings.len() - 1);
+/// Binding of a cubemap created with [`Texture::create_cube`], as `texture_cube` and sampler.
+#[derive(Clone, Copy)]
+pub struct UniformTexCube {
+	pub texture: Texture,
+	pub uniform: Uniform,
+}
 
-		UniformTex2D { texture, uniform }
+impl UniformTexCube {
+	pub fn get_layout(painter: &Painter, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayout {
+		Self::get_layout_with(painter, visibility, &default())
+	}
+
+	/// Layout for cubemaps bound with samplers of the props, see [`UniformTex2D::get_layout_with`].
+	pub fn get_layout_with(
+		painter: &Painter,
+		visibility: wgpu::ShaderStages,
+		sampler: &SamplerProps,
+	) -> wgpu::BindGroupLayout {
+		texture_layout(
+			painter,
+			visibility,
+			wgpu::TextureViewDimension::Cube,
+			sampler,
+		)
+	}
+
+	pub fn new(
+		painter: &mut Painter,
+		layout: &BindGroupLayout,
+		texture: Texture,
+		sampler: &wgpu::Sampler,
+	) -> Self {
+		let uniform = texture_binding(
+			painter,
+			layout,
+			texture,
+			None,
+			sampler,
+			UniformKind::TextureCube,
+		);
+
+		UniformTexCube { texture, uniform }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use wgpu::TextureFormat;
+
+	#[test]
+	fn srgb_view_format_pairs() {
+		assert_eq!(
+			srgb_view_formats(TextureFormat::Rgba8Unorm),
+			vec![TextureFormat::Rgba8UnormSrgb]
+		);
+		assert_eq!(
+			srgb_view_formats(TextureFormat::Bgra8UnormSrgb),
+			vec![TextureFormat::Bgra8Unorm]
+		);
+		assert!(srgb_view_formats(TextureFormat::Rgba16Float).is_empty());
+	}
+
+	#[test]
+	fn pixel_readback_padding() {
+		assert_eq!(padded_bytes_per_row(4), 256);
+		assert_eq!(padded_bytes_per_row(256), 256);
+		assert_eq!(padded_bytes_per_row(4 * 100), 512);
+
+		// 3x2 pixels, with each row padded to 256 bytes.
+		let row_bytes = 3 * 4;
+		let padded_row_bytes = padded_bytes_per_row(row_bytes);
+		let mut data = vec![0xaa; (padded_row_bytes * 2) as usize];
+		for row in 0..2 {
+			let start = (row * padded_row_bytes) as usize;
+			for (i, b) in data[start..start + row_bytes as usize]
+				.iter_mut()
+				.enumerate()
+			{
+				*b = (row * row_bytes) as u8 + i as u8;
+			}
+		}
+		let pixels = unpad_rows(&data, row_bytes, padded_row_bytes);
+		assert_eq!(pixels, (0..24).collect::<Vec<u8>>());
+
+		let mut pixels = vec![1, 2, 3, 4, 5, 6, 7, 8];
+		bgra_to_rgba(&mut pixels);
+		assert_eq!(pixels, vec![3, 2, 1, 4, 7, 6, 5, 8]);
+	}
+
+	#[test]
+	fn sampler_props() {
+		let linear = sampler_descriptor(&SamplerProps::default());
+		assert_eq!(linear.mag_filter, wgpu::FilterMode::Linear);
+		assert_eq!(linear.min_filter, wgpu::FilterMode::Linear);
+		assert_eq!(linear.compare, None);
+
+		let nearest = SamplerProps::nearest();
+		assert_ne!(nearest, SamplerProps::default());
+		let desc = sampler_descriptor(&nearest);
+		assert_eq!(desc.mag_filter, wgpu::FilterMode::Nearest);
+		assert_eq!(desc.min_filter, wgpu::FilterMode::Nearest);
+		assert_eq!(desc.address_mode_w, wgpu::AddressMode::ClampToEdge);
+
+		let desc = sampler_descriptor(&SamplerProps {
+			compare: Some(wgpu::CompareFunction::LessEqual),
+			..SamplerProps::nearest().repeat()
+		});
+		assert_eq!(desc.address_mode_u, wgpu::AddressMode::Repeat);
+		assert_eq!(desc.address_mode_w, wgpu::AddressMode::Repeat);
+		assert_eq!(desc.mag_filter, wgpu::FilterMode::Nearest);
+		assert_eq!(desc.compare, Some(wgpu::CompareFunction::LessEqual));
+
+		assert_eq!(
+			sampler_binding_types(&SamplerProps::nearest()),
+			(
+				wgpu::TextureSampleType::Float { filterable: true },
+				wgpu::SamplerBindingType::Filtering
+			)
+		);
+		let shadow = SamplerProps {
+			compare: Some(wgpu::CompareFunction::LessEqual),
+			..default()
+		};
+		assert_eq!(
+			sampler_binding_types(&shadow),
+			(
+				wgpu::TextureSampleType::Depth,
+				wgpu::SamplerBindingType::Comparison
+			)
+		);
+	}
+
+	#[test]
+	fn mip_levels() {
+		assert_eq!(full_mip_level_count(1, 1), 1);
+		assert_eq!(full_mip_level_count(256, 256), 9);
+		assert_eq!(full_mip_level_count(300, 20), 9);
+		assert_eq!(full_mip_level_count(0, 0), 1);
+
+		let props = Texture2DProps {
+			width: 64,
+			height: 16,
+			format: TextureFormat::Rgba8UnormSrgb,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING,
+			mip_levels: full_mip_level_count(64, 16),
+		};
+		assert_eq!(texture_2d_descriptor(&props, false, &[]).mip_level_count, 7);
+		assert_eq!(texture_2d_descriptor(&props, true, &[]).mip_level_count, 1);
+
+		let too_many = Texture2DProps {
+			mip_levels: 20,
+			..props
+		};
+		assert_eq!(
+			texture_2d_descriptor(&too_many, false, &[]).mip_level_count,
+			7
+		);
+		let none = Texture2DProps {
+			mip_levels: 0,
+			..too_many
+		};
+		assert_eq!(texture_2d_descriptor(&none, false, &[]).mip_level_count, 1);
+	}
+
+	#[test]
+	fn cube() {
+		let colors = [
+			[255, 0, 0, 255],
+			[0, 255, 255, 255],
+			[0, 255, 0, 255],
+			[255, 0, 255, 255],
+			[0, 0, 255, 255],
+			[255, 255, 0, 255],
+		];
+		let faces = colors.map(|color| [color; 4 * 4].concat());
+		let format = TextureFormat::Rgba8UnormSrgb;
+		for face in &faces {
+			assert_eq!(face.len() as u32, cube_row_len(4, format).unwrap() * 4);
+		}
+		assert_eq!(cube_row_len(4, TextureFormat::Rgba16Float), Ok(32));
+		assert_eq!(cube_row_len(4, TextureFormat::R32Float), Ok(16));
+		assert_eq!(
+			cube_row_len(4, TextureFormat::Depth24PlusStencil8),
+			Err(TextureError::UncopyableFormat(
+				TextureFormat::Depth24PlusStencil8
+			))
+		);
+
+		let texture = cube_descriptor(4, format);
+		assert_eq!(texture.size.depth_or_array_layers, 6);
+		assert_eq!(texture.dimension, wgpu::TextureDimension::D2);
+
+		let view = cube_view_descriptor();
+		assert_eq!(view.dimension, Some(wgpu::TextureViewDimension::Cube));
+		assert_eq!(view.array_layer_count, Some(6));
+	}
+
+	#[test]
+	fn checkerboard() {
+		let (a, b) = ([255, 0, 0, 255], [0, 0, 255, 255]);
+		let pixels = checkerboard_pixels(8, 4, a, b);
+		assert_eq!(pixels.len(), 8 * 8 * 4);
+
+		let pixel = |x: usize, y: usize| -> [u8; 4] {
+			let i = (y * 8 + x) * 4;
+			pixels[i..i + 4].try_into().unwrap()
+		};
+		// corners, and the tiles next to the top left one
+		assert_eq!(pixel(0, 0), a);
+		assert_eq!(pixel(1, 1), a);
+		assert_eq!(pixel(2, 0), b);
+		assert_eq!(pixel(0, 2), b);
+		assert_eq!(pixel(2, 2), a);
+		assert_eq!(pixel(7, 0), b);
+		assert_eq!(pixel(0, 7), b);
+		assert_eq!(pixel(7, 7), a);
+
+		// tile counts beyond the size fall back to one tile per pixel
+		assert_eq!(checkerboard_pixels(2, 10, a, b), [a, b, b, a].concat());
+		assert_eq!(checkerboard_pixels(2, 0, a, b), [a; 4].concat());
+	}
+
+	#[test]
+	fn gradient() {
+		let (from, to) = ([0, 0, 0, 255], [255, 100, 0, 255]);
+		let pixels = gradient_pixels(5, from, to, GradientDirection::Horizontal);
+		let pixel = |pixels: &[u8], x: usize, y: usize| -> [u8; 4] {
+			let i = (y * 5 + x) * 4;
+			pixels[i..i + 4].try_into().unwrap()
+		};
+		assert_eq!(pixel(&pixels, 0, 3), from);
+		assert_eq!(pixel(&pixels, 2, 3), [128, 50, 0, 255]);
+		assert_eq!(pixel(&pixels, 4, 0), to);
+
+		let pixels = gradient_pixels(5, from, to, GradientDirection::Vertical);
+		assert_eq!(pixel(&pixels, 4, 0), from);
+		assert_eq!(pixel(&pixels, 0, 4), to);
+
+		let pixels = gradient_pixels(5, from, to, GradientDirection::Diagonal);
+		assert_eq!(pixel(&pixels, 0, 0), from);
+		assert_eq!(pixel(&pixels, 4, 0), pixel(&pixels, 0, 4));
+		assert_eq!(pixel(&pixels, 4, 4), to);
+	}
+
+	/// 3x2 RGBA PNG, with a red top row and a half transparent blue bottom row.
+	const TINY_PNG: &[u8] = &[
+		137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 3, 0, 0, 0, 2, 8, 6,
+		0, 0, 0, 157, 116, 102, 26, 0, 0, 0, 19, 73, 68, 65, 84, 120, 218, 99, 248, 207, 192, 240,
+		31, 134, 25, 128, 184, 1, 134, 1, 142, 19, 10, 120, 182, 83, 143, 56, 0, 0, 0, 0, 73, 69,
+		78, 68, 174, 66, 96, 130,
+	];
+
+	#[test]
+	fn decode_image_bytes() {
+		let (width, height, pixels) = decode_rgba8(TINY_PNG).unwrap();
+		assert_eq!((width, height), (3, 2));
+		assert_eq!(pixels.len(), 3 * 2 * 4);
+		assert_eq!(pixels[..4], [255, 0, 0, 255]);
+		assert_eq!(pixels[20..], [0, 0, 255, 128]);
+
+		assert!(decode_rgba8(&TINY_PNG[..20]).is_err());
+		assert!(decode_rgba8(b"not an image").is_err());
 	}
 }
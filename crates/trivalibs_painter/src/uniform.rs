@@ -1,9 +1,35 @@
-use super::{painter::get_padded_size, Painter};
+use super::{frame_capture::CaptureEvent, painter::get_padded_size, Painter};
+use std::collections::BTreeMap;
 use trivalibs_core::glam::{Mat3, Mat3A, Vec3, Vec3A};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Uniform(pub(crate) usize);
 
+/// Resource bound by a [`Uniform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniformKind {
+	Buffer,
+	Texture,
+	/// Cubemap bound as `texture_cube`, with a layout incompatible with 2D textures.
+	TextureCube,
+}
+
+pub(crate) struct UniformStorage {
+	pub binding: wgpu::BindGroup,
+	pub kind: UniformKind,
+}
+
+/// Resolves a uniform map to its bind group indices and the kind of each bound uniform.
+pub(crate) fn uniform_bindings(
+	uniforms: &BTreeMap<u32, Uniform>,
+	kind_of: impl Fn(&Uniform) -> UniformKind,
+) -> Vec<(u32, UniformKind)> {
+	uniforms
+		.iter()
+		.map(|(index, uniform)| (*index, kind_of(uniform)))
+		.collect()
+}
+
 pub struct UniformBuffer<T> {
 	pub uniform: Uniform,
 	buffer: wgpu::Buffer,
@@ -54,7 +80,10 @@ where
 				label: None,
 			});
 
-		painter.bindings.push(bind_group);
+		painter.bindings.push(UniformStorage {
+			binding: bind_group,
+			kind: UniformKind::Buffer,
+		});
 
 		let binding = Uniform(painter.bindings.len() - 1);
 
@@ -70,6 +99,10 @@ where
 	}
 
 	pub fn update(&self, painter: &Painter, data: T) {
+		painter.capture_event(|| CaptureEvent::UniformUpdate {
+			uniform: self.uniform.0,
+			bytes: std::mem::size_of::<T>(),
+		});
 		painter
 			.queue
 			.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[data]));
@@ -105,3 +138,29 @@ impl UniformBuffer<Vec3U> {
 		self.update(painter, Vec3U(Vec3A::from(data)));
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn resolved_uniform_bindings() {
+		let kinds = [
+			UniformKind::Buffer,
+			UniformKind::Texture,
+			UniformKind::Buffer,
+		];
+		let uniforms = BTreeMap::from([(2, Uniform(0)), (0, Uniform(1)), (1, Uniform(2))]);
+
+		let bindings = uniform_bindings(&uniforms, |u| kinds[u.0]);
+
+		assert_eq!(
+			bindings,
+			vec![
+				(0, UniformKind::Texture),
+				(1, UniformKind::Buffer),
+				(2, UniformKind::Buffer),
+			]
+		);
+	}
+}
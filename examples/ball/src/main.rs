@@ -8,7 +8,6 @@ use trivalibs::{
 		painter::UniformType,
 		shade::ShadeProps,
 		sketch::SketchProps,
-		texture::Texture2DProps,
 		uniform::{Mat3U, UniformBuffer},
 		wgpu::{self, VertexFormat::*},
 		winit::event::{DeviceEvent, WindowEvent},
@@ -49,25 +48,9 @@ impl Default for App {
 
 impl CanvasApp<RenderState, ()> for App {
 	fn init(&self, p: &mut Painter) -> RenderState {
-		let tex_bytes = include_bytes!("../texture.png");
-		let mut reader = png::Decoder::new(std::io::Cursor::new(tex_bytes))
-			.read_info()
+		let texture = p
+			.texture_from_image_bytes(include_bytes!("../texture.png"), &default())
 			.unwrap();
-		// Allocate the output buffer.
-		let mut buf = vec![0; reader.output_buffer_size()];
-		// Read the next frame. An APNG might contain multiple frames.
-		let info = reader.next_frame(&mut buf).unwrap();
-		// Grab the bytes of the image.
-		let tex_rgba = &buf[..info.buffer_size()];
-
-		let texture = p.texture_2d_create(&Texture2DProps {
-			width: info.width,
-			height: info.height,
-			format: wgpu::TextureFormat::Rgba8UnormSrgb,
-			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-		});
-
-		texture.fill_2d(p, tex_rgba);
 
 		let uniform_type = p.uniform_type_buffered_vert();
 		let tex_type = p.uniform_type_tex_2d_frag();
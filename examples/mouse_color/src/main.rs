@@ -27,7 +27,7 @@ impl CanvasApp<(), ()> for App {
 		painter: &mut Painter,
 		_state: &(),
 	) -> std::result::Result<(), wgpu::SurfaceError> {
-		let frame = painter.surface.get_current_texture()?;
+		let frame = painter.surface().get_current_texture()?;
 
 		let view = frame
 			.texture
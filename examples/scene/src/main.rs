@@ -0,0 +1,29 @@
+use trivalibs::{
+	painter::{create_canvas_app, layer::Layer, scene::*, wgpu::SurfaceError, CanvasApp, Painter},
+	prelude::*,
+	rendering::{camera::PerspectiveCamera, transform::Transform},
+};
+
+struct App;
+impl CanvasApp<(Layer, Scene), ()> for App {
+	fn init(&self, p: &mut Painter) -> (Layer, Scene) {
+		let mut scene = Scene::new(p, PerspectiveCamera::default());
+		let cube = p.form_from_buffer(cube_buffer(1.0), default());
+		let red = vec4(1.0, 0.3, 0.2, 1.0);
+		scene.add(p, cube, Transform::from_xyz(-1.0, 0.0, -5.0), red);
+		scene.add(p, cube, Transform::from_xyz(1.0, 0.0, -5.0), Vec4::ONE);
+		(p.layer_create(&scene.layer_props()), scene)
+	}
+	fn update(&mut self, _p: &mut Painter, (_, scene): &mut (Layer, Scene), tpf: f32) {
+		scene.transform_mut(0).rotate_y(tpf);
+	}
+	fn render(&self, p: &mut Painter, (layer, scene): &(Layer, Scene)) -> Result<(), SurfaceError> {
+		scene.render(p, layer)?;
+		p.request_next_frame();
+		p.show(layer)
+	}
+}
+
+pub fn main() {
+	create_canvas_app(App).start();
+}
@@ -225,7 +225,7 @@ impl CanvasApp<RenderState, ()> for App {
 		painter: &mut Painter,
 		state: &RenderState,
 	) -> std::result::Result<(), wgpu::SurfaceError> {
-		let frame = painter.surface.get_current_texture()?;
+		let frame = painter.surface().get_current_texture()?;
 
 		let view = frame
 			.texture
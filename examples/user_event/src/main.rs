@@ -35,7 +35,7 @@ impl CanvasApp<RenderState, UserEvent> for App {
 					push_constant_ranges: &[],
 				});
 
-		// let capabilities = painter.surface.get_capabilities(&painter.adapter);
+		// let capabilities = painter.surface().get_capabilities(&painter.adapter);
 		// let format = capabilities.formats[0];
 
 		// Load the shaders from disk
@@ -82,7 +82,7 @@ impl CanvasApp<RenderState, UserEvent> for App {
 		painter: &mut Painter,
 		state: &RenderState,
 	) -> std::result::Result<(), wgpu::SurfaceError> {
-		let frame = painter.surface.get_current_texture()?;
+		let frame = painter.surface().get_current_texture()?;
 
 		let view = frame
 			.texture